@@ -0,0 +1,55 @@
+//! Diagnostics from the library's `tracing` spans and events, written to stderr.
+//! Verbosity comes from `-v`/`-q`, and `RUST_LOG` overrides it when set.
+
+use clap::{ArgAction, Args, ValueEnum};
+use std::io::IsTerminal;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Args)]
+pub struct LogArgs {
+    /// Log more detail: -v for summaries and timings, -vv per blob, -vvv everything
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Only log errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Format of log lines
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogFormat {
+    Text,
+    /// One JSON object per event, with span fields included
+    Json,
+}
+
+impl LogArgs {
+    fn level(&self) -> &'static str {
+        if self.quiet {
+            return "error";
+        }
+        match self.verbose {
+            0 => "warn",
+            1 => "info",
+            2 => "debug",
+            _ => "trace",
+        }
+    }
+}
+
+pub fn init(args: &LogArgs) {
+    // Verbosity applies to vekt itself; dependencies stay at warnings unless RUST_LOG says otherwise
+    let level = args.level();
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("warn,vekt={level},vekt_core={level}")));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal());
+    match args.log_format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().with_current_span(true).init(),
+    }
+}
@@ -1,12 +1,22 @@
+mod logging;
+mod progress;
+
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use vekt_core::ModelArchiver;
 use vekt_core::SafetensorFile;
-use vekt_core::remote::RemoteClient;
-use vekt_core::utils::{LockFile, find_vekt_root, get_store_path};
+use vekt_core::config::{LayeredSettings, SettingsLayer, Source, UserConfig};
+use vekt_core::progress::Hooks;
+use vekt_core::remote::{RemoteClient, RemoteGcOptions};
+use vekt_core::repository::Repository;
+use vekt_core::server::ServerOptions;
+use vekt_core::storage::RemoteConfig;
+use vekt_core::validation::{validate_key_prefix, validate_s3_url};
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
 
 #[derive(Parser)]
 #[command(name = "vekt")]
@@ -16,6 +26,8 @@ use clap::{Parser, Subcommand};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    #[command(flatten)]
+    log: logging::LogArgs,
 }
 
 #[derive(Subcommand)]
@@ -23,13 +35,21 @@ enum Commands {
     Init,
     Add {
         path: PathBuf,
-        #[arg(long, default_value = "false")]
+        /// Compress blobs with zstd (default: the `compression` setting)
+        #[arg(long)]
         compress: bool,
+        #[arg(long, conflicts_with = "compress")]
+        no_compress: bool,
     },
     Restore {
         path: PathBuf,
         #[arg(long)]
         layers: Option<String>,
+        /// Fetch blobs missing from the local store from this remote
+        #[arg(long)]
+        remote: Option<String>,
+        #[command(flatten)]
+        transfer: TransferArgs,
     },
     Diff {
         old: PathBuf,
@@ -40,75 +60,326 @@ enum Commands {
         action: RemoteCommand,
     },
     Pull {
-        #[arg(default_value = "origin")]
-        remote: String,
+        /// Remote or group (default: the `default_remote` setting, `origin` unless changed)
+        remote: Option<String>,
+        /// Manifest paths or glob patterns (default: manifests in the current directory)
+        manifests: Vec<String>,
+        /// Pull every manifest tracked anywhere in the repository
+        #[arg(long, conflicts_with = "manifests")]
+        all: bool,
+        /// Only update manifests; blobs are fetched on demand by `restore --remote`
+        #[arg(long)]
+        no_blobs: bool,
+        #[command(flatten)]
+        transfer: TransferArgs,
     },
     Push {
-        #[arg(default_value = "origin")]
+        /// Remote or group (default: the `default_remote` setting, `origin` unless changed)
+        remote: Option<String>,
+        /// Manifest paths or glob patterns (default: manifests in the current directory)
+        manifests: Vec<String>,
+        /// Push every manifest tracked anywhere in the repository
+        #[arg(long, conflicts_with = "manifests")]
+        all: bool,
+        /// Re-check every blob on the remote instead of trusting the local cache
+        #[arg(long)]
+        refresh: bool,
+        /// Overwrite remote manifests even if they changed since they were last pulled
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        transfer: TransferArgs,
+    },
+    /// Download a manifest from a remote, even if it does not exist locally
+    Fetch {
         remote: String,
+        manifest: String,
+        /// Also download the manifest's blobs
+        #[arg(long)]
+        blobs: bool,
+        #[command(flatten)]
+        transfer: TransferArgs,
+    },
+    /// Create a new repository populated with every manifest on a remote
+    Clone {
+        url: String,
+        dir: PathBuf,
+        /// Only fetch manifests; blobs are fetched on demand by `restore --remote`
+        #[arg(long)]
+        no_blobs: bool,
+        #[command(flatten)]
+        options: Box<RemoteOptions>,
     },
     Status,
-    Gc,
+    /// Read and change settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    Gc {
+        /// Delete unreferenced blobs on this remote instead of in the local store
+        #[arg(long)]
+        remote: Option<String>,
+        /// List what would be deleted without deleting anything
+        #[arg(long, requires = "remote")]
+        dry_run: bool,
+        /// Keep unreferenced remote blobs uploaded within this many hours
+        /// (default: the `gc_grace_hours` setting, 24 unless changed)
+        #[arg(long, requires = "remote")]
+        grace_hours: Option<u64>,
+        /// Also keep remote blobs referenced by manifests in this repository
+        #[arg(long, requires = "remote")]
+        keep_local: bool,
+    },
+    /// Serve this repository over HTTP for `vekt://` remotes
+    Serve {
+        /// Address to listen on. The server has no authentication, so it stays on
+        /// localhost unless another address is given.
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        bind: String,
+        /// Reject pushes and deletions
+        #[arg(long)]
+        read_only: bool,
+    },
+    /// Mount manifests as read-only safetensors files, assembled from blobs on read
+    #[cfg(feature = "mount")]
+    Mount {
+        /// Empty directory to mount on
+        dir: PathBuf,
+        /// Fetch blobs missing from the local store from this remote or group when read
+        #[arg(long)]
+        remote: Option<String>,
+        #[command(flatten)]
+        transfer: TransferArgs,
+    },
+}
+
+/// Connection settings shared by `remote add` and `clone`
+#[derive(Args)]
+struct RemoteOptions {
+    /// Custom S3 endpoint for MinIO, Ceph, R2 or on-prem gateways
+    #[arg(long)]
+    endpoint: Option<String>,
+    /// S3 region (defaults to AWS_REGION, then us-east-1)
+    #[arg(long)]
+    region: Option<String>,
+    /// Force path-style (true) or virtual-host (false) addressing
+    #[arg(long)]
+    path_style: Option<bool>,
+    /// Key prefix inside the bucket
+    #[arg(long)]
+    prefix: Option<String>,
+    /// Named profile from the AWS credentials file
+    #[arg(long)]
+    profile: Option<String>,
+    /// AWS-style credentials file to use instead of ~/.aws/credentials
+    #[arg(long)]
+    credentials_file: Option<PathBuf>,
+    /// Command printing credentials as AWS credential_process JSON
+    #[arg(long)]
+    credential_helper: Option<String>,
+    /// Upload blobs of at least this many bytes in resumable multipart chunks
+    #[arg(long)]
+    multipart_threshold: Option<u64>,
+    /// Size of each multipart chunk in bytes, 5 MiB to 5 GiB
+    #[arg(long)]
+    multipart_chunk_size: Option<u64>,
+    #[command(flatten)]
+    transfer: TransferArgs,
+}
+
+/// Transfer settings; saved by `remote add`, or overriding the saved ones for one command
+#[derive(Args)]
+struct TransferArgs {
+    /// Number of blobs transferred at the same time
+    #[arg(long)]
+    concurrency: Option<usize>,
+    /// Retries for transient failures, with exponential backoff
+    #[arg(long)]
+    retries: Option<u32>,
+    /// Timeout for each request in seconds
+    #[arg(long)]
+    timeout: Option<u64>,
+    /// Bandwidth cap in bytes per second; accepts K, M and G suffixes (e.g. 20M)
+    #[arg(long, value_parser = parse_rate)]
+    limit_rate: Option<u64>,
+}
+
+impl TransferArgs {
+    fn apply(&self, config: &mut RemoteConfig) {
+        if let Some(concurrency) = self.concurrency {
+            config.concurrency = Some(concurrency);
+        }
+        if let Some(retries) = self.retries {
+            config.retries = Some(retries);
+        }
+        if let Some(timeout) = self.timeout {
+            config.timeout_secs = Some(timeout);
+        }
+        if let Some(limit) = self.limit_rate {
+            config.bandwidth_limit = Some(limit);
+        }
+    }
+
+    /// Client for a configured remote with these overrides applied
+    fn client(
+        &self,
+        repo: &Repository,
+        config: &RemoteConfig,
+    ) -> vekt_core::errors::Result<RemoteClient> {
+        let mut config = config.clone();
+        self.apply(&mut config);
+        RemoteClient::from_config(repo, &config)
+    }
+
+    /// Read client trying each remote in order, with these overrides applied to all of them
+    fn fallback_client(
+        &self,
+        repo: &Repository,
+        remotes: &[(&str, &RemoteConfig)],
+    ) -> vekt_core::errors::Result<RemoteClient> {
+        let configs: Vec<(&str, RemoteConfig)> = remotes
+            .iter()
+            .map(|(name, config)| {
+                let mut config = (*config).clone();
+                self.apply(&mut config);
+                (*name, config)
+            })
+            .collect();
+        let members: Vec<(&str, &RemoteConfig)> = configs
+            .iter()
+            .map(|(name, config)| (*name, config))
+            .collect();
+        RemoteClient::with_fallbacks(repo, &members)
+    }
+}
+
+impl RemoteOptions {
+    /// Builds the remote config, rejecting malformed S3 URLs and prefixes before they are saved
+    fn to_config(&self, url: &str) -> vekt_core::errors::Result<RemoteConfig> {
+        if url.starts_with("s3://") {
+            validate_s3_url(url)?;
+            if let Some(prefix) = &self.prefix {
+                validate_key_prefix(prefix)?;
+            }
+        }
+        let mut config = RemoteConfig {
+            endpoint: self.endpoint.clone(),
+            region: self.region.clone(),
+            path_style: self.path_style,
+            prefix: self.prefix.clone(),
+            profile: self.profile.clone(),
+            credentials_file: self.credentials_file.clone(),
+            credential_helper: self.credential_helper.clone(),
+            multipart_threshold: self.multipart_threshold,
+            multipart_chunk_size: self.multipart_chunk_size,
+            ..RemoteConfig::new(url)
+        };
+        self.transfer.apply(&mut config);
+        Ok(config)
+    }
 }
 
 #[derive(Subcommand)]
 enum RemoteCommand {
-    Add { name: String, url: String },
+    Add {
+        name: String,
+        url: String,
+        #[command(flatten)]
+        options: Box<RemoteOptions>,
+    },
+    List,
+    /// List the manifests stored on a remote
+    Ls {
+        name: String,
+    },
+    /// Define a group of remotes: pushes go to every member,
+    /// reads try members in the order given
+    Group {
+        name: String,
+        #[arg(required = true)]
+        members: Vec<String>,
+    },
+    /// Remove a remote or a group
+    Remove {
+        name: String,
+    },
+    /// Abort multipart uploads that were interrupted and never resumed
+    PruneUploads {
+        name: String,
+        /// Only abort uploads started at least this many hours ago
+        #[arg(long, default_value = "24")]
+        older_than_hours: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the value of a setting in effect here
+    Get { key: String },
+    /// Change a setting in the repository config, or the user config with --user
+    Set {
+        key: String,
+        value: String,
+        #[arg(long)]
+        user: bool,
+    },
+    /// Remove a setting so the next layer's value applies
+    Unset {
+        key: String,
+        #[arg(long)]
+        user: bool,
+    },
+    /// List every setting with its value and where it is set
     List,
-    Remove { name: String },
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    logging::init(&cli.log);
 
-    // Check if repository is initialized for all commands except Init
-    if !matches!(cli.command, Commands::Init) && find_vekt_root().is_none() {
+    match &cli.command {
+        Commands::Init => return init_command(),
+        // Settings can be read and changed outside a repository with --user
+        Commands::Config { action } => return config_command(action),
+        Commands::Clone {
+            url,
+            dir,
+            no_blobs,
+            options,
+        } => return clone_command(url, dir, *no_blobs, options).await,
+        _ => {}
+    }
+
+    // Every other command works on an existing repository
+    let Ok(repo) = Repository::discover() else {
         eprintln!("Error: Not a vekt repository (or any parent up to mount point)");
         eprintln!("Run 'vekt init' first to initialize a repository.");
         std::process::exit(1);
-    }
+    };
 
     match &cli.command {
-        Commands::Init => {
-            let current_dir = std::env::current_dir()?;
-            let vekt_dir = current_dir.join(".vekt");
-
-            if vekt_dir.exists() {
-                println!(
-                    "vekt repository already exists in {}",
-                    current_dir.display()
-                );
-                return Ok(());
-            }
-
-            std::fs::create_dir_all(&vekt_dir)?;
-            std::fs::create_dir_all(vekt_dir.join("blobs"))?;
-
-            // Create default config
-            let config = vekt_core::storage::VektConfig::default();
-            config.save()?;
-
-            // Create .gitignore to ignore everything in .vekt
-            let gitignore_content = "*\n";
-            std::fs::write(vekt_dir.join(".gitignore"), gitignore_content)?;
-            println!(
-                "Initialized empty vekt repository in {}",
-                vekt_dir.display()
-            );
-            println!("\nvekt tracks machine learning models at the tensor level.");
-            println!("Use 'vekt add <model.safetensors>' to start tracking a model.");
+        Commands::Init | Commands::Clone { .. } | Commands::Config { .. } => {
+            unreachable!("handled above")
         }
 
-        Commands::Add { path, compress } => {
-            let _lock = LockFile::lock()?;
+        Commands::Add {
+            path,
+            compress,
+            no_compress,
+        } => {
+            let _lock = repo.lock()?;
 
             let path_str = path.to_str().unwrap();
 
-            print!("Adding file: {} ... ", path_str);
+            println!("Adding file: {}", path_str);
 
-            let file = SafetensorFile::open(path_str)?;
-            let manifest = file.process(true)?;
+            let mut file = SafetensorFile::open(path_str)?;
+            if *compress || *no_compress {
+                file = file.with_compression(*compress);
+            }
+            let manifest = file.process_with(&repo, true, &progress::hooks())?;
             let manifest_json = serde_json::to_string_pretty(&manifest)?;
 
             let output_path = path.with_extension("vekt.json");
@@ -116,14 +387,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             output_file.write_all(manifest_json.as_bytes())?;
 
-            println!("Done! Manifest saved to {}", output_path.to_str().unwrap());
-
-            let store_loc = get_store_path();
-            println!("Blobs stored in {}", store_loc.to_str().unwrap());
+            println!("Manifest saved to {}", output_path.to_str().unwrap());
 
-            if *compress {
-                println!("Note: Compression is enabled but not yet fully integrated. Coming soon!");
-            }
+            println!("Blobs stored in {}", repo.store().display());
         }
 
         Commands::Diff { old, new } => {
@@ -135,10 +401,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let new_manifest: vekt_core::storage::VektManifest =
                 serde_json::from_reader(std::io::BufReader::new(new_file))?;
 
-            old_manifest.print_diff(&new_manifest);
+            print_diff(&old_manifest, &new_manifest);
         }
 
-        Commands::Restore { path, layers } => {
+        Commands::Restore {
+            path,
+            layers,
+            remote,
+            transfer,
+        } => {
             let file = File::open(path).expect("Failed to open manifest file");
             let reader = std::io::BufReader::new(file);
             let manifest: vekt_core::storage::VektManifest =
@@ -158,111 +429,224 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("Partial restore: filtering layers containing '{}'", l);
             }
 
-            match manifest.restore(&output_path, layers.as_deref()) {
+            let hooks = progress::hooks();
+            if let Some(remote) = remote {
+                let config = repo.config()?;
+                let remotes = match config.resolve_remotes(remote) {
+                    Ok(remotes) => remotes,
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+
+                let _lock = repo.lock()?;
+                let client = transfer
+                    .fallback_client(&repo, &remotes)?
+                    .with_hooks(hooks.clone());
+                let fetched = client.fetch_missing(&manifest, layers.as_deref()).await?;
+                if fetched > 0 {
+                    println!("Fetched {} missing blobs from '{}'", fetched, remote);
+                }
+            }
+
+            match manifest.restore_with(&repo, &output_path, layers.as_deref(), &hooks) {
                 Ok(_) => println!("Restoration complete!"),
                 Err(e) => eprintln!("Error: {}", e),
             }
         }
 
-        Commands::Pull { remote } => {
-            let _lock = LockFile::lock()?;
-            let config = vekt_core::storage::VektConfig::load()?;
-            if let Some(url) = config.remotes.get(remote) {
-                println!("Pulling from remote '{}' at URL '{}'", remote, url);
+        Commands::Pull {
+            remote,
+            manifests,
+            all,
+            no_blobs,
+            transfer,
+        } => {
+            let lock = repo.lock()?;
+            let config = repo.config()?;
+            let remote = &remote_or_default(&repo, remote)?;
+            if let Ok(remotes) = config.resolve_remotes(remote) {
+                for (name, remote_config) in &remotes {
+                    println!(
+                        "Pulling from remote '{}' at URL '{}'",
+                        name, remote_config.url
+                    );
+                }
 
-                let client = RemoteClient::new(url)?;
+                let client = transfer
+                    .fallback_client(&repo, &remotes)?
+                    .with_hooks(progress::hooks());
 
                 // Validate access before attempting operations
-                println!("Validating S3 bucket access...");
+                println!("Validating remote access...");
                 if let Err(e) = client.validate_access().await {
-                    eprintln!("Failed to validate S3 access: {}", e);
+                    eprintln!("Failed to validate remote access: {}", e);
                     return Err(e.into());
                 }
 
-                let paths = std::fs::read_dir(".")?;
-
-                for entry in paths {
-                    let entry = entry?;
-                    let path = entry.path();
-                    if let Some(name) = path.file_name().and_then(|n| n.to_str())
-                        && name.ends_with(".vekt.json")
-                    {
-                        println!("Processing manifest: {}", name);
-                        match client.pull(name).await {
-                            Ok(manifest) => {
-                                // Update local manifest file
-                                let json = serde_json::to_string_pretty(&manifest)?;
-                                let mut f = File::create(&path)?;
-                                f.write_all(json.as_bytes())?;
-                                println!("Successfully updated {}", name);
+                let selected =
+                    select_manifests(repo.root(), manifests, *all, &std::env::current_dir()?)?;
+                let names: Vec<&str> = selected.iter().map(|(_, name)| name.as_str()).collect();
+                let results = client.pull_all(&names, !*no_blobs).await?;
+
+                // One failing manifest doesn't stop the others, but still fails the command
+                let mut failed = 0;
+                for ((path, _), (name, result)) in selected.iter().zip(results) {
+                    match result {
+                        Ok(manifest) => {
+                            // Update local manifest file
+                            if let Some(parent) = path.parent() {
+                                std::fs::create_dir_all(parent)?;
                             }
-                            Err(e) => eprintln!("Failed to pull {}: {}", name, e),
+                            let json = serde_json::to_string_pretty(&manifest)?;
+                            let mut f = File::create(path)?;
+                            f.write_all(json.as_bytes())?;
+                            println!("Successfully updated {}", name);
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to pull {}: {}", name, e);
+                            failed += 1;
                         }
                     }
                 }
+                if failed > 0 {
+                    // Exiting skips destructors, so release the lock first
+                    drop(lock);
+                    std::process::exit(1);
+                }
             } else {
                 println!("Remote '{}' not found", remote);
             }
         }
-        Commands::Push { remote } => {
-            let _lock = LockFile::lock()?;
-            let config = vekt_core::storage::VektConfig::load()?;
-            if let Some(url) = config.remotes.get(remote) {
-                println!("Pushing to remote '{}' at URL '{}'", remote, url);
-
-                let client = RemoteClient::new(url)?;
+        Commands::Push {
+            remote,
+            manifests,
+            all,
+            refresh,
+            force,
+            transfer,
+        } => {
+            let lock = repo.lock()?;
+            let config = repo.config()?;
+            let remote = &remote_or_default(&repo, remote)?;
+            let Ok(remotes) = config.resolve_remotes(remote) else {
+                println!("Remote '{}' not found", remote);
+                return Ok(());
+            };
 
-                // Validate access before attempting operations
-                println!("Validating S3 bucket access...");
-                if let Err(e) = client.validate_access().await {
-                    eprintln!("Failed to validate S3 access: {}", e);
-                    return Err(e.into());
-                }
+            let mut loaded = Vec::new();
+            for (path, name) in
+                select_manifests(repo.root(), manifests, *all, &std::env::current_dir()?)?
+            {
+                let f = File::open(&path)?;
+                let reader = std::io::BufReader::new(f);
+                let manifest: vekt_core::storage::VektManifest = serde_json::from_reader(reader)?;
+                loaded.push((name, manifest));
+            }
+            let batch: Vec<(&str, &vekt_core::storage::VektManifest)> = loaded
+                .iter()
+                .map(|(name, manifest)| (name.as_str(), manifest))
+                .collect();
 
-                let paths = std::fs::read_dir(".")?;
-
-                for entry in paths {
-                    let entry = entry?;
-                    let path = entry.path();
-                    if let Some(name) = path.file_name().and_then(|n| n.to_str())
-                        && name.ends_with(".vekt.json")
-                    {
-                        println!("Pushing manifest: {}", name);
-
-                        // Load manifest
-                        let f = File::open(&path)?;
-                        let reader = std::io::BufReader::new(f);
-                        let manifest: vekt_core::storage::VektManifest =
-                            serde_json::from_reader(reader)?;
-
-                        match client.push(&manifest, name).await {
-                            Ok(_) => println!("Successfully pushed {}", name),
-                            Err(e) => eprintln!("Failed to push {}: {}", name, e),
-                        }
-                    }
+            // Each member of a group gets a full copy; a failing remote doesn't stop the others
+            let hooks = progress::hooks();
+            let mut failed = 0;
+            for (name, remote_config) in &remotes {
+                println!(
+                    "Pushing to remote '{}' at URL '{}'",
+                    name, remote_config.url
+                );
+                if let Err(e) = push_to_remote(
+                    &repo,
+                    transfer,
+                    remote_config,
+                    &batch,
+                    *refresh,
+                    *force,
+                    &hooks,
+                )
+                .await
+                {
+                    eprintln!("Failed to push to remote '{}': {}", name, e);
+                    failed += 1;
                 }
-            } else {
-                println!("Remote '{}' not found", remote);
+            }
+            if failed > 0 {
+                // Exiting skips destructors, so release the lock first
+                drop(lock);
+                std::process::exit(1);
             }
         }
+        Commands::Fetch {
+            remote,
+            manifest,
+            blobs,
+            transfer,
+        } => {
+            let _lock = repo.lock()?;
+            let config = repo.config()?;
+            let Ok(remotes) = config.resolve_remotes(remote) else {
+                println!("Remote '{}' not found", remote);
+                return Ok(());
+            };
+
+            let client = transfer
+                .fallback_client(&repo, &remotes)?
+                .with_hooks(progress::hooks());
+            client.fetch(manifest, Path::new("."), *blobs).await?;
+            println!("Fetched {} from '{}'", manifest, remote);
+        }
+
         Commands::Status => {
-            let config = vekt_core::storage::VektConfig::load()?;
+            let config = repo.config()?;
             println!("vekt Configuration Status:");
             println!("Remotes:");
-            for (name, url) in &config.remotes {
-                println!("  {} -> {}", name, url);
+            for (name, remote) in &config.remotes {
+                println!("  {} -> {}", name, remote.url);
+            }
+        }
+
+        Commands::Gc {
+            remote: Some(remote),
+            dry_run,
+            grace_hours,
+            keep_local,
+        } => {
+            let config = repo.config()?;
+            let Some(remote_config) = config.remotes.get(remote) else {
+                println!("Remote '{}' not found", remote);
+                return Ok(());
+            };
+
+            let mut options = RemoteGcOptions {
+                dry_run: *dry_run,
+                grace_period: std::time::Duration::from_secs(
+                    grace_hours.unwrap_or(repo.settings()?.gc_grace_hours) * 3600,
+                ),
+                ..Default::default()
+            };
+            if *keep_local {
+                options.retain = vekt_core::gc::referenced_hashes(repo.root())?;
             }
+
+            println!("Running Garbage Collection on remote '{}'...", remote);
+            let client = RemoteClient::from_config(&repo, remote_config)?;
+            let stats = client.gc(&options).await?;
+            let verb = if *dry_run { "Would delete" } else { "Deleted" };
+            println!(
+                "GC Complete. {}: {} ({} bytes), Kept: {}, Within grace period: {}",
+                verb, stats.deleted, stats.deleted_bytes, stats.kept, stats.recent
+            );
         }
 
-        Commands::Gc => {
-            let _lock = LockFile::lock()?;
+        Commands::Gc { remote: None, .. } => {
             println!(
                 "Running Garbage Collection on {}...",
-                get_store_path().display()
+                repo.store().display()
             );
 
-            let root = find_vekt_root().unwrap_or_else(|| PathBuf::from("."));
-            match vekt_core::gc::run_gc(&root) {
+            match vekt_core::gc::run_gc(&repo) {
                 Ok(stats) => {
                     println!(
                         "GC Complete. Deleted: {}, Kept: {}",
@@ -273,32 +657,601 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        Commands::Serve { bind, read_only } => {
+            let listener = tokio::net::TcpListener::bind(bind).await?;
+            let addr = listener.local_addr()?;
+            println!("Serving {} on http://{}", repo.root().display(), addr);
+            println!(
+                "Add it as a remote with: vekt remote add <name> vekt://{}",
+                addr
+            );
+            println!("Manifests as safetensors files: http://{}/files", addr);
+            if *read_only {
+                println!("Read-only: pushes and deletions are rejected");
+            } else if !addr.ip().is_loopback() {
+                eprintln!(
+                    "Warning: the server has no authentication; anyone who can reach {} can push and delete manifests. Consider --read-only.",
+                    addr
+                );
+            }
+            let options = ServerOptions {
+                read_only: *read_only,
+            };
+            vekt_core::server::serve(listener, repo, options).await?;
+        }
+
+        #[cfg(feature = "mount")]
+        Commands::Mount {
+            dir,
+            remote,
+            transfer,
+        } => {
+            let mut fs = vekt_core::mount::VektFs::new(&repo)?;
+            if let Some(remote) = remote {
+                let config = repo.config()?;
+                let Ok(remotes) = config.resolve_remotes(remote) else {
+                    println!("Remote '{}' not found", remote);
+                    return Ok(());
+                };
+                let client = transfer.fallback_client(&repo, &remotes)?;
+                fs = fs.with_remote(client, tokio::runtime::Handle::current());
+            }
+
+            println!(
+                "Mounted {} manifests from {} on {}",
+                fs.file_count(),
+                repo.root().display(),
+                dir.display()
+            );
+            println!("Unmount with: fusermount -u {}", dir.display());
+            let dir = dir.clone();
+            // The filesystem loop blocks, and lazy fetches need the runtime free to run them
+            tokio::task::spawn_blocking(move || vekt_core::mount::mount(fs, &dir)).await??;
+        }
+
         // Remote management commands
         Commands::Remote { action } => {
-            let mut config = vekt_core::storage::VektConfig::load()?;
+            let mut config = repo.config()?;
 
             match action {
-                RemoteCommand::Add { name, url } => {
-                    config.add_remote(name.clone(), url.clone());
-                    config.save()?;
+                RemoteCommand::Add { name, url, options } => {
+                    let remote = options.to_config(url)?;
+                    config.add_remote(name.clone(), remote);
+                    repo.save_config(&config)?;
                     println!("Added remote '{}' with URL '{}'", name, url);
                 }
                 RemoteCommand::List => {
                     println!("Configured remotes:");
-                    for (name, url) in &config.remotes {
-                        println!("{} -> {}", name, url);
+                    for (name, remote) in &config.remotes {
+                        match &remote.endpoint {
+                            Some(endpoint) => {
+                                println!("{} -> {} (endpoint {})", name, remote.url, endpoint)
+                            }
+                            None => println!("{} -> {}", name, remote.url),
+                        }
+                    }
+                    for (name, members) in &config.groups {
+                        println!("{} -> group [{}]", name, members.join(", "));
                     }
                 }
+                RemoteCommand::Ls { name } => {
+                    let Ok(remotes) = config.resolve_remotes(name) else {
+                        println!("Remote '{}' not found", name);
+                        return Ok(());
+                    };
+                    let client = RemoteClient::with_fallbacks(&repo, &remotes)?;
+                    let manifests = match client.list_manifests().await {
+                        Err(e @ vekt_core::errors::VektError::Unsupported(_)) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                        result => result?,
+                    };
+                    if manifests.is_empty() {
+                        println!("No manifests on '{}'", name);
+                    }
+                    for manifest in manifests {
+                        println!(
+                            "{:>12}  {:<25}  {}",
+                            manifest.size,
+                            format_timestamp(manifest.last_modified),
+                            manifest.name
+                        );
+                    }
+                }
+                RemoteCommand::Group { name, members } => {
+                    config.add_group(name.clone(), members.clone())?;
+                    repo.save_config(&config)?;
+                    println!("Added group '{}' with remotes {}", name, members.join(", "));
+                }
                 RemoteCommand::Remove { name } => {
                     if config.remotes.remove(name).is_some() {
-                        config.save()?;
+                        // Groups keep working with their remaining members
+                        for members in config.groups.values_mut() {
+                            members.retain(|member| member != name);
+                        }
+                        config.groups.retain(|_, members| !members.is_empty());
+                        repo.save_config(&config)?;
                         println!("Removed remote '{}'", name);
+                    } else if config.groups.remove(name).is_some() {
+                        repo.save_config(&config)?;
+                        println!("Removed group '{}'", name);
                     } else {
                         println!("Remote '{}' not found", name);
                     }
                 }
+                RemoteCommand::PruneUploads {
+                    name,
+                    older_than_hours,
+                } => {
+                    let Some(remote_config) = config.remotes.get(name) else {
+                        println!("Remote '{}' not found", name);
+                        return Ok(());
+                    };
+                    let client = RemoteClient::from_config(&repo, remote_config)?;
+                    let older_than = std::time::Duration::from_secs(older_than_hours * 3600);
+                    let aborted = client.remote().abort_incomplete_uploads(older_than).await?;
+                    println!("Aborted {} incomplete uploads on '{}'", aborted, name);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `vekt init` in the working directory
+fn init_command() -> Result<(), Box<dyn std::error::Error>> {
+    let current_dir = std::env::current_dir()?;
+    let vekt_dir = current_dir.join(".vekt");
+
+    if vekt_dir.exists() {
+        println!(
+            "vekt repository already exists in {}",
+            current_dir.display()
+        );
+        return Ok(());
+    }
+
+    init_repo(&current_dir)?;
+    println!(
+        "Initialized empty vekt repository in {}",
+        vekt_dir.display()
+    );
+    println!("\nvekt tracks machine learning models at the tensor level.");
+    println!("Use 'vekt add <model.safetensors>' to start tracking a model.");
+    Ok(())
+}
+
+/// The remote named on the command line, or the `default_remote` setting
+fn remote_or_default(
+    repo: &Repository,
+    remote: &Option<String>,
+) -> vekt_core::errors::Result<String> {
+    match remote {
+        Some(remote) => Ok(remote.clone()),
+        None => Ok(repo.settings()?.default_remote),
+    }
+}
+
+/// `vekt config`: settings from the repository, if there is one, the user config
+/// file and the environment
+fn config_command(action: &ConfigCommand) -> Result<(), Box<dyn std::error::Error>> {
+    let repo = Repository::discover().ok();
+    let writes_repo = matches!(
+        action,
+        ConfigCommand::Set { user: false, .. } | ConfigCommand::Unset { user: false, .. }
+    );
+    if writes_repo && repo.is_none() {
+        eprintln!("Error: Not a vekt repository; use --user to change the user config");
+        std::process::exit(1);
+    }
+
+    match action {
+        ConfigCommand::Get { key } => {
+            let (value, _) = layered_settings(repo.as_ref())?.get(key)?;
+            println!("{}", value);
+        }
+        ConfigCommand::List => {
+            let settings = layered_settings(repo.as_ref())?;
+            for key in SettingsLayer::KEYS {
+                let (value, source) = settings.get(key)?;
+                println!("{:<18} {:<12} ({})", key, value, source);
+            }
+        }
+        ConfigCommand::Set { key, value, user } => {
+            let written = edit_settings(repo.as_ref(), *user, |settings| settings.set(key, value))?;
+            println!("Set {} to {} in the {} config", key, value, written);
+
+            // Say so when a higher layer still decides the value
+            let (value, source) = layered_settings(repo.as_ref())?.get(key)?;
+            if source != written {
+                let origin = match source {
+                    Source::Environment => vekt_core::config::env_var(key),
+                    _ => format!("the {} config", source),
+                };
+                eprintln!("Warning: {} is still {}, set by {}", key, value, origin);
+            }
+        }
+        ConfigCommand::Unset { key, user } => {
+            let written = edit_settings(repo.as_ref(), *user, |settings| settings.unset(key))?;
+            println!("Unset {} in the {} config", key, written);
+        }
+    }
+    Ok(())
+}
+
+/// Changes the user config if `user` is set, otherwise the repository's,
+/// returning which one was written
+fn edit_settings(
+    repo: Option<&Repository>,
+    user: bool,
+    edit: impl FnOnce(&mut SettingsLayer) -> vekt_core::errors::Result<()>,
+) -> vekt_core::errors::Result<Source> {
+    match repo {
+        Some(repo) if !user => {
+            let mut config = repo.config()?;
+            edit(&mut config.settings)?;
+            repo.save_config(&config)?;
+            Ok(Source::Repository)
+        }
+        _ => {
+            let mut config = UserConfig::load()?;
+            edit(&mut config.settings)?;
+            config.save()?;
+            Ok(Source::User)
+        }
+    }
+}
+
+fn layered_settings(repo: Option<&Repository>) -> vekt_core::errors::Result<LayeredSettings> {
+    match repo {
+        Some(repo) => repo.layered_settings(),
+        None => Ok(LayeredSettings {
+            user: UserConfig::load()?.settings,
+            env: SettingsLayer::from_env()?,
+            ..Default::default()
+        }),
+    }
+}
+
+/// `vekt clone`: a new repository in `dir` with `url` as its `origin` remote
+async fn clone_command(
+    url: &str,
+    dir: &Path,
+    no_blobs: bool,
+    options: &RemoteOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let remote_config = options.to_config(url)?;
+    if dir.exists() && std::fs::read_dir(dir)?.next().is_some() {
+        eprintln!(
+            "Error: destination '{}' already exists and is not empty",
+            dir.display()
+        );
+        std::process::exit(1);
+    }
+
+    let repo = init_repo(dir)?;
+    let mut config = repo.config()?;
+    config.add_remote("origin".to_string(), remote_config.clone());
+    repo.save_config(&config)?;
+    println!("Cloning from '{}' into {}", url, dir.display());
+
+    let _lock = repo.lock()?;
+    let client = RemoteClient::from_config(&repo, &remote_config)?.with_hooks(progress::hooks());
+    println!("Validating remote access...");
+    client.validate_access().await?;
+
+    let manifests = match client.list_manifests().await {
+        // Plain HTTP remotes can't be listed; the repository still works for fetch by name
+        Err(e @ vekt_core::errors::VektError::Unsupported(_)) => {
+            eprintln!("Warning: {}", e);
+            println!("Created an empty repository with origin '{}'", url);
+            println!("Fetch manifests by name with `vekt fetch origin <manifest> --blobs`");
+            return Ok(());
+        }
+        result => result?,
+    };
+    for manifest in &manifests {
+        println!("Fetching manifest: {}", manifest.name);
+        client.fetch(&manifest.name, repo.root(), !no_blobs).await?;
+    }
+    println!("Cloned {} manifests", manifests.len());
+    Ok(())
+}
+
+/// Creates the `.vekt` directory layout under `root` with an empty config
+fn init_repo(root: &Path) -> vekt_core::errors::Result<Repository> {
+    let repo = Repository::init(root)?;
+    repo.save_config(&vekt_core::storage::VektConfig::default())?;
+    Ok(repo)
+}
+
+/// Prints a human-readable comparison of two manifests
+fn print_diff(old: &vekt_core::storage::VektManifest, new: &vekt_core::storage::VektManifest) {
+    let comparison = old.diff(new);
+    let diff = &comparison.tensor_diff;
+
+    println!("\nManifest Comparison:");
+    println!("==================");
+
+    if !diff.added.is_empty() {
+        println!("\nAdded Tensors ({}):", diff.added.len());
+        for name in &diff.added {
+            let tensor = &new.tensors[name];
+            println!(
+                "  + {} [shape: {:?}, dtype: {}, hash: {}]",
+                name,
+                tensor.shape,
+                tensor.dtype,
+                &tensor.hash[..8]
+            );
+        }
+    }
+
+    if !diff.removed.is_empty() {
+        println!("\nRemoved Tensors ({}):", diff.removed.len());
+        for name in &diff.removed {
+            let tensor = &old.tensors[name];
+            println!(
+                "  - {} [shape: {:?}, dtype: {}, hash: {}]",
+                name,
+                tensor.shape,
+                tensor.dtype,
+                &tensor.hash[..8]
+            );
+        }
+    }
+
+    if !diff.modified.is_empty() {
+        println!("\nModified Tensors ({}):", diff.modified.len());
+        for name in &diff.modified {
+            let old_tensor = &old.tensors[name];
+            let new_tensor = &new.tensors[name];
+            println!(
+                "  ~ {} [shape: {:?} -> {:?}, dtype: {}, hash: {} -> {}]",
+                name,
+                old_tensor.shape,
+                new_tensor.shape,
+                new_tensor.dtype,
+                &old_tensor.hash[..8],
+                &new_tensor.hash[..8]
+            );
+        }
+    }
+
+    println!("\nUnchanged Tensors: {}", diff.unchanged.len());
+
+    let sign = if comparison.size_change >= 0 { "+" } else { "" };
+    println!(
+        "Total Size Change: {}{} bytes",
+        sign, comparison.size_change
+    );
+
+    println!("\nStorage Efficiency:");
+    println!(
+        "  Old manifest: {} tensors, {} unique blobs",
+        comparison.storage_savings.total_tensors_old, comparison.storage_savings.unique_blobs_old
+    );
+    println!(
+        "  New manifest: {} tensors, {} unique blobs",
+        comparison.storage_savings.total_tensors_new, comparison.storage_savings.unique_blobs_new
+    );
+    println!(
+        "  Shared blobs: {}",
+        comparison.storage_savings.shared_blobs
+    );
+    println!(
+        "  Deduplication ratio: {:.2}x",
+        comparison.storage_savings.deduplication_ratio
+    );
+}
+
+/// Pushes manifests to one remote, reporting each manifest's outcome
+async fn push_to_remote(
+    repo: &Repository,
+    transfer: &TransferArgs,
+    remote_config: &RemoteConfig,
+    batch: &[(&str, &vekt_core::storage::VektManifest)],
+    refresh: bool,
+    force: bool,
+    hooks: &Hooks,
+) -> vekt_core::errors::Result<()> {
+    let client = transfer
+        .client(repo, remote_config)?
+        .with_hooks(hooks.clone());
+
+    // Validate access before attempting operations
+    println!("Validating remote access...");
+    client.validate_access().await?;
+
+    if refresh {
+        client.clear_known_blobs()?;
+    }
+
+    let mut failed = Vec::new();
+    for (name, result) in client.push_all(batch, force).await? {
+        match result {
+            Ok(_) => println!("Successfully pushed {}", name),
+            Err(e) => {
+                eprintln!("Failed to push {}: {}", name, e);
+                failed.push(name);
             }
         }
     }
+    if !failed.is_empty() {
+        return Err(vekt_core::errors::VektError::RemoteError(format!(
+            "{} of {} manifests were not pushed: {}",
+            failed.len(),
+            batch.len(),
+            failed.join(", ")
+        )));
+    }
     Ok(())
 }
+
+/// Formats a Unix timestamp as RFC 3339, or `-` when the remote does not report one
+fn format_timestamp(secs: Option<u64>) -> String {
+    secs.and_then(|secs| OffsetDateTime::from_unix_timestamp(secs as i64).ok())
+        .and_then(|time| time.format(&Rfc3339).ok())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Parses a byte rate such as `500000`, `512K`, `20M` or `1G` (binary multiples)
+fn parse_rate(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&value[..i], 1 << 10),
+        Some((i, 'm' | 'M')) => (&value[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&value[..i], 1 << 30),
+        _ => (value, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|n| *n > 0)
+        .ok_or_else(|| {
+            format!(
+                "invalid rate '{}', expected e.g. 500000, 512K or 20M",
+                value
+            )
+        })
+}
+
+/// Resolves manifest paths, glob patterns or `--all` to local paths paired with the
+/// name each manifest has on remotes: its path relative to the repository root.
+/// Without patterns, selects the manifests in `cwd`. Relative paths and patterns are
+/// taken from `cwd` too.
+fn select_manifests(
+    root: &Path,
+    patterns: &[String],
+    all: bool,
+    cwd: &Path,
+) -> Result<Vec<(PathBuf, String)>, Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+    if all {
+        paths = vekt_core::gc::find_manifests(&cwd.join(root))?;
+    } else if patterns.is_empty() {
+        for entry in std::fs::read_dir(cwd)? {
+            let path = entry?.path();
+            if path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|name| name.ends_with(".vekt.json"))
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+    } else {
+        for pattern in patterns {
+            if pattern.contains(['*', '?', '[']) {
+                let expanded = if Path::new(pattern).is_absolute() {
+                    pattern.clone()
+                } else {
+                    format!(
+                        "{}/{}",
+                        glob::Pattern::escape(&cwd.to_string_lossy()),
+                        pattern
+                    )
+                };
+                let matches: Vec<PathBuf> = glob::glob(&expanded)?.collect::<Result<_, _>>()?;
+                if matches.is_empty() {
+                    eprintln!("Warning: '{}' matched no manifests", pattern);
+                }
+                paths.extend(matches);
+            } else {
+                // May not exist locally yet when pulling
+                paths.push(cwd.join(pattern));
+            }
+        }
+    }
+
+    let root = normalize(&cwd.join(root));
+    let mut seen = std::collections::HashSet::new();
+    let mut selected = Vec::new();
+    for path in paths {
+        let absolute = normalize(&path);
+        let Ok(relative) = absolute.strip_prefix(&root) else {
+            eprintln!(
+                "Warning: {} is outside the repository, skipping",
+                path.display()
+            );
+            continue;
+        };
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if seen.insert(name.clone()) {
+            selected.push((path, name));
+        }
+    }
+    Ok(selected)
+}
+
+/// Resolves `.` and `..` components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifests_are_named_by_repository_path() {
+        let root = std::env::temp_dir().join(format!(
+            "vekt_cli_select_{}_{}",
+            std::process::id(),
+            OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ));
+        for dir in ["bert", "gpt"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join("model.vekt.json"), "{}").unwrap();
+        }
+        let names = |patterns: &[&str], all: bool, cwd: &Path| -> Vec<String> {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            select_manifests(&root, &patterns, all, cwd)
+                .unwrap()
+                .into_iter()
+                .map(|(_, name)| name)
+                .collect()
+        };
+
+        // Same-named manifests in different directories never share a remote name
+        assert_eq!(
+            names(&[], false, &root.join("bert")),
+            ["bert/model.vekt.json"]
+        );
+        assert_eq!(
+            names(&[], false, &root.join("gpt")),
+            ["gpt/model.vekt.json"]
+        );
+        assert_eq!(
+            names(&["model.vekt.json"], false, &root.join("gpt")),
+            ["gpt/model.vekt.json"]
+        );
+        assert_eq!(
+            names(&["*/model.vekt.json"], false, &root),
+            ["bert/model.vekt.json", "gpt/model.vekt.json"]
+        );
+        let mut all = names(&[], true, &root.join("bert"));
+        all.sort();
+        assert_eq!(all, ["bert/model.vekt.json", "gpt/model.vekt.json"]);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
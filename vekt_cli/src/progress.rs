@@ -0,0 +1,128 @@
+//! Progress bars for long-running commands, drawn on stderr from the library's
+//! progress events, and Ctrl-C handling that cancels the running operation.
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use vekt_core::progress::{CancellationToken, Hooks, Progress, Stage};
+
+/// Hooks drawing a bar per stage, cancelled when the user presses Ctrl-C.
+/// A second Ctrl-C exits immediately.
+pub fn hooks() -> Hooks {
+    let cancel = CancellationToken::new();
+    let interrupted = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("\nInterrupted, stopping (press Ctrl-C again to exit now)");
+            interrupted.cancel();
+        }
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+    Hooks::default()
+        .with_progress(Arc::new(Bars::default()))
+        .with_cancel(cancel)
+}
+
+#[derive(Default)]
+struct Bars {
+    multi: MultiProgress,
+    stages: Mutex<HashMap<Stage, StageBar>>,
+}
+
+struct StageBar {
+    bar: ProgressBar,
+    /// Whether the bar counts bytes rather than blobs
+    by_bytes: bool,
+    done: usize,
+    skipped: usize,
+}
+
+fn label(stage: Stage) -> &'static str {
+    match stage {
+        Stage::Hash => "Hashing",
+        Stage::Store => "Storing",
+        Stage::Restore => "Restoring",
+        Stage::Upload => "Uploading",
+        Stage::Download => "Downloading",
+    }
+}
+
+impl Bars {
+    fn advance(&self, stage: Stage, bytes: u64, skipped: bool) {
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = stages.get_mut(&stage) else {
+            return;
+        };
+        state.bar.inc(if state.by_bytes { bytes } else { 1 });
+        if skipped {
+            state.skipped += 1;
+            state.bar.set_message(format!("{} skipped", state.skipped));
+        } else {
+            state.done += 1;
+        }
+    }
+}
+
+impl Progress for Bars {
+    fn started(&self, stage: Stage, items: usize, bytes: Option<u64>) {
+        let template = match bytes {
+            Some(_) => {
+                "{prefix:>11} [{bar:30}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}"
+            }
+            None => "{prefix:>11} [{bar:30}] {pos}/{len} blobs {msg}",
+        };
+        let bar = self
+            .multi
+            .add(ProgressBar::new(bytes.unwrap_or(items as u64)));
+        bar.set_style(
+            ProgressStyle::with_template(template)
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("=> "),
+        );
+        bar.set_prefix(label(stage));
+        let state = StageBar {
+            bar,
+            by_bytes: bytes.is_some(),
+            done: 0,
+            skipped: 0,
+        };
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = stages.insert(stage, state) {
+            previous.bar.finish_and_clear();
+        }
+    }
+
+    fn blob_done(&self, stage: Stage, _hash: &str, bytes: u64) {
+        self.advance(stage, bytes, false);
+    }
+
+    fn blob_skipped(&self, stage: Stage, _hash: &str, bytes: u64) {
+        self.advance(stage, bytes, true);
+    }
+
+    fn finished(&self, stage: Stage) {
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = stages.remove(&stage) else {
+            return;
+        };
+        state.bar.finish_and_clear();
+        let summary = match stage {
+            Stage::Upload => format!(
+                "Upload complete: {} uploaded, {} skipped (already on remote)",
+                state.done, state.skipped
+            ),
+            Stage::Download => format!(
+                "Download complete: {} downloaded, {} skipped (already local)",
+                state.done, state.skipped
+            ),
+            Stage::Store => format!(
+                "Stored {} new blobs, {} already in the store",
+                state.done, state.skipped
+            ),
+            Stage::Hash | Stage::Restore => return,
+        };
+        self.multi.suspend(|| println!("{}", summary));
+    }
+}
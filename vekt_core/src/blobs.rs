@@ -1,8 +1,7 @@
-use crate::utils::get_store_path;
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{Read, Seek, SeekFrom, Write};
 /// Blob storage module - Single source of truth for all blob operations
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Computes the blake3 hash of data and returns it as a hex string
 /// Single source of truth for hash computation
@@ -11,45 +10,160 @@ pub fn compute_blob_hash(data: &[u8]) -> String {
     hex::encode(hash.as_bytes())
 }
 
-/// Returns the full path to a blob given its hash
-pub fn get_blob_path(hash: &str) -> PathBuf {
-    get_store_path().join(hash)
+/// Computes the blake3 hash of a file's contents without loading it into memory
+pub fn compute_file_hash(path: &Path) -> std::io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    let mut file = File::open(path)?;
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize().as_bytes()))
 }
 
-/// Checks if a blob exists in storage
-pub fn blob_exists(hash: &str) -> bool {
-    get_blob_path(hash).exists()
+/// Returns the path of a blob within a store directory
+pub fn blob_path_in(store: &Path, hash: &str) -> PathBuf {
+    store.join(hash)
 }
 
-/// Atomically writes data to a blob file using temp file + rename pattern
-/// Returns the hash of the written data
-/// Single source of truth for blob writing
-pub fn write_blob_atomic(data: &[u8]) -> std::io::Result<String> {
-    let hash = compute_blob_hash(data);
-    let blob_path = get_blob_path(&hash);
+/// Blobs at least this large get a BLAKE3 outboard tree, so byte ranges can be verified
+/// without hashing the whole blob. Smaller ones are cheap enough to verify in full.
+pub const OUTBOARD_MIN_SIZE: u64 = 1 << 20;
 
-    // Skip if already exists (deduplication)
-    if blob_path.exists() {
-        return Ok(hash);
+/// Returns the path of a blob's outboard tree, kept in `outboards` next to the store
+pub fn outboard_path_in(store: &Path, hash: &str) -> PathBuf {
+    store.parent().unwrap_or(store).join("outboards").join(hash)
+}
+
+/// Unique temp path next to an outboard. Tied tensors and concurrent range reads can
+/// build the same outboard at once; each writes its own file and the last rename wins,
+/// replacing identical content.
+fn outboard_staging_path(outboard_path: &Path) -> PathBuf {
+    outboard_path.with_extension(format!("{}.tmp", rand::random::<u64>()))
+}
+
+/// Hashes the file at `path` and, if it is large enough, builds its outboard tree in the
+/// same pass. The outboard is stored for `expected` only when the hashes match.
+/// Returns the actual hash.
+pub fn hash_file_with_outboard(
+    path: &Path,
+    store: &Path,
+    expected: &str,
+) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    if file.metadata()?.len() < OUTBOARD_MIN_SIZE {
+        return compute_file_hash(path);
     }
 
-    // Ensure blobs directory exists
-    let store_path = get_store_path();
-    fs::create_dir_all(&store_path)?;
+    let outboard_path = outboard_path_in(store, expected);
+    if let Some(parent) = outboard_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = outboard_staging_path(&outboard_path);
+    let encoded = (|| {
+        let outboard = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)?;
+        let mut encoder = bao::encode::Encoder::new_outboard(outboard);
+        std::io::copy(&mut file, &mut encoder)?;
+        Ok(encoder.finalize()?.to_hex().to_string())
+    })();
 
-    // Atomic write: temp file + rename
-    let tmp_path = blob_path.with_extension("tmp");
-    let mut f = File::create(&tmp_path)?;
-    f.write_all(data)?;
-    f.sync_all()?;
-    fs::rename(tmp_path, blob_path)?;
+    match encoded {
+        Ok(actual) if actual == expected => {
+            fs::rename(&tmp_path, &outboard_path)?;
+            Ok(actual)
+        }
+        other => {
+            let _ = fs::remove_file(&tmp_path);
+            other
+        }
+    }
+}
+
+/// Returns a unique temp path for a blob being written into a store directory. Being in
+/// the store makes admitting it a rename; the `.tmp` suffix keeps gc and listings off it.
+pub fn staging_path_in(store: &Path, hash: &str) -> PathBuf {
+    store.join(format!("{}.{}.tmp", hash, rand::random::<u64>()))
+}
+
+/// Moves a fully written, verified staging file into place as blob `hash`
+pub fn commit_blob_file_in(store: &Path, staging: &Path, hash: &str) -> std::io::Result<()> {
+    fs::rename(staging, blob_path_in(store, hash))
+}
+
+/// Reads `len` bytes at `offset` of blob `hash` from its open file `blob`, checking them
+/// against the hash. Blobs with an outboard tree only have the BLAKE3 chunks covering
+/// the range read and verified, building the outboard first if it is missing; smaller
+/// blobs are verified whole. Content that doesn't match fails with `InvalidData`.
+pub fn read_verified_range_in(
+    store: &Path,
+    hash: &str,
+    mut blob: &File,
+    offset: u64,
+    len: u64,
+) -> std::io::Result<Vec<u8>> {
+    let size = blob.metadata()?.len();
+    let mismatch = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("blob {} does not match its hash", hash),
+        )
+    };
+    if offset.checked_add(len).is_none_or(|end| end > size) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!(
+                "bytes {}..{} are past the end of blob {} with {} bytes",
+                offset,
+                offset.saturating_add(len),
+                hash,
+                size
+            ),
+        ));
+    }
+
+    if size < OUTBOARD_MIN_SIZE {
+        let mut data = Vec::with_capacity(size as usize);
+        blob.seek(SeekFrom::Start(0))?;
+        blob.read_to_end(&mut data)?;
+        if compute_blob_hash(&data) != hash {
+            return Err(mismatch());
+        }
+        data.truncate((offset + len) as usize);
+        data.drain(..offset as usize);
+        return Ok(data);
+    }
+
+    let outboard_path = outboard_path_in(store, hash);
+    if !outboard_path.exists()
+        && hash_file_with_outboard(&blob_path_in(store, hash), store, hash)? != hash
+    {
+        return Err(mismatch());
+    }
+    let root = blake3::Hash::from_hex(hash).map_err(|_| mismatch())?;
+    let extractor =
+        bao::encode::SliceExtractor::new_outboard(blob, File::open(&outboard_path)?, offset, len);
+    let mut decoder = bao::decode::SliceDecoder::new(extractor, &root, offset, len);
+    let mut data = Vec::with_capacity(len as usize);
+    decoder.read_to_end(&mut data)?;
+    Ok(data)
+}
 
-    Ok(hash)
+/// Checks if a blob exists in a store directory
+pub fn blob_exists_in(store: &Path, hash: &str) -> bool {
+    blob_path_in(store, hash).exists()
 }
 
-/// Reads a blob from storage given its hash
-pub fn read_blob(hash: &str) -> std::io::Result<Vec<u8>> {
-    let blob_path = get_blob_path(hash);
+/// Atomically writes data to a blob file using temp file + rename
+/// Returns the hash of the written data
+/// Single source of truth for blob writing
+pub fn write_blob_atomic_in(store_path: &Path, data: &[u8]) -> std::io::Result<String> {
+    save_blob_deduplicated_in(store_path, data).map(|(hash, _)| hash)
+}
+
+/// Reads a blob from a store directory given its hash
+pub fn read_blob_in(store: &Path, hash: &str) -> std::io::Result<Vec<u8>> {
+    let blob_path = blob_path_in(store, hash);
     std::fs::read(&blob_path).map_err(|e| {
         std::io::Error::new(
             e.kind(),
@@ -65,15 +179,68 @@ pub fn read_blob(hash: &str) -> std::io::Result<Vec<u8>> {
 
 /// Saves a blob only if it doesn't already exist (deduplication)
 /// Returns the hash and whether it was newly written
-pub fn save_blob_deduplicated(data: &[u8]) -> std::io::Result<(String, bool)> {
-    let hash = compute_blob_hash(data);
-    let existed = blob_exists(&hash);
+pub fn save_blob_deduplicated_in(store: &Path, data: &[u8]) -> std::io::Result<(String, bool)> {
+    // The outboard's root hash is the blob's BLAKE3 hash, so large blobs are hashed once
+    let (outboard, hash) = if data.len() as u64 >= OUTBOARD_MIN_SIZE {
+        let (outboard, root) = bao::encode::outboard(data);
+        (Some(outboard), root.to_hex().to_string())
+    } else {
+        (None, compute_blob_hash(data))
+    };
+    let blob_path = blob_path_in(store, &hash);
+    if blob_path.exists() {
+        return Ok((hash, false));
+    }
+
+    store_blob_in(store, &hash, data, outboard)?;
+    Ok((hash, true))
+}
 
-    if !existed {
-        write_blob_atomic(data)?;
+/// Replaces blob `hash` with `data`, such as a compressed blob with its decoded bytes.
+/// Fails with `InvalidData`, leaving the blob as it was, if `data` doesn't match the hash.
+pub fn rewrite_blob_in(store: &Path, hash: &str, data: &[u8]) -> std::io::Result<()> {
+    let (outboard, actual) = if data.len() as u64 >= OUTBOARD_MIN_SIZE {
+        let (outboard, root) = bao::encode::outboard(data);
+        (Some(outboard), root.to_hex().to_string())
+    } else {
+        (None, compute_blob_hash(data))
+    };
+    if actual != hash {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("data for blob {} hashes to {}", hash, actual),
+        ));
     }
+    store_blob_in(store, hash, data, outboard)
+}
+
+/// Writes `data` as blob `hash` through a staging file, then its outboard if it has one
+fn store_blob_in(
+    store: &Path,
+    hash: &str,
+    data: &[u8],
+    outboard: Option<Vec<u8>>,
+) -> std::io::Result<()> {
+    // Ensure blobs directory exists
+    fs::create_dir_all(store)?;
+
+    // Atomic write: temp file + rename
+    let tmp_path = staging_path_in(store, hash);
+    let mut f = File::create(&tmp_path)?;
+    f.write_all(data)?;
+    f.sync_all()?;
+    commit_blob_file_in(store, &tmp_path, hash)?;
 
-    Ok((hash, !existed))
+    if let Some(outboard) = outboard {
+        let outboard_path = outboard_path_in(store, hash);
+        if let Some(parent) = outboard_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = outboard_staging_path(&outboard_path);
+        fs::write(&tmp_path, outboard)?;
+        fs::rename(tmp_path, outboard_path)?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -89,35 +256,82 @@ mod tests {
         assert_eq!(hash1.len(), 64, "Blake3 hash should be 64 hex chars");
     }
 
+    fn temp_store(name: &str) -> PathBuf {
+        std::env::temp_dir().join("vekt_blobs_test").join(format!(
+            "{}_{}",
+            name,
+            rand::random::<u64>()
+        ))
+    }
+
     #[test]
     fn test_blob_deduplication() {
+        let store = temp_store("dedup");
         let data = b"unique test data for dedup";
 
-        // Cleanup before test to ensure fresh state
-        let hash = compute_blob_hash(data);
-        let _ = fs::remove_file(get_blob_path(&hash));
-
-        let (hash1, written1) = save_blob_deduplicated(data).unwrap();
-        let (hash2, written2) = save_blob_deduplicated(data).unwrap();
+        let (hash1, written1) = save_blob_deduplicated_in(&store, data).unwrap();
+        let (hash2, written2) = save_blob_deduplicated_in(&store, data).unwrap();
 
         assert_eq!(hash1, hash2);
         assert!(written1, "First write should occur");
         assert!(!written2, "Second write should be skipped (deduplicated)");
-        assert!(blob_exists(&hash1));
+        assert!(blob_exists_in(&store, &hash1));
+
+        let _ = fs::remove_dir_all(store);
+    }
+
+    #[test]
+    fn test_large_blob_hash_and_outboard() {
+        let root = temp_store("large");
+        let store = root.join("blobs");
+        let data: Vec<u8> = (0..OUTBOARD_MIN_SIZE + 1).map(|i| i as u8).collect();
+        let hash = write_blob_atomic_in(&store, &data).unwrap();
+
+        assert_eq!(hash, compute_blob_hash(&data));
+        assert!(outboard_path_in(&store, &hash).exists());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_concurrent_outboard_builds() {
+        let root = temp_store("concurrent");
+        let store = root.join("blobs");
+        let data: Vec<u8> = (0..2 * OUTBOARD_MIN_SIZE)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let hash = write_blob_atomic_in(&store, &data).unwrap();
+        fs::remove_file(outboard_path_in(&store, &hash)).unwrap();
+
+        // Several readers find the outboard missing and build it at the same time
+        let blob_path = blob_path_in(&store, &hash);
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    let actual = hash_file_with_outboard(&blob_path, &store, &hash).unwrap();
+                    assert_eq!(actual, hash);
+                });
+            }
+        });
+
+        let blob = File::open(&blob_path).unwrap();
+        let range = read_verified_range_in(&store, &hash, &blob, OUTBOARD_MIN_SIZE, 4096).unwrap();
+        assert_eq!(range, &data[OUTBOARD_MIN_SIZE as usize..][..4096]);
+        let leftovers = fs::read_dir(root.join("outboards")).unwrap().count();
+        assert_eq!(leftovers, 1);
 
-        // Cleanup
-        let _ = fs::remove_file(get_blob_path(&hash1));
+        let _ = fs::remove_dir_all(root);
     }
 
     #[test]
     fn test_write_and_read_blob() {
+        let store = temp_store("write");
         let original_data = b"test blob content";
-        let hash = write_blob_atomic(original_data).unwrap();
-        let read_data = read_blob(&hash).unwrap();
+        let hash = write_blob_atomic_in(&store, original_data).unwrap();
+        let read_data = read_blob_in(&store, &hash).unwrap();
 
         assert_eq!(original_data, &read_data[..]);
 
-        // Cleanup
-        let _ = fs::remove_file(get_blob_path(&hash));
+        let _ = fs::remove_dir_all(store);
     }
 }
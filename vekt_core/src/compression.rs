@@ -1,15 +1,23 @@
+use crate::blobs;
 use crate::errors::{Result, VektError};
 use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Compression level (0-9, where 9 is maximum compression)
 const COMPRESSION_LEVEL: u32 = 6;
 
+/// First bytes of a blob stored compressed: the flag byte, then the zstd frame magic
+const COMPRESSED_PREFIX: [u8; 5] = [1, 0x28, 0xB5, 0x2F, 0xFD];
+
 /// Compress data using zstd
 pub fn compress_blob(data: &[u8]) -> Result<Vec<u8>> {
-    zstd::encode_all(data, COMPRESSION_LEVEL as i32)
-        .map_err(|e| VektError::CompressionError(e.to_string()))
+    compress_blob_at(data, COMPRESSION_LEVEL as i32)
+}
+
+/// Compress data using zstd at `level`, 1 to 22
+pub fn compress_blob_at(data: &[u8], level: i32) -> Result<Vec<u8>> {
+    zstd::encode_all(data, level).map_err(|e| VektError::CompressionError(e.to_string()))
 }
 
 /// Decompress data using zstd
@@ -24,7 +32,7 @@ pub fn save_blob_with_compression(
     data: &[u8],
     enable_compression: bool,
 ) -> Result<bool> {
-    // Create parent directory if needed (get_store_path already ensures .vekt has .gitignore)
+    // Create parent directory if needed (Repository::init already gives .vekt its .gitignore)
     if let Some(parent) = blob_path.parent() {
         fs::create_dir_all(parent)?;
     }
@@ -77,6 +85,66 @@ pub fn load_blob_with_decompression(blob_path: &Path) -> Result<Vec<u8>> {
     }
 }
 
+/// Saves a blob into `store` for `vekt add --compress`: zstd-compressed at `level` behind
+/// the flag byte `load_blob_with_decompression` reads, or as `blobs` saves it when that
+/// would not make it smaller. Returns the hash and whether it was newly written.
+pub fn save_blob_compressed_in(store: &Path, data: &[u8], level: i32) -> Result<(String, bool)> {
+    let hash = blobs::compute_blob_hash(data);
+    if blobs::blob_exists_in(store, &hash) {
+        return Ok((hash, false));
+    }
+
+    let compressed = compress_blob_at(data, level)?;
+    // Readers tell compressed blobs apart by a length other than the tensor's
+    if compressed.len() + 1 >= data.len() {
+        return Ok(blobs::save_blob_deduplicated_in(store, data)?);
+    }
+
+    fs::create_dir_all(store)?;
+    let tmp_path = blobs::staging_path_in(store, &hash);
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&[1u8])?;
+    file.write_all(&compressed)?;
+    file.sync_all()?;
+    blobs::commit_blob_file_in(store, &tmp_path, &hash)?;
+    Ok((hash, true))
+}
+
+/// Decoded bytes of blob `hash` if it is stored compressed, `None` if it is stored as is.
+/// Decoded bytes only count if they match the hash, so a raw blob that merely starts
+/// like a compressed one is still read as is.
+pub fn decode_blob_in(store: &Path, hash: &str) -> Result<Option<Vec<u8>>> {
+    let path = blobs::blob_path_in(store, hash);
+    let mut prefix = [0u8; COMPRESSED_PREFIX.len()];
+    if File::open(&path)?.read_exact(&mut prefix).is_err() || prefix != COMPRESSED_PREFIX {
+        return Ok(None);
+    }
+    let Ok(decoded) = load_blob_with_decompression(&path) else {
+        return Ok(None);
+    };
+    Ok((blobs::compute_blob_hash(&decoded) == hash).then_some(decoded))
+}
+
+/// Stores blob `hash` uncompressed from now on if it is compressed, for readers such as
+/// mounts and the server that read ranges of the blob file directly
+pub fn inflate_blob_in(store: &Path, hash: &str) -> Result<()> {
+    if let Some(decoded) = decode_blob_in(store, hash)? {
+        blobs::rewrite_blob_in(store, hash, &decoded)?;
+    }
+    Ok(())
+}
+
+/// Writes the decoded bytes of blob `hash` to a staging file if it is stored compressed,
+/// so it can be uploaded as remotes store blobs. The caller removes the file.
+pub fn decoded_copy_in(store: &Path, hash: &str) -> Result<Option<PathBuf>> {
+    let Some(decoded) = decode_blob_in(store, hash)? else {
+        return Ok(None);
+    };
+    let tmp_path = blobs::staging_path_in(store, hash);
+    fs::write(&tmp_path, decoded)?;
+    Ok(Some(tmp_path))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +173,31 @@ mod tests {
 
         assert_eq!(original, decompressed);
     }
+
+    #[test]
+    fn test_compressed_blobs_decode_and_inflate() {
+        let store = std::env::temp_dir()
+            .join(format!("vekt_compression_{}", rand::random::<u64>()))
+            .join("blobs");
+        let data = vec![7u8; 4096];
+        let (hash, written) = save_blob_compressed_in(&store, &data, 3).unwrap();
+        assert!(written);
+        let stored = fs::metadata(blobs::blob_path_in(&store, &hash))
+            .unwrap()
+            .len();
+        assert!(stored < data.len() as u64);
+        assert_eq!(decode_blob_in(&store, &hash).unwrap(), Some(data.clone()));
+
+        // Data that doesn't shrink is stored as is
+        let noise: Vec<u8> = (0..4096).map(|_| rand::random::<u8>()).collect();
+        let (raw, _) = save_blob_compressed_in(&store, &noise, 3).unwrap();
+        assert_eq!(blobs::read_blob_in(&store, &raw).unwrap(), noise);
+        assert_eq!(decode_blob_in(&store, &raw).unwrap(), None);
+
+        inflate_blob_in(&store, &hash).unwrap();
+        assert_eq!(blobs::read_blob_in(&store, &hash).unwrap(), data);
+        assert_eq!(decode_blob_in(&store, &hash).unwrap(), None);
+
+        let _ = fs::remove_dir_all(store.parent().unwrap());
+    }
 }
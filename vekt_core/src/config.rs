@@ -0,0 +1,503 @@
+//! Settings layered from the user config file, the repository's `.vekt/config.json`
+//! and `VEKT_*` environment variables, each overriding the ones before it.
+//! Config files carry a schema version and are migrated when loaded.
+
+use crate::errors::{Result, VektError};
+use crate::utils::write_file_atomic;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Schema version written to config files. Files without a version are version 1,
+/// where remotes could be plain URL strings.
+pub const CONFIG_VERSION: u32 = 2;
+
+pub(crate) fn config_version() -> u32 {
+    CONFIG_VERSION
+}
+
+/// Settings as stored in one layer; unset keys fall through to the layer below
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsLayer {
+    /// Compress blobs written by `vekt add`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<bool>,
+
+    /// zstd level used when compressing, 1 to 22
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_level: Option<i32>,
+
+    /// Remote or group used by `push` and `pull` when none is named
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_remote: Option<String>,
+
+    /// Blobs transferred at the same time by remotes that don't set their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+
+    /// Hours an unreferenced blob is kept on a remote by `vekt gc --remote`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gc_grace_hours: Option<u64>,
+
+    /// Alignment in bytes of tensor data in restored files, a power of two
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restore_alignment: Option<u64>,
+}
+
+/// Settings in effect once every layer is applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub compression: bool,
+    pub compression_level: i32,
+    pub default_remote: String,
+    pub concurrency: usize,
+    pub gc_grace_hours: u64,
+    pub restore_alignment: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            compression: false,
+            compression_level: 6,
+            default_remote: "origin".to_string(),
+            concurrency: 10,
+            gc_grace_hours: 24,
+            restore_alignment: 8,
+        }
+    }
+}
+
+impl From<&Settings> for SettingsLayer {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            compression: Some(settings.compression),
+            compression_level: Some(settings.compression_level),
+            default_remote: Some(settings.default_remote.clone()),
+            concurrency: Some(settings.concurrency),
+            gc_grace_hours: Some(settings.gc_grace_hours),
+            restore_alignment: Some(settings.restore_alignment),
+        }
+    }
+}
+
+/// Largest accepted `restore_alignment`
+const MAX_ALIGNMENT: u64 = 1 << 20;
+
+impl SettingsLayer {
+    /// Every settings key, in the order `vekt config list` shows them
+    pub const KEYS: [&'static str; 6] = [
+        "compression",
+        "compression_level",
+        "default_remote",
+        "concurrency",
+        "gc_grace_hours",
+        "restore_alignment",
+    ];
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Value of `key` in this layer as text, `None` if unset
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(match key {
+            "compression" => self.compression.map(|v| v.to_string()),
+            "compression_level" => self.compression_level.map(|v| v.to_string()),
+            "default_remote" => self.default_remote.clone(),
+            "concurrency" => self.concurrency.map(|v| v.to_string()),
+            "gc_grace_hours" => self.gc_grace_hours.map(|v| v.to_string()),
+            "restore_alignment" => self.restore_alignment.map(|v| v.to_string()),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// Parses and validates `value` for `key`, leaving the layer unchanged on error
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let mut updated = self.clone();
+        match key {
+            "compression" => updated.compression = Some(parse_bool(key, value)?),
+            "compression_level" => updated.compression_level = Some(parse(key, value)?),
+            "default_remote" => updated.default_remote = Some(value.to_string()),
+            "concurrency" => updated.concurrency = Some(parse(key, value)?),
+            "gc_grace_hours" => updated.gc_grace_hours = Some(parse(key, value)?),
+            "restore_alignment" => updated.restore_alignment = Some(parse(key, value)?),
+            _ => return Err(unknown_key(key)),
+        }
+        updated.validate()?;
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn unset(&mut self, key: &str) -> Result<()> {
+        match key {
+            "compression" => self.compression = None,
+            "compression_level" => self.compression_level = None,
+            "default_remote" => self.default_remote = None,
+            "concurrency" => self.concurrency = None,
+            "gc_grace_hours" => self.gc_grace_hours = None,
+            "restore_alignment" => self.restore_alignment = None,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// Reads `VEKT_<KEY>` variables, e.g. `VEKT_CONCURRENCY=4`
+    pub fn from_env() -> Result<Self> {
+        let mut layer = Self::default();
+        for key in Self::KEYS {
+            let var = env_var(key);
+            if let Ok(value) = std::env::var(&var) {
+                layer.set(key, &value).map_err(|e| within(&var, e))?;
+            }
+        }
+        Ok(layer)
+    }
+
+    /// Rejects values no command could use, such as a hand-edited zero concurrency
+    pub fn validate(&self) -> Result<()> {
+        if let Some(level) = self.compression_level
+            && !(1..=22).contains(&level)
+        {
+            return Err(VektError::InvalidConfig(format!(
+                "compression_level must be between 1 and 22, got {}",
+                level
+            )));
+        }
+        if self.default_remote.as_deref() == Some("") {
+            return Err(VektError::InvalidConfig(
+                "default_remote must not be empty".to_string(),
+            ));
+        }
+        if self.concurrency == Some(0) {
+            return Err(VektError::InvalidConfig(
+                "concurrency must be at least 1".to_string(),
+            ));
+        }
+        if let Some(alignment) = self.restore_alignment
+            && (!alignment.is_power_of_two() || alignment > MAX_ALIGNMENT)
+        {
+            return Err(VektError::InvalidConfig(format!(
+                "restore_alignment must be a power of two up to {}, got {}",
+                MAX_ALIGNMENT, alignment
+            )));
+        }
+        Ok(())
+    }
+
+    fn apply_to(&self, settings: &mut Settings) {
+        if let Some(v) = self.compression {
+            settings.compression = v;
+        }
+        if let Some(v) = self.compression_level {
+            settings.compression_level = v;
+        }
+        if let Some(v) = &self.default_remote {
+            settings.default_remote = v.clone();
+        }
+        if let Some(v) = self.concurrency {
+            settings.concurrency = v;
+        }
+        if let Some(v) = self.gc_grace_hours {
+            settings.gc_grace_hours = v;
+        }
+        if let Some(v) = self.restore_alignment {
+            settings.restore_alignment = v;
+        }
+    }
+}
+
+/// Environment variable overriding `key`
+pub fn env_var(key: &str) -> String {
+    format!("VEKT_{}", key.to_uppercase())
+}
+
+/// Names where a bad value came from, without repeating "Invalid configuration"
+fn within(origin: &dyn fmt::Display, error: VektError) -> VektError {
+    let reason = match error {
+        VektError::InvalidConfig(reason) => reason,
+        other => other.to_string(),
+    };
+    VektError::InvalidConfig(format!("{}: {}", origin, reason))
+}
+
+fn unknown_key(key: &str) -> VektError {
+    VektError::InvalidConfig(format!(
+        "unknown setting '{}', expected one of: {}",
+        key,
+        SettingsLayer::KEYS.join(", ")
+    ))
+}
+
+fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| VektError::InvalidConfig(format!("invalid value '{}' for {}", value, key)))
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(VektError::InvalidConfig(format!(
+            "invalid value '{}' for {}, expected true or false",
+            value, key
+        ))),
+    }
+}
+
+/// Layer a setting's effective value comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    User,
+    Repository,
+    Environment,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Source::Default => "default",
+            Source::User => "user",
+            Source::Repository => "repository",
+            Source::Environment => "environment",
+        })
+    }
+}
+
+/// The settings layers of a repository, from lowest to highest precedence
+#[derive(Debug, Clone, Default)]
+pub struct LayeredSettings {
+    pub user: SettingsLayer,
+    pub repo: SettingsLayer,
+    pub env: SettingsLayer,
+}
+
+impl LayeredSettings {
+    pub fn resolve(&self) -> Settings {
+        let mut settings = Settings::default();
+        for layer in [&self.user, &self.repo, &self.env] {
+            layer.apply_to(&mut settings);
+        }
+        settings
+    }
+
+    /// Effective value of `key` as text and the layer it was set in
+    pub fn get(&self, key: &str) -> Result<(String, Source)> {
+        for (layer, source) in [
+            (&self.env, Source::Environment),
+            (&self.repo, Source::Repository),
+            (&self.user, Source::User),
+        ] {
+            if let Some(value) = layer.get(key)? {
+                return Ok((value, source));
+            }
+        }
+        let defaults = SettingsLayer::from(&Settings::default());
+        Ok((defaults.get(key)?.unwrap_or_default(), Source::Default))
+    }
+}
+
+/// Per-user config file, holding settings shared by every repository
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserConfig {
+    #[serde(default = "config_version")]
+    pub version: u32,
+
+    #[serde(default, skip_serializing_if = "SettingsLayer::is_empty")]
+    pub settings: SettingsLayer,
+}
+
+impl Default for UserConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            settings: SettingsLayer::default(),
+        }
+    }
+}
+
+impl UserConfig {
+    /// `VEKT_CONFIG` if set, otherwise `vekt/config.json` in the platform config
+    /// directory (`~/.config` on Linux)
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("VEKT_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        dirs::config_dir().map(|dir| dir.join("vekt").join("config.json"))
+    }
+
+    /// Loads the user config, or the default one if there is no file
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => load_versioned(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            VektError::InvalidConfig(
+                "no user config directory found; set VEKT_CONFIG to a file path".to_string(),
+            )
+        })?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        write_file_atomic(&path, json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Reads a config file, migrating it to the current schema, and checks its settings
+pub(crate) fn load_versioned<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        VektError::Io(std::io::Error::other(format!(
+            "Failed to open config file at {}: {}",
+            path.display(),
+            e
+        )))
+    })?;
+    let at = |e: VektError| within(&path.display(), e);
+    let mut value: Value = serde_json::from_slice(&bytes).map_err(|e| at(e.into()))?;
+    if migrate(&mut value).map_err(at)? {
+        tracing::info!(
+            path = %path.display(),
+            version = CONFIG_VERSION,
+            "migrated config to the current schema"
+        );
+    }
+    if let Some(settings) = value.get("settings") {
+        let settings: SettingsLayer =
+            serde_json::from_value(settings.clone()).map_err(|e| at(e.into()))?;
+        settings.validate().map_err(at)?;
+    }
+    serde_json::from_value(value).map_err(|e| at(e.into()))
+}
+
+/// Upgrades config JSON in place to `CONFIG_VERSION`. Returns whether anything changed;
+/// the file itself is rewritten the next time the config is saved.
+pub fn migrate(value: &mut Value) -> Result<bool> {
+    let Some(object) = value.as_object_mut() else {
+        return Err(VektError::InvalidConfig(
+            "config must be a JSON object".to_string(),
+        ));
+    };
+    let version = match object.get("version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| VektError::InvalidConfig(format!("invalid version {}", version)))?,
+    };
+    if version > CONFIG_VERSION {
+        return Err(VektError::InvalidConfig(format!(
+            "schema version {} is newer than this vekt supports ({}); upgrade vekt",
+            version, CONFIG_VERSION
+        )));
+    }
+    if version == CONFIG_VERSION {
+        return Ok(false);
+    }
+
+    // 1 -> 2: remotes given as a bare URL become objects, and the version is recorded
+    if let Some(Value::Object(remotes)) = object.get_mut("remotes") {
+        for remote in remotes.values_mut() {
+            if let Value::String(url) = remote {
+                *remote = serde_json::json!({ "url": url });
+            }
+        }
+    }
+    object.insert("version".to_string(), CONFIG_VERSION.into());
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let mut layers = LayeredSettings::default();
+        layers.user.set("concurrency", "4").unwrap();
+        layers.user.set("compression", "yes").unwrap();
+        layers.repo.set("concurrency", "2").unwrap();
+        layers.env.set("default_remote", "backup").unwrap();
+
+        let settings = layers.resolve();
+        assert_eq!(settings.concurrency, 2);
+        assert!(settings.compression);
+        assert_eq!(settings.default_remote, "backup");
+        assert_eq!(settings.restore_alignment, 8);
+
+        assert_eq!(
+            layers.get("concurrency").unwrap(),
+            ("2".to_string(), Source::Repository)
+        );
+        assert_eq!(
+            layers.get("gc_grace_hours").unwrap(),
+            ("24".to_string(), Source::Default)
+        );
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        let mut layer = SettingsLayer::default();
+        assert!(layer.set("concurrency", "0").is_err());
+        assert!(layer.set("restore_alignment", "12").is_err());
+        assert!(layer.set("compression_level", "30").is_err());
+        assert!(layer.set("compression", "maybe").is_err());
+        assert!(layer.set("colour", "blue").is_err());
+        assert!(layer.is_empty());
+    }
+
+    #[test]
+    fn test_migrates_unversioned_config() {
+        let mut value = serde_json::json!({"remotes": {"origin": "s3://bucket"}});
+        assert!(migrate(&mut value).unwrap());
+        assert_eq!(value["version"], CONFIG_VERSION);
+        assert_eq!(value["remotes"]["origin"]["url"], "s3://bucket");
+        assert!(!migrate(&mut value).unwrap());
+
+        let mut newer = serde_json::json!({"version": CONFIG_VERSION + 1});
+        assert!(matches!(
+            migrate(&mut newer),
+            Err(VektError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_unversioned_config_is_migrated_on_save() {
+        let root =
+            std::env::temp_dir().join(format!("vekt_config_migration_{}", rand::random::<u64>()));
+        let repo = crate::repository::Repository::init(&root).unwrap();
+        std::fs::write(
+            repo.config_path(),
+            r#"{"remotes": {"origin": "file:///tmp/models"}}"#,
+        )
+        .unwrap();
+
+        let mut config = repo.config().unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.remotes["origin"].url, "file:///tmp/models");
+
+        config.settings.set("default_remote", "origin").unwrap();
+        repo.save_config(&config).unwrap();
+        let saved: Value =
+            serde_json::from_slice(&std::fs::read(repo.config_path()).unwrap()).unwrap();
+        assert_eq!(saved["version"], CONFIG_VERSION);
+        assert_eq!(saved["remotes"]["origin"]["url"], "file:///tmp/models");
+        assert_eq!(saved["settings"]["default_remote"], "origin");
+
+        // A config from a newer vekt is refused rather than silently rewritten
+        std::fs::write(repo.config_path(), r#"{"version": 99, "remotes": {}}"#).unwrap();
+        assert!(matches!(repo.config(), Err(VektError::InvalidConfig(_))));
+
+        std::fs::remove_dir_all(root).ok();
+    }
+}
@@ -75,88 +75,6 @@ impl VektManifest {
             storage_savings,
         }
     }
-
-    /// Print a human-readable diff
-    pub fn print_diff(&self, other: &VektManifest) {
-        let comparison = self.diff(other);
-        let diff = &comparison.tensor_diff;
-
-        println!("\nManifest Comparison:");
-        println!("==================");
-
-        if !diff.added.is_empty() {
-            println!("\nAdded Tensors ({}):", diff.added.len());
-            for name in &diff.added {
-                let tensor = &other.tensors[name];
-                println!(
-                    "  + {} [shape: {:?}, dtype: {}, hash: {}]",
-                    name,
-                    tensor.shape,
-                    tensor.dtype,
-                    &tensor.hash[..8]
-                );
-            }
-        }
-
-        if !diff.removed.is_empty() {
-            println!("\nRemoved Tensors ({}):", diff.removed.len());
-            for name in &diff.removed {
-                let tensor = &self.tensors[name];
-                println!(
-                    "  - {} [shape: {:?}, dtype: {}, hash: {}]",
-                    name,
-                    tensor.shape,
-                    tensor.dtype,
-                    &tensor.hash[..8]
-                );
-            }
-        }
-
-        if !diff.modified.is_empty() {
-            println!("\nModified Tensors ({}):", diff.modified.len());
-            for name in &diff.modified {
-                let old_tensor = &self.tensors[name];
-                let new_tensor = &other.tensors[name];
-                println!(
-                    "  ~ {} [shape: {:?} -> {:?}, dtype: {}, hash: {} -> {}]",
-                    name,
-                    old_tensor.shape,
-                    new_tensor.shape,
-                    new_tensor.dtype,
-                    &old_tensor.hash[..8],
-                    &new_tensor.hash[..8]
-                );
-            }
-        }
-
-        println!("\nUnchanged Tensors: {}", diff.unchanged.len());
-
-        let sign = if comparison.size_change >= 0 { "+" } else { "" };
-        println!(
-            "Total Size Change: {}{} bytes",
-            sign, comparison.size_change
-        );
-
-        println!("\nStorage Efficiency:");
-        println!(
-            "  Old manifest: {} tensors, {} unique blobs",
-            comparison.storage_savings.total_tensors_old,
-            comparison.storage_savings.unique_blobs_old
-        );
-        println!(
-            "  New manifest: {} tensors, {} unique blobs",
-            comparison.storage_savings.total_tensors_new,
-            comparison.storage_savings.unique_blobs_new
-        );
-        println!(
-            "  Shared blobs: {}",
-            comparison.storage_savings.shared_blobs
-        );
-        println!(
-            "  Deduplication ratio: {:.2}x",
-            comparison.storage_savings.deduplication_ratio
-        );
-    }
 }
 
 fn calculate_storage_savings(old: &VektManifest, new: &VektManifest) -> StorageSavings {
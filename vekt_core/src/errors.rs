@@ -38,6 +38,17 @@ pub enum VektError {
     #[error("Remote not found: {0}")]
     RemoteNotFound(String),
 
+    #[error("Remote is read-only: {0}")]
+    ReadOnlyRemote(String),
+
+    /// The remote can't do this at all, as opposed to failing to
+    #[error("Not supported by the remote: {0}")]
+    Unsupported(String),
+
+    /// Transient failure (timeout, throttling, 5xx) that is worth retrying
+    #[error("Remote temporarily unavailable: {0}")]
+    RemoteUnavailable(String),
+
     #[error("Compression error: {0}")]
     CompressionError(String),
 
@@ -53,6 +64,9 @@ pub enum VektError {
     #[error("Invalid tensor name: {0}")]
     InvalidTensorName(String),
 
+    #[error("Range out of bounds: {0}")]
+    OutOfRange(String),
+
     #[error("Stale lock file detected. Process may have crashed. Lock age: {0} seconds")]
     StaleLock(u64),
 
@@ -68,6 +82,9 @@ pub enum VektError {
     #[error("Git operation failed: {0}")]
     GitError(String),
 
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
     #[error("Operation cancelled: {0}")]
     Cancelled(String),
 }
@@ -1,22 +1,28 @@
+use crate::blobs;
 use crate::errors::{Result, VektError};
+use crate::repository::Repository;
 use crate::storage::VektManifest;
-use crate::utils::{LockFile, get_store_path};
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{debug, info, info_span, warn};
 
 pub struct GcStats {
     pub deleted: usize,
     pub kept: usize,
 }
 
-pub fn run_gc(root_path: &Path) -> Result<GcStats> {
+/// Deletes blobs no manifest in `repo`'s working tree or git history references
+pub fn run_gc(repo: &Repository) -> Result<GcStats> {
     // CRITICAL: Acquire lock for entire GC operation to prevent race conditions
     // This ensures no other vekt operations can modify manifests or blobs during GC
-    let _lock = LockFile::lock()?;
+    let _lock = repo.lock()?;
+    let _span = info_span!("gc", root = %repo.root().display()).entered();
+    let started = Instant::now();
 
-    let store_path = get_store_path();
+    let store_path = repo.store();
     if !store_path.exists() {
         return Ok(GcStats {
             deleted: 0,
@@ -24,13 +30,7 @@ pub fn run_gc(root_path: &Path) -> Result<GcStats> {
         });
     }
 
-    let mut referenced_hashes = HashSet::new();
-
-    // Scan working tree
-    scan_manifests(root_path, &mut referenced_hashes)?;
-
-    // Scan git history
-    scan_git_history(root_path, &mut referenced_hashes)?;
+    let referenced_hashes = referenced_hashes(repo.root())?;
 
     let mut stats = GcStats {
         deleted: 0,
@@ -46,7 +46,10 @@ pub fn run_gc(root_path: &Path) -> Result<GcStats> {
                 continue;
             }
             if !referenced_hashes.contains(hash) {
+                debug!(hash, "deleting unreferenced blob");
                 std::fs::remove_file(&path)?;
+                // Not every blob has an outboard
+                let _ = std::fs::remove_file(blobs::outboard_path_in(&store_path, hash));
                 stats.deleted += 1;
             } else {
                 stats.kept += 1;
@@ -54,10 +57,58 @@ pub fn run_gc(root_path: &Path) -> Result<GcStats> {
         }
     }
 
+    info!(
+        deleted = stats.deleted,
+        kept = stats.kept,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "gc complete"
+    );
     Ok(stats)
 }
 
+/// Blob hashes referenced by manifests in the working tree or anywhere in git history
+pub fn referenced_hashes(root_path: &Path) -> Result<HashSet<String>> {
+    let mut hashes = HashSet::new();
+
+    // Scan working tree
+    scan_manifests(root_path, &mut hashes)?;
+
+    // Scan git history
+    scan_git_history(root_path, &mut hashes)?;
+
+    Ok(hashes)
+}
+
 fn scan_manifests(dir: &Path, hashes: &mut HashSet<String>) -> Result<()> {
+    for path in find_manifests(dir)? {
+        let f = File::open(&path)?;
+        let reader = std::io::BufReader::new(f);
+        match serde_json::from_reader::<_, VektManifest>(reader) {
+            Ok(manifest) => {
+                for tensor in manifest.tensors.values() {
+                    hashes.insert(tensor.hash.clone());
+                }
+            }
+            Err(e) => {
+                // Log corrupted manifests but continue GC
+                // This prevents partial failures from blocking cleanup
+                warn!(path = %path.display(), error = %e, "skipping unparseable manifest");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds every `*.vekt.json` manifest under `dir`, sorted by path.
+/// Skips `.git`, `.vekt` and common build directories.
+pub fn find_manifests(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut manifests = Vec::new();
+    collect_manifests(dir, &mut manifests)?;
+    manifests.sort();
+    Ok(manifests)
+}
+
+fn collect_manifests(dir: &Path, manifests: &mut Vec<PathBuf>) -> Result<()> {
     if dir.is_dir() {
         for entry in std::fs::read_dir(dir)? {
             let entry = entry?;
@@ -68,28 +119,11 @@ fn scan_manifests(dir: &Path, hashes: &mut HashSet<String>) -> Result<()> {
                 if name == ".git" || name == ".vekt" || name == "target" || name == "node_modules" {
                     continue;
                 }
-                scan_manifests(&path, hashes)?;
+                collect_manifests(&path, manifests)?;
             } else if let Some(name) = path.file_name().and_then(|n| n.to_str())
                 && name.ends_with(".vekt.json")
             {
-                let f = File::open(&path)?;
-                let reader = std::io::BufReader::new(f);
-                match serde_json::from_reader::<_, VektManifest>(reader) {
-                    Ok(manifest) => {
-                        for tensor in manifest.tensors.values() {
-                            hashes.insert(tensor.hash.clone());
-                        }
-                    }
-                    Err(e) => {
-                        // Log corrupted manifests but continue GC
-                        // This prevents partial failures from blocking cleanup
-                        eprintln!(
-                            "Warning: Failed to parse manifest at {}: {}. Skipping this manifest.",
-                            path.display(),
-                            e
-                        );
-                    }
-                }
+                manifests.push(path);
             }
         }
     }
@@ -117,10 +151,7 @@ fn scan_git_history(repo_root: &Path, hashes: &mut HashSet<String>) -> Result<()
 
     if !rev_list_output.status.success() {
         let stderr = String::from_utf8_lossy(&rev_list_output.stderr);
-        eprintln!(
-            "Warning: git rev-list failed: {}. Skipping git history scan.",
-            stderr
-        );
+        warn!(stderr = %stderr.trim(), "git rev-list failed, skipping git history scan");
         return Ok(());
     }
 
@@ -160,7 +191,7 @@ fn scan_git_history(repo_root: &Path, hashes: &mut HashSet<String>) -> Result<()
         use std::io::Write;
         for sha in &manifest_objects {
             if let Err(e) = writeln!(stdin, "{}", sha) {
-                eprintln!("Warning: Failed to write SHA to git cat-file: {}", e);
+                warn!(error = %e, "failed to write object id to git cat-file");
                 break;
             }
         }
@@ -196,7 +227,7 @@ fn scan_git_history(repo_root: &Path, hashes: &mut HashSet<String>) -> Result<()
             let size: usize = match parts[2].parse() {
                 Ok(s) => s,
                 Err(_) => {
-                    eprintln!("Warning: Invalid size in git cat-file output: {}", parts[2]);
+                    warn!(size = parts[2], "invalid size in git cat-file output");
                     continue;
                 }
             };
@@ -204,7 +235,7 @@ fn scan_git_history(repo_root: &Path, hashes: &mut HashSet<String>) -> Result<()
             // Read exactly 'size' bytes (the actual file content)
             let mut content = vec![0u8; size];
             if let Err(e) = reader.read_exact(&mut content) {
-                eprintln!("Warning: Failed to read git object content: {}", e);
+                warn!(error = %e, "failed to read git object content");
                 break;
             }
 
@@ -221,7 +252,7 @@ fn scan_git_history(repo_root: &Path, hashes: &mut HashSet<String>) -> Result<()
                 }
                 Err(e) => {
                     // Log but don't fail - file might be corrupted or not valid JSON
-                    eprintln!("Warning: Failed to parse git object as manifest: {}", e);
+                    warn!(error = %e, "skipping git object that is not a valid manifest");
                 }
             }
         }
@@ -1,23 +1,45 @@
 pub mod blobs;
 pub mod compression;
+pub mod config;
 pub mod diff;
 pub mod errors;
 pub mod gc;
+#[cfg(feature = "mount")]
+pub mod mount;
+pub mod progress;
+pub mod reader;
 pub mod remote;
+pub mod repository;
+pub mod server;
 pub mod storage;
 pub mod utils;
 pub mod validation;
 
 use crate::errors::{Result, VektError};
+use crate::progress::{Hooks, Stage};
+use crate::repository::Repository;
 use memmap2::Mmap;
 use rayon::prelude::*;
 use std::collections::BTreeMap;
+use std::time::Instant;
+use tracing::{debug, info, info_span};
 
 use storage::{ManifestTensor, RawHeader, VektManifest};
 
 pub trait ModelArchiver {
-    fn process(&self, save_blobs: bool) -> Result<VektManifest>;
+    /// Builds the manifest, saving the tensor blobs into `repo` if `save_blobs` is set
+    fn process(&self, repo: &Repository, save_blobs: bool) -> Result<VektManifest> {
+        self.process_with(repo, save_blobs, &Hooks::default())
+    }
+    /// Like `process`, reporting progress to and stopping when cancelled through `hooks`
+    fn process_with(
+        &self,
+        repo: &Repository,
+        save_blobs: bool,
+        hooks: &Hooks,
+    ) -> Result<VektManifest>;
     fn restore(
+        repo: &Repository,
         manifest: &VektManifest,
         output_path: &std::path::Path,
         filter: Option<&str>,
@@ -28,6 +50,8 @@ pub struct SafetensorFile {
     pub header: RawHeader,
     pub mmap: Mmap,
     pub header_len: usize,
+    /// Whether saved blobs are compressed; `None` follows the repository's `compression` setting
+    pub compress: Option<bool>,
 }
 
 impl SafetensorFile {
@@ -36,8 +60,15 @@ impl SafetensorFile {
             header,
             mmap,
             header_len,
+            compress: None,
         }
     }
+
+    /// Compresses saved blobs, or not, regardless of the `compression` setting
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = Some(compress);
+        self
+    }
     pub fn open(path: &str) -> Result<Self> {
         // Open the file and create a memory-mapped buffer
         use std::fs::File;
@@ -77,19 +108,38 @@ impl SafetensorFile {
     }
 }
 
-impl ModelArchiver for SafetensorFile {
-    fn process(&self, save_blobs: bool) -> Result<VektManifest> {
+impl SafetensorFile {
+    /// Hashes every tensor, saving their blobs into `store` if given, compressed at
+    /// `compression` level if that is set
+    fn archive(
+        &self,
+        store: Option<&std::path::Path>,
+        compression: Option<i32>,
+        hooks: &Hooks,
+    ) -> Result<VektManifest> {
         let header_entries: Vec<(usize, &String, &storage::RawTensorMetaData)> = self
             .header
             .iter()
             .enumerate()
             .map(|(i, (k, v))| (i, k, v))
             .collect();
+        let data_size: u64 = header_entries
+            .iter()
+            .map(|(_, _, meta)| (meta.data_offsets.1.saturating_sub(meta.data_offsets.0)) as u64)
+            .sum();
+        // Rayon workers do not inherit the current span, so events name it as parent
+        let span = info_span!("add", tensors = header_entries.len(), bytes = data_size);
+        let _entered = span.enter();
+        let started = Instant::now();
 
         // Step 1: Compute hashes in parallel (CPU Bound)
+        hooks
+            .progress
+            .started(Stage::Hash, header_entries.len(), Some(data_size));
         let processed_tensors: Vec<Result<(String, ManifestTensor, usize, usize)>> = header_entries
             .par_iter()
             .map(|(index, tensor_name, tensor_meta)| {
+                hooks.cancel.check()?;
                 let (start, end) = tensor_meta.data_offsets;
                 let absolute_start = self.header_len + 8 + start;
                 let absolute_end = self.header_len + 8 + end;
@@ -105,6 +155,10 @@ impl ModelArchiver for SafetensorFile {
 
                 let data_slice = &self.mmap[absolute_start..absolute_end];
                 let hash_hex = blobs::compute_blob_hash(data_slice);
+                debug!(parent: &span, hash = %hash_hex, bytes = data_slice.len(), tensor = %tensor_name, "hashed tensor");
+                hooks
+                    .progress
+                    .blob_done(Stage::Hash, &hash_hex, data_slice.len() as u64);
 
                 Ok((
                     (*tensor_name).clone(),
@@ -127,24 +181,52 @@ impl ModelArchiver for SafetensorFile {
 
         for res in processed_tensors {
             let (name, tensor, start, end) = res?;
+            valid_entries.push((tensor.hash.clone(), start, end));
             results.insert(name, tensor);
-            valid_entries.push((start, end));
         }
+        hooks.progress.finished(Stage::Hash);
+        let hash_ms = started.elapsed().as_millis() as u64;
+        let mut stored = 0usize;
 
         // Step 2: Save blobs (IO Bound)
         // We use try_for_each to handle errors, and par_iter to potentially parallelize IO
         // (though disk IO is often better serialized or throttled, rayon handles this reasonably well)
-        if save_blobs {
+        if let Some(store) = store {
+            hooks
+                .progress
+                .started(Stage::Store, valid_entries.len(), Some(data_size));
             valid_entries
                 .par_iter()
-                .try_for_each(|(start, end)| -> Result<()> {
+                .try_for_each(|(hash, start, end)| -> Result<()> {
+                    hooks.cancel.check()?;
                     let data = &self.mmap[*start..*end];
-                    match blobs::save_blob_deduplicated(data) {
-                        Ok(_) => Ok(()),
-                        Err(e) => Err(VektError::Io(e)),
+                    let (_, written) = match compression {
+                        Some(level) => compression::save_blob_compressed_in(store, data, level)?,
+                        None => blobs::save_blob_deduplicated_in(store, data)?,
+                    };
+                    debug!(parent: &span, hash = %hash, bytes = data.len(), written, "stored blob");
+                    if written {
+                        hooks
+                            .progress
+                            .blob_done(Stage::Store, hash, data.len() as u64);
+                    } else {
+                        hooks
+                            .progress
+                            .blob_skipped(Stage::Store, hash, data.len() as u64);
                     }
+                    Ok(())
                 })?;
+            hooks.progress.finished(Stage::Store);
+            stored = valid_entries.len();
         }
+        info!(
+            tensors = results.len(),
+            bytes = data_size,
+            blobs_stored = stored,
+            hash_ms,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "add complete"
+        );
 
         Ok(VektManifest {
             tensors: results,
@@ -152,12 +234,33 @@ impl ModelArchiver for SafetensorFile {
             total_size: self.mmap.len(),
         })
     }
+}
+
+impl ModelArchiver for SafetensorFile {
+    fn process_with(
+        &self,
+        repo: &Repository,
+        save_blobs: bool,
+        hooks: &Hooks,
+    ) -> Result<VektManifest> {
+        if save_blobs {
+            let settings = repo.settings()?;
+            let compression = self
+                .compress
+                .unwrap_or(settings.compression)
+                .then_some(settings.compression_level);
+            self.archive(Some(&repo.store()), compression, hooks)
+        } else {
+            self.archive(None, None, hooks)
+        }
+    }
 
     fn restore(
+        repo: &Repository,
         manifest: &VektManifest,
         output_path: &std::path::Path,
         filter: Option<&str>,
     ) -> Result<()> {
-        manifest.restore(output_path, filter)
+        manifest.restore(repo, output_path, filter)
     }
 }
@@ -0,0 +1,421 @@
+//! Read-only FUSE filesystem showing every manifest as the safetensors file
+//! `restore` would write. File contents are assembled on demand from blobs, so
+//! tools can open or `mmap` a model without restoring it first.
+
+use crate::blobs;
+use crate::compression;
+use crate::errors::{Result, VektError};
+use crate::remote::RemoteClient;
+use crate::repository::Repository;
+use crate::storage::{FileLayout, Segment, VektManifest};
+use fuser::consts::FOPEN_KEEP_CACHE;
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry,
+    ReplyOpen, Request,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const ROOT_INO: u64 = fuser::FUSE_ROOT_ID;
+/// Files never change while mounted, so the kernel may cache attributes for long
+const TTL: Duration = Duration::from_secs(3600);
+/// Blob files kept open between reads
+const MAX_OPEN_BLOBS: usize = 256;
+
+enum Node {
+    Dir {
+        parent: u64,
+        children: BTreeMap<OsString, u64>,
+    },
+    File {
+        layout: FileLayout,
+        modified: SystemTime,
+    },
+}
+
+/// Downloads blobs missing from the local store on first read
+struct LazyFetch {
+    client: RemoteClient,
+    runtime: tokio::runtime::Handle,
+}
+
+/// Snapshot of the repository's manifests, taken when the filesystem is created
+pub struct VektFs {
+    /// Node for inode `n` at index `n - 1`
+    nodes: Vec<Node>,
+    store: PathBuf,
+    open_blobs: HashMap<String, File>,
+    fetch: Option<LazyFetch>,
+    uid: u32,
+    gid: u32,
+}
+
+impl VektFs {
+    /// Builds the tree from every manifest in `repo`, each shown at the same path
+    /// relative to the root as `<name>.safetensors`. Manifests that fail to parse are skipped.
+    pub fn new(repo: &Repository) -> Result<Self> {
+        let mut fs = Self {
+            nodes: vec![Node::Dir {
+                parent: ROOT_INO,
+                children: BTreeMap::new(),
+            }],
+            store: repo.store(),
+            open_blobs: HashMap::new(),
+            fetch: None,
+            // SAFETY: getuid/getgid cannot fail
+            uid: unsafe { libc::getuid() },
+            gid: unsafe { libc::getgid() },
+        };
+
+        // Files hold exactly the bytes `vekt restore` would write
+        let alignment = repo.settings()?.restore_alignment;
+        for path in repo.manifests()? {
+            let Ok(relative) = path.strip_prefix(repo.root()) else {
+                continue;
+            };
+            let layout =
+                VektManifest::load(&path).and_then(|manifest| manifest.layout(None, alignment));
+            let layout = match layout {
+                Ok(layout) => layout,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "skipping unreadable manifest");
+                    continue;
+                }
+            };
+            let modified = std::fs::metadata(&path)?.modified()?;
+            fs.insert(relative, Node::File { layout, modified });
+        }
+        Ok(fs)
+    }
+
+    /// Fetches blobs missing from the local store from `client` when they are read,
+    /// running the downloads on `runtime`
+    pub fn with_remote(mut self, client: RemoteClient, runtime: tokio::runtime::Handle) -> Self {
+        self.fetch = Some(LazyFetch { client, runtime });
+        self
+    }
+
+    /// Number of safetensors files in the tree
+    pub fn file_count(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node, Node::File { .. }))
+            .count()
+    }
+
+    /// Adds a file node for the manifest at `relative`, creating parent directories
+    fn insert(&mut self, relative: &Path, file: Node) {
+        let mut parent = ROOT_INO;
+        let components: Vec<&OsStr> = relative.iter().collect();
+        let Some((manifest_name, dirs)) = components.split_last() else {
+            return;
+        };
+        for dir in dirs {
+            parent = match self.child(parent, dir) {
+                Some(ino) => ino,
+                None => self.add_node(
+                    parent,
+                    dir,
+                    Node::Dir {
+                        parent,
+                        children: BTreeMap::new(),
+                    },
+                ),
+            };
+        }
+        let name = manifest_name.to_string_lossy();
+        let stem = name.strip_suffix(".vekt.json").unwrap_or(&name);
+        self.add_node(parent, OsStr::new(&format!("{}.safetensors", stem)), file);
+    }
+
+    fn add_node(&mut self, parent: u64, name: &OsStr, node: Node) -> u64 {
+        self.nodes.push(node);
+        let ino = self.nodes.len() as u64;
+        if let Some(Node::Dir { children, .. }) = self.node_mut(parent) {
+            children.insert(name.to_os_string(), ino);
+        }
+        ino
+    }
+
+    fn node(&self, ino: u64) -> Option<&Node> {
+        self.nodes.get(ino.checked_sub(1)? as usize)
+    }
+
+    fn node_mut(&mut self, ino: u64) -> Option<&mut Node> {
+        self.nodes.get_mut(ino.checked_sub(1)? as usize)
+    }
+
+    fn child(&self, parent: u64, name: &OsStr) -> Option<u64> {
+        match self.node(parent)? {
+            Node::Dir { children, .. } => children.get(name).copied(),
+            Node::File { .. } => None,
+        }
+    }
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        let (kind, size, perm, modified) = match self.node(ino)? {
+            Node::Dir { .. } => (FileType::Directory, 0, 0o555, SystemTime::UNIX_EPOCH),
+            Node::File { layout, modified } => {
+                (FileType::RegularFile, layout.size, 0o444, *modified)
+            }
+        };
+        Some(FileAttr {
+            ino,
+            size,
+            blocks: size.div_ceil(512),
+            atime: modified,
+            mtime: modified,
+            ctime: modified,
+            crtime: modified,
+            kind,
+            perm,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            blksize: 4096,
+            flags: 0,
+        })
+    }
+
+    /// Reads up to `size` bytes of file `ino` from `offset`
+    fn read_file(&mut self, ino: u64, offset: u64, size: usize) -> Result<Vec<u8>> {
+        let Self {
+            nodes,
+            store,
+            open_blobs,
+            fetch,
+            ..
+        } = self;
+        let Some(Node::File { layout, .. }) = ino
+            .checked_sub(1)
+            .and_then(|index| nodes.get(index as usize))
+        else {
+            return Err(VektError::BlobNotFound(format!(
+                "no file with inode {}",
+                ino
+            )));
+        };
+
+        let mut buf = vec![0u8; size];
+        let read = layout.read_at(offset, &mut buf, |segment, blob_offset, out| {
+            let file = open_blob(store, open_blobs, fetch.as_ref(), segment)?;
+            if segment.len < blobs::OUTBOARD_MIN_SIZE {
+                // Verified whole when opened; straight from the blob file into the reply buffer
+                file.read_exact_at(out, blob_offset)?;
+                return Ok(());
+            }
+            let data = blobs::read_verified_range_in(
+                store,
+                &segment.hash,
+                file,
+                blob_offset,
+                out.len() as u64,
+            )
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::InvalidData => hash_mismatch(segment),
+                _ => e.into(),
+            })?;
+            out.copy_from_slice(&data);
+            Ok(())
+        })?;
+        buf.truncate(read);
+        Ok(buf)
+    }
+}
+
+fn hash_mismatch(segment: &Segment) -> VektError {
+    VektError::TensorCorruption(format!(
+        "Blob {} for tensor '{}' does not match its hash",
+        segment.hash, segment.tensor
+    ))
+}
+
+/// Opens the blob behind `segment`, fetching it first if it is missing locally.
+/// Small blobs are verified whole here; reads of larger ones are verified against
+/// their outboard tree.
+fn open_blob<'a>(
+    store: &Path,
+    open_blobs: &'a mut HashMap<String, File>,
+    fetch: Option<&LazyFetch>,
+    segment: &Segment,
+) -> Result<&'a File> {
+    if !open_blobs.contains_key(&segment.hash) {
+        let path = blobs::blob_path_in(store, &segment.hash);
+        if !path.exists()
+            && let Some(fetch) = fetch
+        {
+            fetch.runtime.block_on(
+                fetch
+                    .client
+                    .fetch_blobs(std::slice::from_ref(&segment.hash)),
+            )?;
+        }
+        // Reads go straight to the blob file, so a compressed blob is stored decoded first
+        if path.exists() {
+            compression::inflate_blob_in(store, &segment.hash)?;
+        }
+
+        let file = File::open(&path).map_err(|_| {
+            VektError::BlobNotFound(format!(
+                "Blob {} for tensor '{}' is not in the local store",
+                segment.hash, segment.tensor
+            ))
+        })?;
+        // Reads of a truncated blob would otherwise come back short
+        let len = file.metadata()?.len();
+        if len != segment.len {
+            return Err(VektError::TensorCorruption(format!(
+                "Blob {} for tensor '{}' has {} bytes, expected {}",
+                segment.hash, segment.tensor, len, segment.len
+            )));
+        }
+        if len < blobs::OUTBOARD_MIN_SIZE && blobs::compute_file_hash(&path)? != segment.hash {
+            return Err(hash_mismatch(segment));
+        }
+
+        if open_blobs.len() >= MAX_OPEN_BLOBS {
+            open_blobs.clear();
+        }
+        open_blobs.insert(segment.hash.clone(), file);
+    }
+    Ok(&open_blobs[&segment.hash])
+}
+
+impl Filesystem for VektFs {
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        match self.child(parent, name).and_then(|ino| self.attr(ino)) {
+            Some(attr) => reply.entry(&TTL, &attr, 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        match self.attr(ino) {
+            Some(attr) => reply.attr(&TTL, &attr),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn open(&mut self, _req: &Request<'_>, ino: u64, flags: i32, reply: ReplyOpen) {
+        match self.node(ino) {
+            Some(Node::File { .. }) if flags & libc::O_ACCMODE == libc::O_RDONLY => {
+                // Contents never change while mounted, so cached pages stay valid
+                reply.opened(0, FOPEN_KEEP_CACHE)
+            }
+            Some(Node::File { .. }) => reply.error(libc::EROFS),
+            Some(Node::Dir { .. }) => reply.error(libc::EISDIR),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        match self.read_file(ino, offset.max(0) as u64, size as usize) {
+            Ok(data) => reply.data(&data),
+            Err(e) => {
+                tracing::warn!(ino, error = %e, "read failed");
+                reply.error(libc::EIO);
+            }
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let Some(Node::Dir { parent, children }) = self.node(ino) else {
+            reply.error(libc::ENOTDIR);
+            return;
+        };
+
+        let mut entries = vec![
+            (ino, FileType::Directory, OsStr::new(".")),
+            (*parent, FileType::Directory, OsStr::new("..")),
+        ];
+        for (name, &child) in children {
+            let kind = match self.node(child) {
+                Some(Node::Dir { .. }) => FileType::Directory,
+                _ => FileType::RegularFile,
+            };
+            entries.push((child, kind, name.as_os_str()));
+        }
+
+        for (i, (child, kind, name)) in entries.into_iter().enumerate().skip(offset.max(0) as usize)
+        {
+            // The offset passed back is where the next call resumes
+            if reply.add(child, (i + 1) as i64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+}
+
+/// Mounts `fs` read-only at `mountpoint`, blocking until it is unmounted
+pub fn mount(fs: VektFs, mountpoint: &Path) -> Result<()> {
+    let options = [
+        MountOption::RO,
+        MountOption::FSName("vekt".to_string()),
+        MountOption::Subtype("vekt".to_string()),
+        MountOption::DefaultPermissions,
+    ];
+    fuser::mount2(fs, mountpoint, &options)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_mirrors_manifest_paths() {
+        let mut fs = VektFs {
+            nodes: vec![Node::Dir {
+                parent: ROOT_INO,
+                children: BTreeMap::new(),
+            }],
+            store: PathBuf::from("store"),
+            open_blobs: HashMap::new(),
+            fetch: None,
+            uid: 0,
+            gid: 0,
+        };
+        let file = || Node::File {
+            layout: FileLayout {
+                header: vec![2, 0, 0, 0, 0, 0, 0, 0, b'{', b'}'],
+                segments: Vec::new(),
+                size: 10,
+            },
+            modified: SystemTime::UNIX_EPOCH,
+        };
+        fs.insert(Path::new("models/bert/model.vekt.json"), file());
+        fs.insert(Path::new("models/gpt.vekt.json"), file());
+        assert_eq!(fs.file_count(), 2);
+
+        let models = fs.child(ROOT_INO, OsStr::new("models")).unwrap();
+        let bert = fs.child(models, OsStr::new("bert")).unwrap();
+        let model = fs.child(bert, OsStr::new("model.safetensors")).unwrap();
+        assert!(fs.child(models, OsStr::new("gpt.safetensors")).is_some());
+        assert_eq!(fs.attr(model).unwrap().size, 10);
+        assert_eq!(fs.read_file(model, 8, 100).unwrap(), b"{}");
+        assert!(fs.read_file(model, 10, 100).unwrap().is_empty());
+    }
+}
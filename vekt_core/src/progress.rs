@@ -0,0 +1,164 @@
+//! Progress reporting and cancellation for long-running operations. The library never
+//! prints; callers that want to show progress implement `Progress` and pass it in
+//! `Hooks`, along with a `CancellationToken` they can trigger from another thread.
+
+use crate::errors::{Result, VektError};
+use futures::future::{self, Either};
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+
+/// Part of an operation that progress is reported for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Hashing the tensors of a file being added
+    Hash,
+    /// Writing blobs of a file being added into the store
+    Store,
+    /// Copying blobs into a restored safetensors file
+    Restore,
+    /// Uploading blobs to a remote
+    Upload,
+    /// Downloading blobs from a remote
+    Download,
+}
+
+/// Receives progress events. Every method does nothing by default, so an observer only
+/// implements what it shows. Events may arrive from several threads at once.
+pub trait Progress: Send + Sync {
+    /// `stage` begins with `items` blobs to go through, totalling `bytes` if known
+    fn started(&self, _stage: Stage, _items: usize, _bytes: Option<u64>) {}
+
+    /// A blob of `bytes` was hashed, written, copied or transferred
+    fn blob_done(&self, _stage: Stage, _hash: &str, _bytes: u64) {}
+
+    /// A blob needed no work: already in the store, on the remote or local
+    fn blob_skipped(&self, _stage: Stage, _hash: &str, _bytes: u64) {}
+
+    /// `stage` completed; not called if it failed or was cancelled
+    fn finished(&self, _stage: Stage) {}
+}
+
+/// Observer that ignores every event
+pub struct NoProgress;
+
+impl Progress for NoProgress {}
+
+/// Shared flag that makes running operations stop with `VektError::Cancelled`.
+/// Operations check it between blobs, and abandon in-flight transfers, leaving
+/// partial downloads and multipart uploads to be resumed by the next attempt.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every operation holding a clone of this token
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Errors with `VektError::Cancelled` once the token is cancelled
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(cancelled_error());
+        }
+        Ok(())
+    }
+
+    /// Completes when the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            let mut notified = pin!(self.inner.notify.notified());
+            // Register before checking so a cancel in between is not missed
+            notified.as_mut().enable();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Runs `op` unless the token is cancelled first, in which case `op` is dropped
+    pub async fn run<T>(&self, op: impl Future<Output = Result<T>>) -> Result<T> {
+        self.check()?;
+        match future::select(pin!(op), pin!(self.cancelled())).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(cancelled_error()),
+        }
+    }
+}
+
+fn cancelled_error() -> VektError {
+    VektError::Cancelled("cancelled by caller".to_string())
+}
+
+/// Progress observer and cancellation token for an operation
+#[derive(Clone)]
+pub struct Hooks {
+    pub progress: Arc<dyn Progress>,
+    pub cancel: CancellationToken,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Self {
+            progress: Arc::new(NoProgress),
+            cancel: CancellationToken::new(),
+        }
+    }
+}
+
+impl Hooks {
+    pub fn with_progress(mut self, progress: Arc<dyn Progress>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_running_operation() {
+        let token = CancellationToken::new();
+        let waiting = token.clone();
+        let handle = tokio::spawn(async move {
+            waiting
+                .run(async {
+                    std::future::pending::<()>().await;
+                    Ok(())
+                })
+                .await
+        });
+        tokio::task::yield_now().await;
+        token.cancel();
+        assert!(matches!(
+            handle.await.unwrap(),
+            Err(VektError::Cancelled(_))
+        ));
+        assert!(token.check().is_err());
+    }
+}
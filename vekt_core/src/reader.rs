@@ -0,0 +1,257 @@
+//! Reading individual tensors straight from the blob store, without restoring
+//! the whole safetensors file.
+
+use crate::blobs;
+use crate::compression;
+use crate::errors::{Result, VektError};
+use crate::repository::Repository;
+use crate::storage::{ManifestTensor, VektManifest};
+use crate::utils::get_dtype_size;
+use memmap2::Mmap;
+use std::fs::File;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Gives access to the tensors of a manifest, reading each blob only when asked for
+pub struct TensorReader {
+    manifest: VektManifest,
+    store: PathBuf,
+}
+
+impl TensorReader {
+    /// Reads tensors of `manifest` from `repo`'s blob store
+    pub fn new(repo: &Repository, manifest: VektManifest) -> Self {
+        Self::with_store(manifest, repo.store())
+    }
+
+    /// Reads tensors of `manifest` from an explicit blob store directory
+    pub fn with_store(manifest: VektManifest, store: PathBuf) -> Self {
+        Self { manifest, store }
+    }
+
+    /// Loads the manifest at `path` and reads from `repo`'s blob store
+    pub fn open(repo: &Repository, path: &Path) -> Result<Self> {
+        Ok(Self::new(repo, VektManifest::load(path)?))
+    }
+
+    pub fn manifest(&self) -> &VektManifest {
+        &self.manifest
+    }
+
+    /// Blob store the tensors are read from
+    pub fn store(&self) -> &Path {
+        &self.store
+    }
+
+    /// Tensor names in their original file order
+    pub fn names(&self) -> Vec<&String> {
+        self.manifest.select_tensors(None)
+    }
+
+    fn meta(&self, name: &str) -> Result<&ManifestTensor> {
+        self.manifest
+            .tensors
+            .get(name)
+            .ok_or_else(|| VektError::InvalidTensorName(format!("no tensor named '{}'", name)))
+    }
+
+    fn open_blob(&self, name: &str, meta: &ManifestTensor) -> Result<(PathBuf, File)> {
+        let path = blobs::blob_path_in(&self.store, &meta.hash);
+        let file = File::open(&path).map_err(|_| {
+            VektError::BlobNotFound(format!(
+                "Blob {} not found for tensor '{}'",
+                meta.hash, name
+            ))
+        })?;
+        Ok((path, file))
+    }
+
+    /// Opens tensor `name`. Its blob is mapped (or decoded if stored compressed)
+    /// and checked for size; the hash is only verified on the first `bytes()` call.
+    pub fn tensor(&self, name: &str) -> Result<Tensor> {
+        let meta = self.meta(name)?;
+        let (path, file) = self.open_blob(name, meta)?;
+
+        let expected = tensor_size(meta);
+        let actual = file.metadata()?.len();
+        let data = if actual == expected {
+            if expected == 0 {
+                TensorData::Decoded(Vec::new())
+            } else {
+                // SAFETY: blobs are written once via temp file + rename and never modified
+                TensorData::Mapped(unsafe { Mmap::map(&file)? })
+            }
+        } else {
+            // A blob saved with compression carries a flag byte, so its length differs
+            let decoded = compression::load_blob_with_decompression(&path).ok();
+            match decoded {
+                Some(data) if data.len() as u64 == expected => TensorData::Decoded(data),
+                _ => {
+                    return Err(VektError::TensorCorruption(format!(
+                        "Tensor '{}': shape and dtype give {} bytes, but blob {} has {}",
+                        name, expected, meta.hash, actual
+                    )));
+                }
+            }
+        };
+
+        Ok(Tensor {
+            name: name.to_string(),
+            shape: meta.shape.clone(),
+            dtype: meta.dtype.clone(),
+            hash: meta.hash.clone(),
+            data,
+            actual_hash: OnceLock::new(),
+        })
+    }
+
+    /// Opens every tensor in its original file order
+    pub fn tensors(&self) -> impl Iterator<Item = Result<Tensor>> + '_ {
+        self.names().into_iter().map(|name| self.tensor(name))
+    }
+
+    /// Reads bytes `range` of tensor `name`. For blobs with an outboard tree only the
+    /// BLAKE3 chunks covering the range are read and verified; other blobs are verified
+    /// whole, building the outboard if the blob is large enough to need one.
+    pub fn read_range(&self, name: &str, range: Range<u64>) -> Result<Vec<u8>> {
+        let meta = self.meta(name)?;
+        let size = tensor_size(meta);
+        if range.start > range.end || range.end > size {
+            return Err(VektError::OutOfRange(format!(
+                "bytes {}..{} of tensor '{}' with {} bytes",
+                range.start, range.end, name, size
+            )));
+        }
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (_, file) = self.open_blob(name, meta)?;
+        if file.metadata()?.len() != size || size < blobs::OUTBOARD_MIN_SIZE {
+            let tensor = self.tensor(name)?;
+            return Ok(tensor.bytes()?[range.start as usize..range.end as usize].to_vec());
+        }
+
+        blobs::read_verified_range_in(
+            &self.store,
+            &meta.hash,
+            &file,
+            range.start,
+            range.end - range.start,
+        )
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::InvalidData {
+                VektError::TensorCorruption(format!(
+                    "Tensor '{}': bytes {}..{} do not match blob {}",
+                    name, range.start, range.end, meta.hash
+                ))
+            } else {
+                e.into()
+            }
+        })
+    }
+
+    /// Reads `rows` along the first dimension of tensor `name`, such as a few rows of an
+    /// embedding matrix or one expert of a stacked MoE weight
+    pub fn slice(&self, name: &str, rows: Range<usize>) -> Result<TensorSlice> {
+        let meta = self.meta(name)?;
+        let Some((&dim, rest)) = meta.shape.split_first() else {
+            return Err(VektError::OutOfRange(format!(
+                "tensor '{}' is a scalar and cannot be sliced",
+                name
+            )));
+        };
+        if rows.start > rows.end || rows.end > dim {
+            return Err(VektError::OutOfRange(format!(
+                "rows {}..{} of tensor '{}' with {} rows",
+                rows.start, rows.end, name, dim
+            )));
+        }
+
+        let row_size = (rest.iter().product::<usize>() * get_dtype_size(&meta.dtype)) as u64;
+        let data = self.read_range(
+            name,
+            rows.start as u64 * row_size..rows.end as u64 * row_size,
+        )?;
+        let mut shape = meta.shape.clone();
+        shape[0] = rows.len();
+        Ok(TensorSlice {
+            shape,
+            dtype: meta.dtype.clone(),
+            data,
+        })
+    }
+}
+
+/// Size in bytes given by a tensor's shape and dtype
+fn tensor_size(meta: &ManifestTensor) -> u64 {
+    (meta.shape.iter().product::<usize>() * get_dtype_size(&meta.dtype)) as u64
+}
+
+/// Verified bytes of a range of rows of a tensor
+pub struct TensorSlice {
+    pub shape: Vec<usize>,
+    pub dtype: String,
+    pub data: Vec<u8>,
+}
+
+enum TensorData {
+    Mapped(Mmap),
+    Decoded(Vec<u8>),
+}
+
+/// A single tensor and its raw little-endian bytes
+pub struct Tensor {
+    pub name: String,
+    pub shape: Vec<usize>,
+    pub dtype: String,
+    /// Blob hash recorded in the manifest
+    pub hash: String,
+    data: TensorData,
+    actual_hash: OnceLock<String>,
+}
+
+impl Tensor {
+    /// The tensor's bytes, verified against the manifest hash on first access
+    pub fn bytes(&self) -> Result<&[u8]> {
+        self.verify()?;
+        Ok(self.bytes_unverified())
+    }
+
+    /// The tensor's bytes without checking the hash
+    pub fn bytes_unverified(&self) -> &[u8] {
+        match &self.data {
+            TensorData::Mapped(mmap) => mmap,
+            TensorData::Decoded(data) => data,
+        }
+    }
+
+    /// Checks the bytes against the manifest hash, hashing them only once
+    pub fn verify(&self) -> Result<()> {
+        let actual = self
+            .actual_hash
+            .get_or_init(|| blobs::compute_blob_hash(self.bytes_unverified()));
+        if *actual != self.hash {
+            return Err(VektError::HashMismatch {
+                expected: self.hash.clone(),
+                actual: actual.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the bytes are mapped from the blob file rather than copied into memory
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.data, TensorData::Mapped(_))
+    }
+
+    /// Number of bytes
+    pub fn len(&self) -> usize {
+        self.bytes_unverified().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -1,267 +1,766 @@
+mod cache;
+mod fallback;
+mod fs;
+mod gc;
+mod http;
+mod s3;
+mod transfer;
+mod vekt;
+
+pub use self::cache::{KnownBlobs, ManifestBases};
+pub use self::fallback::FallbackRemote;
+pub use self::fs::FsRemote;
+pub use self::gc::{RemoteGcOptions, RemoteGcStats};
+pub use self::http::HttpRemote;
+pub use self::s3::S3Remote;
+pub use self::transfer::{Throttle, TransferOptions};
+pub use self::vekt::VektRemote;
+
+use self::transfer::with_retries;
 use crate::blobs;
+use crate::compression;
 use crate::errors::{Result, VektError};
-use crate::storage::VektManifest;
-use crate::validation::validate_s3_url;
+use crate::progress::{Hooks, Stage};
+use crate::repository::Repository;
+use crate::storage::{RemoteConfig, VektManifest};
+use crate::utils::write_file_atomic;
+use crate::validation::{
+    validate_file_url, validate_http_url, validate_path_safe, validate_vekt_url,
+};
+use async_trait::async_trait;
 use futures::stream::{self, StreamExt};
-use s3::bucket::Bucket;
-use s3::creds::Credentials;
-use s3::region::Region;
-use std::str::FromStr;
-use tokio::fs::File;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+/// Metadata object holding the time of the last remote garbage collection
+const GC_EPOCH_META: &str = "gc-epoch";
+
+/// Batches up to this size are checked blob by blob rather than by listing the remote
+const INDIVIDUAL_CHECK_LIMIT: usize = 16;
+
+/// An object stored on a remote, named relative to its `blobs/` or `manifests/` prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteObject {
+    pub name: String,
+    pub size: u64,
+    /// Last modification time in seconds since the Unix epoch, if the backend reports it
+    pub last_modified: Option<u64>,
+}
+
+/// Storage operations a remote backend must provide.
+/// Blobs live under `blobs/<hash>` and manifests under `manifests/<name>`.
+#[async_trait]
+pub trait Remote: Send + Sync {
+    /// Whether the backend rejects uploads and deletions
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Stable description of where the remote keeps its data, used to key local caches
+    fn location(&self) -> String;
+
+    /// Locations of the remotes behind this one, whose local caches and manifest
+    /// bases a read also applies to. Only a group has more than its own location.
+    fn member_locations(&self) -> Vec<String> {
+        vec![self.location()]
+    }
+
+    /// Checks that the remote is reachable with the configured credentials
+    async fn validate_access(&self) -> Result<()>;
+
+    async fn list_blobs(&self) -> Result<Vec<RemoteObject>>;
+    async fn has_blob(&self, hash: &str) -> Result<bool>;
+
+    /// Returns the subset of `hashes` present on the remote. Small batches are checked
+    /// individually; larger ones with a single (paginated) listing of the blobs.
+    async fn existing_blobs(&self, hashes: &[String]) -> Result<HashSet<String>> {
+        if hashes.len() <= INDIVIDUAL_CHECK_LIMIT {
+            let checks = stream::iter(hashes.iter().cloned())
+                .map(|hash| async move {
+                    let exists = self.has_blob(&hash).await?;
+                    Ok::<_, VektError>((hash, exists))
+                })
+                .buffer_unordered(10);
+            let results: Vec<_> = checks.collect().await;
+            let mut present = HashSet::new();
+            for res in results {
+                let (hash, exists) = res?;
+                if exists {
+                    present.insert(hash);
+                }
+            }
+            return Ok(present);
+        }
+
+        let wanted: HashSet<&str> = hashes.iter().map(String::as_str).collect();
+        Ok(self
+            .list_blobs()
+            .await?
+            .into_iter()
+            .filter(|object| wanted.contains(object.name.as_str()))
+            .map(|object| object.name)
+            .collect())
+    }
+    /// Downloads a blob into `dest`, which the caller verifies and renames into the store.
+    /// If `dest` already holds a partial download, only the remaining bytes are fetched
+    /// and appended to it.
+    async fn get_blob(&self, hash: &str, dest: &Path) -> Result<()>;
+    async fn put_blob(&self, hash: &str, src: &Path) -> Result<()>;
+    async fn delete_blob(&self, hash: &str) -> Result<()>;
+
+    /// Aborts multipart uploads started more than `older_than` ago and never completed.
+    /// Returns how many were aborted. Backends without multipart uploads have nothing to do.
+    async fn abort_incomplete_uploads(&self, _older_than: Duration) -> Result<usize> {
+        Ok(0)
+    }
+
+    async fn list_manifests(&self) -> Result<Vec<RemoteObject>>;
+    async fn has_manifest(&self, name: &str) -> Result<bool>;
+    async fn get_manifest(&self, name: &str) -> Result<Vec<u8>>;
+    async fn put_manifest(&self, name: &str, data: &[u8]) -> Result<()>;
+    async fn delete_manifest(&self, name: &str) -> Result<()>;
+
+    /// Reads a small bookkeeping object stored under `meta/<name>`, if it exists
+    async fn get_meta(&self, name: &str) -> Result<Option<Vec<u8>>>;
+    async fn put_meta(&self, name: &str, data: &[u8]) -> Result<()>;
+}
 
+/// Transfers between a remote and a local repository, whose store blobs are read
+/// from and written to and whose `.vekt` directory keeps the per-remote caches
 pub struct RemoteClient {
-    bucket: Bucket,
+    remote: Box<dyn Remote>,
+    options: TransferOptions,
+    repo: Repository,
+    hooks: Hooks,
 }
 
 impl RemoteClient {
-    pub fn new(url: &str) -> Result<Self> {
-        // Validate S3 URL format
-        let bucket_name = validate_s3_url(url)?;
-
-        let region = std::env::var("AWS_REGION")
-            .ok()
-            .and_then(|r| Region::from_str(&r).ok())
-            .unwrap_or(Region::UsEast1);
-
-        // Validate credentials exist before proceeding
-        let creds = Credentials::default()
-            .map_err(|e| VektError::CredentialError(format!(
-                "Failed to load AWS credentials. Ensure AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are set, or configure ~/.aws/credentials: {}",
-                e
-            )))?;
-
-        // Verify credentials are not empty
-        if creds.access_key.is_none() || creds.secret_key.is_none() {
-            return Err(VektError::CredentialError(
-                "AWS credentials are empty. Please configure valid credentials.".to_string(),
-            ));
+    /// Creates a client for a remote URL with default settings.
+    /// Supported schemes are `s3://`, `file://`, read-only `http(s)://` and `vekt://`
+    /// for a `vekt serve` instance.
+    pub fn new(repo: &Repository, url: &str) -> Result<Self> {
+        Self::from_config(repo, &RemoteConfig::new(url))
+    }
+
+    /// Creates a client from a remote's configured settings
+    pub fn from_config(repo: &Repository, config: &RemoteConfig) -> Result<Self> {
+        let options = TransferOptions::from_config(config, &repo.settings()?);
+        let remote = build_backend(repo, config, &options)?;
+        Ok(Self {
+            remote,
+            options,
+            repo: repo.clone(),
+            hooks: Hooks::default(),
+        })
+    }
+
+    /// Creates a read-only client over several remotes in priority order, reading
+    /// each object from the first remote that has it. Remotes that cannot be set up
+    /// are skipped with a warning; transfer settings come from the first remote.
+    pub fn with_fallbacks(repo: &Repository, remotes: &[(&str, &RemoteConfig)]) -> Result<Self> {
+        if let [(_, config)] = remotes {
+            return Self::from_config(repo, config);
         }
 
-        let bucket = *Bucket::new(&bucket_name, region, creds).map_err(|e| {
-            VektError::RemoteError(format!("Failed to initialize S3 bucket: {}", e))
+        let settings = repo.settings()?;
+        let mut options = None;
+        let mut backends = Vec::new();
+        for (name, config) in remotes {
+            let remote_options = TransferOptions::from_config(config, &settings);
+            match build_backend(repo, config, &remote_options) {
+                Ok(backend) => {
+                    options.get_or_insert(remote_options);
+                    backends.push((name.to_string(), backend));
+                }
+                Err(e) => {
+                    tracing::warn!(remote = %name, error = %e, "skipping remote that cannot be set up")
+                }
+            }
+        }
+        let options = options.ok_or_else(|| {
+            VektError::RemoteError("No remote in the group could be set up".to_string())
         })?;
+        Ok(Self {
+            remote: Box::new(FallbackRemote::new(backends)),
+            options,
+            repo: repo.clone(),
+            hooks: Hooks::default(),
+        })
+    }
+
+    /// Wraps an existing backend with default transfer options
+    pub fn from_remote(repo: &Repository, remote: Box<dyn Remote>) -> Self {
+        Self {
+            remote,
+            options: TransferOptions::default(),
+            repo: repo.clone(),
+            hooks: Hooks::default(),
+        }
+    }
+
+    /// Reports blob transfers to `hooks.progress`, and stops transfers with
+    /// `VektError::Cancelled` once `hooks.cancel` is cancelled
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Transfer settings in effect for this client
+    pub fn options(&self) -> &TransferOptions {
+        &self.options
+    }
 
-        Ok(Self { bucket })
+    /// Returns the underlying backend
+    pub fn remote(&self) -> &dyn Remote {
+        self.remote.as_ref()
     }
 
-    /// Validates bucket access by attempting a list operation
+    /// Forgets which blobs are known to be on the remote, so the next push checks them all again
+    pub fn clear_known_blobs(&self) -> Result<()> {
+        let mut known = KnownBlobs::load(&self.repo, &self.remote.location());
+        known.clear();
+        known.save()
+    }
+
+    /// Time of the last garbage collection on the remote, 0 if it never ran
+    async fn gc_epoch(&self) -> Result<u64> {
+        let marker = with_retries(self.options.retries, "Reading remote GC marker", || {
+            self.remote.get_meta(GC_EPOCH_META)
+        })
+        .await?;
+        Ok(marker
+            .and_then(|data| String::from_utf8(data).ok())
+            .and_then(|epoch| epoch.trim().parse().ok())
+            .unwrap_or(0))
+    }
+
+    /// Validates remote access before any transfer is attempted
     pub async fn validate_access(&self) -> Result<()> {
-        self.bucket
-            .list("/".to_string(), Some("/".to_string()))
-            .await
-            .map_err(|e| {
-                VektError::RemoteError(format!(
-                    "Failed to access S3 bucket. Check bucket name and permissions: {}",
-                    e
-                ))
-            })?;
-        Ok(())
+        self.remote.validate_access().await
+    }
+
+    /// Uploads the manifest's missing blobs, then the manifest itself.
+    /// Fails with `ConflictDetected` if the remote manifest changed since it was last
+    /// fetched or pushed from here, unless `force` is set.
+    pub async fn push(
+        &self,
+        manifest: &VektManifest,
+        manifest_name: &str,
+        force: bool,
+    ) -> Result<()> {
+        let mut results = self.push_all(&[(manifest_name, manifest)], force).await?;
+        results.pop().map_or(Ok(()), |(_, result)| result)
     }
 
-    pub async fn push(&self, manifest: &VektManifest, manifest_name: &str) -> Result<()> {
-        // Check for existing manifest and warn about conflicts
-        let manifest_path = format!("manifests/{}", manifest_name);
-        if let Ok((_, 200)) = self.bucket.head_object(&manifest_path).await {
-            eprintln!(
-                "Warning: Manifest '{}' already exists on remote. This will overwrite the existing version.",
-                manifest_name
-            );
+    /// Pushes several manifests, uploading each blob they share only once.
+    /// A failed blob upload aborts the whole push; conflicts and manifest upload
+    /// failures are reported per manifest, in the order given.
+    #[tracing::instrument(name = "push", skip_all, fields(remote = %self.remote.location(), manifests = manifests.len()))]
+    pub async fn push_all(
+        &self,
+        manifests: &[(&str, &VektManifest)],
+        force: bool,
+    ) -> Result<Vec<(String, Result<()>)>> {
+        // Fail before transferring anything rather than after the first blob
+        if self.remote.is_read_only() {
+            return Err(VektError::ReadOnlyRemote(format!(
+                "cannot push to {}",
+                self.remote.location()
+            )));
         }
 
-        println!("Pushing {} blobs to remote...", manifest.tensors.len());
+        let mut bases = ManifestBases::load(&self.repo, &self.remote.location());
+        let mut results: Vec<Option<Result<()>>> = Vec::with_capacity(manifests.len());
+        let mut ready = Vec::new();
+        for (index, (name, manifest)) in manifests.iter().enumerate() {
+            let json = serde_json::to_string_pretty(manifest).map_err(VektError::Json)?;
+            let new_hash = blobs::compute_blob_hash(json.as_bytes());
+            if !force && let Err(e) = self.check_manifest_base(&bases, name, &new_hash).await {
+                results.push(Some(Err(e)));
+                continue;
+            }
+            results.push(None);
+            ready.push((index, *name, *manifest, json, new_hash));
+        }
 
-        let mut uploaded = 0;
-        let mut skipped = 0;
-
-        let tasks = stream::iter(manifest.tensors.values())
-            .map(|tensor| {
-                let hash = tensor.hash.clone();
-                async move {
-                    let blob_path = blobs::get_blob_path(&hash);
-                    let remote_path = format!("blobs/{}", hash);
-
-                    // Check if blob already exists on remote (avoid re-upload)
-                    match self.bucket.head_object(&remote_path).await {
-                        Ok((_, 200)) => Ok::<(bool, String), VektError>((false, hash)),
-                        _ => {
-                            if !blob_path.exists() {
-                                return Err(VektError::BlobNotFound(format!(
-                                    "Blob {} not found locally for upload",
-                                    hash
-                                )));
-                            }
-
-                            let mut file = File::open(&blob_path).await.map_err(|e| {
-                                VektError::Io(std::io::Error::other(format!(
-                                    "Failed to open blob {}: {}",
-                                    hash, e
-                                )))
-                            })?;
-
-                            let response = self
-                                .bucket
-                                .put_object_stream(&mut file, &remote_path)
-                                .await
-                                .map_err(|e| {
-                                    VektError::RemoteError(format!(
-                                        "Failed to upload blob {}: {}",
-                                        hash, e
-                                    ))
-                                })?;
-
-                            if response.status_code() != 200 {
-                                return Err(VektError::RemoteError(format!(
-                                    "Failed to upload blob {}, status: {}",
-                                    hash,
-                                    response.status_code()
-                                )));
-                            }
-
-                            Ok((true, hash))
-                        }
-                    }
+        let mut seen = HashSet::new();
+        let hashes: Vec<String> = ready
+            .iter()
+            .flat_map(|(_, _, manifest, _, _)| manifest.tensors.values())
+            .filter(|tensor| seen.insert(tensor.hash.as_str()))
+            .map(|tensor| tensor.hash.clone())
+            .collect();
+        let epoch = self.upload_blobs(&hashes).await?;
+
+        let mut pushed = false;
+        for (index, name, _, json, new_hash) in ready {
+            // Check again: someone may have pushed while the blobs were uploading
+            if !force && let Err(e) = self.check_manifest_base(&bases, name, &new_hash).await {
+                results[index] = Some(Err(e));
+                continue;
+            }
+
+            // Manifest goes last so it never references blobs the remote doesn't have yet
+            let uploaded = with_retries(
+                self.options.retries,
+                &format!("Uploading manifest {}", name),
+                || self.remote.put_manifest(name, json.as_bytes()),
+            )
+            .await;
+            let result = match uploaded {
+                Ok(()) => bases.record(name, new_hash),
+                Err(e) => Err(e),
+            };
+            pushed |= result.is_ok();
+            results[index] = Some(result);
+        }
+
+        // A remote GC since the existence check may have deleted blobs this push found
+        // already present; it can't delete them again now the manifests reference them
+        if pushed && self.gc_epoch().await? != epoch {
+            warn!("remote was garbage collected during the push, checking its blobs again");
+            self.upload_blobs(&hashes).await?;
+        }
+
+        Ok(manifests
+            .iter()
+            .zip(results)
+            .map(|((name, _), result)| (name.to_string(), result.unwrap_or(Ok(()))))
+            .collect())
+    }
+
+    /// Uploads the blobs the remote doesn't have yet. Returns the remote's GC epoch the
+    /// existence checks were made against.
+    #[tracing::instrument(name = "upload", skip_all, fields(blobs = hashes.len()))]
+    async fn upload_blobs(&self, hashes: &[String]) -> Result<u64> {
+        let started = Instant::now();
+        // Blobs an earlier push already confirmed need no round trip at all;
+        // the rest are checked in one batch instead of one request per blob
+        let mut known = KnownBlobs::load(&self.repo, &self.remote.location());
+        let epoch = self.gc_epoch().await?;
+        known.sync_gc_epoch(epoch);
+        let unknown: Vec<String> = hashes
+            .iter()
+            .filter(|hash| !known.contains(hash))
+            .cloned()
+            .collect();
+        let present = if unknown.is_empty() {
+            HashSet::new()
+        } else {
+            self.hooks
+                .cancel
+                .run(with_retries(
+                    self.options.retries,
+                    "Checking remote blobs",
+                    || self.remote.existing_blobs(&unknown),
+                ))
+                .await?
+        };
+        for hash in &present {
+            known.insert(hash.clone());
+        }
+        let missing: Vec<&String> = unknown.iter().filter(|h| !present.contains(*h)).collect();
+
+        let store = &self.repo.store();
+        let blob_size = |hash: &str| {
+            std::fs::metadata(blobs::blob_path_in(store, hash)).map_or(0, |meta| meta.len())
+        };
+        let progress = &self.hooks.progress;
+        progress.started(
+            Stage::Upload,
+            hashes.len(),
+            Some(hashes.iter().map(|hash| blob_size(hash)).sum()),
+        );
+        // Everything known to be on the remote by now needs no upload
+        for hash in hashes.iter().filter(|hash| known.contains(hash)) {
+            progress.blob_skipped(Stage::Upload, hash, blob_size(hash));
+        }
+
+        let tasks = stream::iter(missing)
+            .map(|hash| async move {
+                let blob_path = blobs::blob_path_in(store, hash);
+                if !blob_path.exists() {
+                    return Err(VektError::BlobNotFound(format!(
+                        "Blob {} not found locally for upload",
+                        hash
+                    )));
+                }
+
+                // Remotes keep blobs as their hash says, so a compressed one is sent decoded
+                let decoded = {
+                    let (store, hash) = (store.clone(), hash.clone());
+                    tokio::task::spawn_blocking(move || compression::decoded_copy_in(&store, &hash))
+                        .await
+                        .map_err(|e| {
+                            VektError::RemoteError(format!("Decoding task failed: {}", e))
+                        })??
+                };
+                let upload_path = decoded.as_deref().unwrap_or(&blob_path);
+
+                let blob_started = Instant::now();
+                let uploaded = self
+                    .hooks
+                    .cancel
+                    .run(with_retries(
+                        self.options.retries,
+                        &format!("Uploading blob {}", hash),
+                        || self.remote.put_blob(hash, upload_path),
+                    ))
+                    .await;
+                if let Some(path) = &decoded {
+                    let _ = tokio::fs::remove_file(path).await;
                 }
+                uploaded?;
+                let bytes = blob_size(hash);
+                debug!(
+                    hash = %hash,
+                    bytes,
+                    elapsed_ms = blob_started.elapsed().as_millis() as u64,
+                    "uploaded blob"
+                );
+                progress.blob_done(Stage::Upload, hash, bytes);
+                Ok::<&String, VektError>(hash)
             })
-            .buffer_unordered(10);
+            .buffer_unordered(self.options.concurrency);
 
         let results: Vec<_> = tasks.collect().await;
+        let mut first_error = None;
+        let mut uploaded = 0;
         for res in results {
-            let (was_uploaded, hash) = res?;
-            if was_uploaded {
-                uploaded += 1;
-                println!("Uploaded blob {}", hash);
-            } else {
-                skipped += 1;
+            match res {
+                Ok(hash) => {
+                    uploaded += 1;
+                    known.insert(hash.clone());
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
             }
         }
 
-        println!(
-            "Upload complete: {} uploaded, {} skipped (already on remote)",
-            uploaded, skipped
+        // Remember what made it even if some uploads failed, so a retry skips them
+        known.save()?;
+        if let Some(e) = first_error {
+            return Err(e);
+        }
+        progress.finished(Stage::Upload);
+        info!(
+            uploaded,
+            skipped = hashes.len() - uploaded,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "upload complete"
         );
+        Ok(epoch)
+    }
 
-        // Upload manifest with atomic-like behavior (S3 PUT is atomic)
-        let json = serde_json::to_string_pretty(manifest).map_err(VektError::Json)?;
-
-        self.bucket
-            .put_object(&manifest_path, json.as_bytes())
-            .await
-            .map_err(|e| {
-                VektError::RemoteError(format!(
-                    "Failed to upload manifest {}: {}",
-                    manifest_name, e
-                ))
-            })?;
+    /// Errors if the remote manifest is neither the version the local copy is based on
+    /// nor identical to the one about to be pushed
+    async fn check_manifest_base(
+        &self,
+        bases: &ManifestBases,
+        manifest_name: &str,
+        new_hash: &str,
+    ) -> Result<()> {
+        let what = format!("Checking manifest {}", manifest_name);
+        let exists = with_retries(self.options.retries, &what, || {
+            self.remote.has_manifest(manifest_name)
+        })
+        .await?;
+        if !exists {
+            return Ok(());
+        }
+        let remote = with_retries(self.options.retries, &what, || {
+            self.remote.get_manifest(manifest_name)
+        })
+        .await?;
+        let remote_hash = blobs::compute_blob_hash(&remote);
+        if remote_hash == new_hash || bases.get(manifest_name) == Some(remote_hash.as_str()) {
+            return Ok(());
+        }
 
-        println!("Uploaded manifest {}", manifest_name);
-        Ok(())
+        let reason = if bases.get(manifest_name).is_some() {
+            "changed on the remote since it was last pulled"
+        } else {
+            "already exists on the remote and was never pulled here"
+        };
+        Err(VektError::ConflictDetected(format!(
+            "manifest '{}' {}; pull it first or push with --force to overwrite",
+            manifest_name, reason
+        )))
     }
 
     pub async fn pull(&self, manifest_name: &str) -> Result<VektManifest> {
-        let manifest_path = format!("manifests/{}", manifest_name);
+        let manifest = self.fetch_manifest(manifest_name).await?;
+        let hashes: Vec<String> = manifest.tensors.values().map(|t| t.hash.clone()).collect();
+        self.fetch_blobs(&hashes).await?;
+        Ok(manifest)
+    }
 
-        let response_data = self.bucket.get_object(&manifest_path).await.map_err(|e| {
-            VektError::RemoteError(format!(
-                "Failed to download manifest '{}': {}. Ensure the manifest exists on remote.",
-                manifest_name, e
-            ))
-        })?;
+    /// Fetches several manifests and, if `with_blobs` is set, downloads each blob they
+    /// reference once. A failed blob download aborts the pull; missing or invalid
+    /// manifests are reported per name, in the order given.
+    pub async fn pull_all(
+        &self,
+        manifest_names: &[&str],
+        with_blobs: bool,
+    ) -> Result<Vec<(String, Result<VektManifest>)>> {
+        let mut results = Vec::with_capacity(manifest_names.len());
+        for name in manifest_names {
+            results.push((name.to_string(), self.fetch_manifest(name).await));
+        }
+
+        if with_blobs {
+            let mut seen = HashSet::new();
+            let hashes: Vec<String> = results
+                .iter()
+                .filter_map(|(_, result)| result.as_ref().ok())
+                .flat_map(|manifest| manifest.tensors.values())
+                .filter(|tensor| seen.insert(tensor.hash.as_str()))
+                .map(|tensor| tensor.hash.clone())
+                .collect();
+            self.fetch_blobs(&hashes).await?;
+        }
+
+        Ok(results)
+    }
+
+    /// Downloads only the blobs needed to restore the tensors matching `filter`
+    /// that are missing from the repository's store. Returns the number of blobs downloaded.
+    pub async fn fetch_missing(
+        &self,
+        manifest: &VektManifest,
+        filter: Option<&str>,
+    ) -> Result<usize> {
+        let missing = manifest.missing_blobs(&self.repo, filter);
+        if missing.is_empty() {
+            return Ok(0);
+        }
+        self.fetch_blobs(&missing).await
+    }
+
+    /// Lists the manifests stored on the remote, sorted by name
+    pub async fn list_manifests(&self) -> Result<Vec<RemoteObject>> {
+        let mut manifests = self.remote.list_manifests().await?;
+        manifests.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(manifests)
+    }
+
+    /// Downloads a manifest, and optionally its blobs, writing it to `dest_dir/<manifest_name>`.
+    /// Works for manifests that do not exist locally yet.
+    pub async fn fetch(
+        &self,
+        manifest_name: &str,
+        dest_dir: &Path,
+        with_blobs: bool,
+    ) -> Result<VektManifest> {
+        // The name comes from the remote, so it must not escape the destination
+        validate_path_safe(manifest_name)?;
+
+        let manifest = if with_blobs {
+            self.pull(manifest_name).await?
+        } else {
+            self.fetch_manifest(manifest_name).await?
+        };
+
+        let path = dest_dir.join(manifest_name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&manifest)?;
+        write_file_atomic(&path, json.as_bytes())?;
 
-        let bytes = response_data.bytes();
-        let manifest: VektManifest = serde_json::from_slice(bytes).map_err(|e| {
+        Ok(manifest)
+    }
+
+    /// Downloads a manifest from the remote without fetching any of its blobs
+    #[tracing::instrument(skip(self), fields(remote = %self.remote.location()))]
+    pub async fn fetch_manifest(&self, manifest_name: &str) -> Result<VektManifest> {
+        let bytes = with_retries(
+            self.options.retries,
+            &format!("Downloading manifest {}", manifest_name),
+            || self.remote.get_manifest(manifest_name),
+        )
+        .await?;
+        let manifest: VektManifest = serde_json::from_slice(&bytes).map_err(|e| {
             VektError::InvalidManifest(format!(
                 "Failed to parse manifest '{}': {}",
                 manifest_name, e
             ))
         })?;
 
-        println!(
-            "Downloading {} blobs from remote...",
-            manifest.tensors.len()
-        );
-
-        let mut downloaded = 0;
-        let mut skipped = 0;
+        // Later pushes of this manifest are checked against the version seen here
+        let hash = blobs::compute_blob_hash(&bytes);
+        for location in self.remote.member_locations() {
+            ManifestBases::load(&self.repo, &location).record(manifest_name, hash.clone())?;
+        }
 
-        let tasks = stream::iter(manifest.tensors.values())
-            .map(|tensor| {
-                let hash = tensor.hash.clone();
-                async move {
-                    let blob_path = blobs::get_blob_path(&hash);
+        Ok(manifest)
+    }
 
-                    // Skip if blob already exists locally
-                    if blob_path.exists() {
-                        return Ok::<bool, VektError>(false);
-                    }
+    /// Downloads one blob, resuming the partial file after transient failures
+    async fn download_blob(&self, hash: &str, dest: &Path) -> Result<()> {
+        self.hooks
+            .cancel
+            .run(with_retries(
+                self.options.retries,
+                &format!("Downloading blob {}", hash),
+                || self.remote.get_blob(hash, dest),
+            ))
+            .await
+    }
 
-                    let remote_path = format!("blobs/{}", hash);
-
-                    let mut stream =
-                        self.bucket
-                            .get_object_stream(&remote_path)
-                            .await
-                            .map_err(|e| {
-                                VektError::RemoteError(format!(
-                                    "Failed to download blob {}: {}",
-                                    hash, e
-                                ))
-                            })?;
-
-                    // Write to temp file first, then rename for atomicity
-                    let tmp_path = blob_path.with_extension("tmp");
-                    let mut file = File::create(&tmp_path).await.map_err(|e| {
-                        VektError::Io(std::io::Error::other(format!(
-                            "Failed to create temp file for blob {}: {}",
-                            hash, e
-                        )))
-                    })?;
+    /// Downloads the given blobs into the repository's store, skipping those already present
+    /// and repeated hashes. Returns the number of blobs downloaded.
+    #[tracing::instrument(name = "download", skip_all, fields(remote = %self.remote.location(), blobs = hashes.len()))]
+    pub async fn fetch_blobs(&self, hashes: &[String]) -> Result<usize> {
+        let mut downloaded = 0;
+        let started = Instant::now();
+
+        // Make sure the store exists before writing temp files into it
+        let store = &self.repo.store();
+        tokio::fs::create_dir_all(store).await?;
+
+        // Tied tensors share a blob, and two downloads of it would append to one temp file
+        let mut seen = HashSet::new();
+        let hashes: Vec<&String> = hashes.iter().filter(|h| seen.insert(h.as_str())).collect();
+
+        let progress = &self.hooks.progress;
+        let total = hashes.len();
+        progress.started(Stage::Download, total, None);
+        let tasks = stream::iter(hashes)
+            .map(|hash| async move {
+                let blob_path = blobs::blob_path_in(store, hash);
+
+                // Skip if blob already exists locally
+                if let Ok(meta) = tokio::fs::metadata(&blob_path).await {
+                    progress.blob_skipped(Stage::Download, hash, meta.len());
+                    return Ok::<bool, VektError>(false);
+                }
 
-                    tokio::io::copy(&mut stream, &mut file).await.map_err(|e| {
-                        VektError::Io(std::io::Error::other(format!(
-                            "Failed to write blob {}: {}",
-                            hash, e
-                        )))
-                    })?;
+                // Write to temp file first, then verify and rename for atomicity.
+                // A temp file left by an interrupted pull is resumed rather than restarted.
+                let blob_started = Instant::now();
+                let tmp_path = blob_path.with_extension("tmp");
+                let resumed = tokio::fs::try_exists(&tmp_path).await.unwrap_or(false);
+                self.download_blob(hash, &tmp_path).await?;
+
+                if let Err(e) = verify_download(&self.repo, hash, &tmp_path).await {
+                    // The partial file we resumed from may itself have been bad, so start over once
+                    if !resumed {
+                        return Err(e);
+                    }
+                    self.download_blob(hash, &tmp_path).await?;
+                    verify_download(&self.repo, hash, &tmp_path).await?;
+                }
 
-                    // Ensure data is flushed
-                    file.sync_all().await.map_err(|e| {
+                // Atomic rename
+                tokio::fs::rename(&tmp_path, &blob_path)
+                    .await
+                    .map_err(|e| {
                         VektError::Io(std::io::Error::other(format!(
-                            "Failed to sync blob {}: {}",
+                            "Failed to finalize blob {}: {}",
                             hash, e
                         )))
                     })?;
 
-                    drop(file);
-
-                    // Atomic rename
-                    tokio::fs::rename(&tmp_path, &blob_path)
-                        .await
-                        .map_err(|e| {
-                            VektError::Io(std::io::Error::other(format!(
-                                "Failed to finalize blob {}: {}",
-                                hash, e
-                            )))
-                        })?;
-
-                    Ok(true)
-                }
+                let size = tokio::fs::metadata(&blob_path).await?.len();
+                debug!(
+                    hash = %hash,
+                    bytes = size,
+                    resumed,
+                    elapsed_ms = blob_started.elapsed().as_millis() as u64,
+                    "downloaded blob"
+                );
+                progress.blob_done(Stage::Download, hash, size);
+                Ok(true)
             })
-            .buffer_unordered(10);
+            .buffer_unordered(self.options.concurrency);
 
         let results: Vec<_> = tasks.collect().await;
         for res in results {
             if res? {
                 downloaded += 1;
-            } else {
-                skipped += 1;
             }
         }
-
-        println!(
-            "Download complete: {} downloaded, {} skipped (already local)",
-            downloaded, skipped
+        progress.finished(Stage::Download);
+        info!(
+            downloaded,
+            skipped = total - downloaded,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "download complete"
         );
 
-        Ok(manifest)
+        Ok(downloaded)
     }
 }
+
+/// Builds the backend for a remote URL with its transfer settings applied
+fn build_backend(
+    repo: &Repository,
+    config: &RemoteConfig,
+    options: &TransferOptions,
+) -> Result<Box<dyn Remote>> {
+    let url = config.url.as_str();
+    if url.starts_with("s3://") {
+        Ok(Box::new(
+            S3Remote::new(config, repo.vekt_dir().join("uploads"))?
+                .with_transfer_options(options)?,
+        ))
+    } else if url.starts_with("file://") {
+        Ok(Box::new(
+            FsRemote::new(validate_file_url(url)?).with_transfer_options(options),
+        ))
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(Box::new(
+            HttpRemote::new(validate_http_url(url)?).with_transfer_options(options)?,
+        ))
+    } else if url.starts_with("vekt://") || url.starts_with("vekts://") {
+        Ok(Box::new(
+            VektRemote::new(url, validate_vekt_url(url)?).with_transfer_options(options)?,
+        ))
+    } else {
+        Err(VektError::InvalidRemoteUrl(format!(
+            "Unsupported remote URL '{}'. Expected s3://, file://, http(s):// or vekt://",
+            url
+        )))
+    }
+}
+
+/// Checks a downloaded blob against its expected hash before it is admitted into the store.
+/// Corrupt downloads are moved to `.vekt/quarantine` for inspection.
+async fn verify_download(repo: &Repository, hash: &str, tmp_path: &Path) -> Result<()> {
+    let path = tmp_path.to_path_buf();
+    let expected = hash.to_string();
+    let store = repo.store();
+    // Large blobs get their outboard tree while being hashed anyway
+    let actual = tokio::task::spawn_blocking(move || {
+        blobs::hash_file_with_outboard(&path, &store, &expected)
+    })
+    .await
+    .map_err(|e| VektError::RemoteError(format!("Hashing task failed: {}", e)))??;
+
+    if actual == hash {
+        return Ok(());
+    }
+
+    let quarantine_dir = repo.vekt_dir().join("quarantine");
+    tokio::fs::create_dir_all(&quarantine_dir).await?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    tokio::fs::rename(
+        tmp_path,
+        quarantine_dir.join(format!("{}.{}", hash, timestamp)),
+    )
+    .await?;
+
+    Err(VektError::HashMismatch {
+        expected: hash.to_string(),
+        actual,
+    })
+}
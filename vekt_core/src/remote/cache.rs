@@ -0,0 +1,145 @@
+use crate::blobs;
+use crate::errors::Result;
+use crate::repository::Repository;
+use crate::utils::write_file_atomic;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Path of a per-remote cache file under `.vekt/remotes`, keyed by the remote's location
+fn cache_path(repo: &Repository, location: &str, extension: &str) -> PathBuf {
+    let id = blobs::compute_blob_hash(location.as_bytes());
+    repo.vekt_dir()
+        .join("remotes")
+        .join(format!("{}.{}", &id[..32], extension))
+}
+
+/// Header line recording which remote garbage collection the cache postdates
+const GC_EPOCH_HEADER: &str = "# gc-epoch ";
+
+/// Local record of blobs known to be present on a remote, stored as one hash per
+/// line under `.vekt/remotes`. Lets a push skip existence checks for blobs an
+/// earlier push or listing already confirmed.
+pub struct KnownBlobs {
+    path: PathBuf,
+    hashes: HashSet<String>,
+    gc_epoch: u64,
+    dirty: bool,
+}
+
+impl KnownBlobs {
+    /// Loads `repo`'s cache for the remote identified by `location`.
+    /// A missing or unreadable cache is treated as empty.
+    pub fn load(repo: &Repository, location: &str) -> Self {
+        let path = cache_path(repo, location, "known");
+
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let gc_epoch = content
+            .lines()
+            .find_map(|line| line.strip_prefix(GC_EPOCH_HEADER))
+            .and_then(|epoch| epoch.trim().parse().ok())
+            .unwrap_or(0);
+        let hashes = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect();
+
+        Self {
+            path,
+            hashes,
+            gc_epoch,
+            dirty: false,
+        }
+    }
+
+    /// Forgets everything if the remote was garbage collected since the cache was
+    /// written, as blobs it lists may have been deleted
+    pub fn sync_gc_epoch(&mut self, gc_epoch: u64) {
+        if gc_epoch != self.gc_epoch {
+            self.clear();
+            self.gc_epoch = gc_epoch;
+            self.dirty = true;
+        }
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    pub fn insert(&mut self, hash: String) {
+        self.dirty |= self.hashes.insert(hash);
+    }
+
+    pub fn remove(&mut self, hash: &str) {
+        self.dirty |= self.hashes.remove(hash);
+    }
+
+    /// Forgets everything, e.g. after blobs were deleted from the remote
+    pub fn clear(&mut self) {
+        self.dirty |= !self.hashes.is_empty();
+        self.hashes.clear();
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut sorted: Vec<&String> = self.hashes.iter().collect();
+        sorted.sort();
+        let mut content = String::with_capacity(sorted.len() * 65 + 32);
+        if self.gc_epoch > 0 {
+            content.push_str(&format!("{}{}\n", GC_EPOCH_HEADER, self.gc_epoch));
+        }
+        for hash in sorted {
+            content.push_str(hash);
+            content.push('\n');
+        }
+        write_file_atomic(&self.path, content.as_bytes())?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// Hashes of the remote manifests the local copies are based on, recorded whenever a
+/// manifest is fetched or pushed. A push compares the remote manifest against this
+/// base to detect that someone else replaced it in the meantime.
+pub struct ManifestBases {
+    path: PathBuf,
+    bases: HashMap<String, String>,
+}
+
+impl ManifestBases {
+    /// Loads `repo`'s bases for the remote identified by `location`.
+    /// A missing or unreadable record is treated as empty.
+    pub fn load(repo: &Repository, location: &str) -> Self {
+        let path = cache_path(repo, location, "bases.json");
+        let bases = std::fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Self { path, bases }
+    }
+
+    pub fn get(&self, manifest_name: &str) -> Option<&str> {
+        self.bases.get(manifest_name).map(String::as_str)
+    }
+
+    /// Records `hash` as the base of `manifest_name` and saves immediately
+    pub fn record(&mut self, manifest_name: &str, hash: String) -> Result<()> {
+        if self.get(manifest_name) == Some(hash.as_str()) {
+            return Ok(());
+        }
+        self.bases.insert(manifest_name.to_string(), hash);
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(&self.bases)?;
+        write_file_atomic(&self.path, &json)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use super::{Remote, RemoteObject};
+use crate::errors::{Result, VektError};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Read-only view over several remotes in priority order. Each read goes to the
+/// first remote that can serve it, moving on when a remote is unreachable or
+/// lacks the object. Writes go to each remote separately.
+pub struct FallbackRemote {
+    remotes: Vec<(String, Box<dyn Remote>)>,
+}
+
+impl FallbackRemote {
+    pub fn new(remotes: Vec<(String, Box<dyn Remote>)>) -> Self {
+        Self { remotes }
+    }
+
+    fn read_only(&self, operation: &str) -> VektError {
+        VektError::ReadOnlyRemote(format!(
+            "cannot {} through a group; push to each remote instead",
+            operation
+        ))
+    }
+
+    fn no_remotes() -> VektError {
+        VektError::RemoteError("Remote group has no members".to_string())
+    }
+
+    /// Error for a listing no member answered, kept `Unsupported` when no member can list
+    fn unlisted(&self, all_unsupported: bool) -> VektError {
+        if all_unsupported && !self.remotes.is_empty() {
+            VektError::Unsupported("no remote in the group can list its contents".to_string())
+        } else {
+            VektError::RemoteError("No remote in the group could be listed".to_string())
+        }
+    }
+}
+
+/// Tries `$op` on each remote in order, returning the first success
+/// or the last remote's error
+macro_rules! first_success {
+    ($self:ident, |$remote:ident| $op:expr, $what:expr) => {{
+        let mut last_error = None;
+        for (name, $remote) in &$self.remotes {
+            match $op.await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    if $self.remotes.len() > 1 {
+                        tracing::warn!(remote = %name, error = %e, "{} failed, trying next remote", $what);
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(Self::no_remotes))
+    }};
+}
+
+#[async_trait]
+impl Remote for FallbackRemote {
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn location(&self) -> String {
+        format!("group:{}", self.member_locations().join(","))
+    }
+
+    fn member_locations(&self) -> Vec<String> {
+        self.remotes.iter().map(|(_, r)| r.location()).collect()
+    }
+
+    async fn validate_access(&self) -> Result<()> {
+        first_success!(self, |remote| remote.validate_access(), "Access check")
+    }
+
+    /// Union of the blobs on every reachable remote
+    async fn list_blobs(&self) -> Result<Vec<RemoteObject>> {
+        let mut blobs = BTreeMap::new();
+        let mut reachable = false;
+        let mut all_unsupported = true;
+        for (name, remote) in &self.remotes {
+            match remote.list_blobs().await {
+                Ok(objects) => {
+                    reachable = true;
+                    for object in objects {
+                        blobs.entry(object.name.clone()).or_insert(object);
+                    }
+                }
+                Err(e) => {
+                    all_unsupported &= matches!(e, VektError::Unsupported(_));
+                    tracing::warn!(remote = %name, error = %e, "listing blobs failed")
+                }
+            }
+        }
+        if !reachable {
+            return Err(self.unlisted(all_unsupported));
+        }
+        Ok(blobs.into_values().collect())
+    }
+
+    async fn has_blob(&self, hash: &str) -> Result<bool> {
+        let mut last_error = None;
+        for (_, remote) in &self.remotes {
+            match remote.has_blob(hash).await {
+                Ok(true) => return Ok(true),
+                Ok(false) => last_error = None,
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(e) => Err(e),
+            None => Ok(false),
+        }
+    }
+
+    async fn existing_blobs(&self, hashes: &[String]) -> Result<HashSet<String>> {
+        let mut present = HashSet::new();
+        for (name, remote) in &self.remotes {
+            let remaining: Vec<String> = hashes
+                .iter()
+                .filter(|hash| !present.contains(*hash))
+                .cloned()
+                .collect();
+            if remaining.is_empty() {
+                break;
+            }
+            match remote.existing_blobs(&remaining).await {
+                Ok(found) => present.extend(found),
+                Err(e) => tracing::warn!(remote = %name, error = %e, "checking blobs failed"),
+            }
+        }
+        Ok(present)
+    }
+
+    async fn get_blob(&self, hash: &str, dest: &Path) -> Result<()> {
+        // Content is addressed by hash, so a partial file from one remote can be
+        // resumed from another; the caller verifies the result either way
+        first_success!(
+            self,
+            |remote| remote.get_blob(hash, dest),
+            format!("Downloading blob {}", hash)
+        )
+    }
+
+    async fn put_blob(&self, _hash: &str, _src: &Path) -> Result<()> {
+        Err(self.read_only("upload blobs"))
+    }
+
+    async fn delete_blob(&self, _hash: &str) -> Result<()> {
+        Err(self.read_only("delete blobs"))
+    }
+
+    /// Union of the manifests on every reachable remote, preferring earlier remotes
+    async fn list_manifests(&self) -> Result<Vec<RemoteObject>> {
+        let mut manifests = BTreeMap::new();
+        let mut reachable = false;
+        let mut all_unsupported = true;
+        for (name, remote) in &self.remotes {
+            match remote.list_manifests().await {
+                Ok(objects) => {
+                    reachable = true;
+                    for object in objects {
+                        manifests.entry(object.name.clone()).or_insert(object);
+                    }
+                }
+                Err(e) => {
+                    all_unsupported &= matches!(e, VektError::Unsupported(_));
+                    tracing::warn!(remote = %name, error = %e, "listing manifests failed")
+                }
+            }
+        }
+        if !reachable {
+            return Err(self.unlisted(all_unsupported));
+        }
+        Ok(manifests.into_values().collect())
+    }
+
+    async fn has_manifest(&self, name: &str) -> Result<bool> {
+        for (_, remote) in &self.remotes {
+            if let Ok(true) = remote.has_manifest(name).await {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    async fn get_manifest(&self, name: &str) -> Result<Vec<u8>> {
+        first_success!(
+            self,
+            |remote| remote.get_manifest(name),
+            format!("Downloading manifest {}", name)
+        )
+    }
+
+    async fn put_manifest(&self, _name: &str, _data: &[u8]) -> Result<()> {
+        Err(self.read_only("upload manifests"))
+    }
+
+    async fn delete_manifest(&self, _name: &str) -> Result<()> {
+        Err(self.read_only("delete manifests"))
+    }
+
+    async fn get_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        first_success!(self, |remote| remote.get_meta(name), "Reading metadata")
+    }
+
+    async fn put_meta(&self, _name: &str, _data: &[u8]) -> Result<()> {
+        Err(self.read_only("write metadata"))
+    }
+}
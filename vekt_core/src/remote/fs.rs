@@ -0,0 +1,262 @@
+use super::transfer::Throttled;
+use super::{Remote, RemoteObject, Throttle, TransferOptions};
+use crate::errors::{Result, VektError};
+use crate::validation::validate_path_safe;
+use async_trait::async_trait;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+/// Remote backed by a directory, typically on a shared network filesystem.
+/// Uses the same `blobs/<hash>` and `manifests/<name>` layout as the S3 remote.
+pub struct FsRemote {
+    root: PathBuf,
+    throttle: Option<Arc<Throttle>>,
+}
+
+impl FsRemote {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            throttle: None,
+        }
+    }
+
+    /// Applies bandwidth limits; a shared filesystem is often a network mount
+    pub fn with_transfer_options(mut self, options: &TransferOptions) -> Self {
+        self.throttle = options.throttle();
+        self
+    }
+
+    fn blob_path(&self, hash: &str) -> Result<PathBuf> {
+        validate_path_safe(hash)?;
+        Ok(self.root.join("blobs").join(hash))
+    }
+
+    fn manifest_path(&self, name: &str) -> Result<PathBuf> {
+        validate_path_safe(name)?;
+        Ok(self.root.join("manifests").join(name))
+    }
+
+    /// Copies `src` to `dest` through a temp file + rename so readers never see partial files
+    async fn copy_atomic(&self, src: &Path, dest: &Path) -> Result<()> {
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = TempPath::new(dest);
+        let mut source = Throttled::new(File::open(src).await?, self.throttle.clone());
+        let mut tmp = File::create(tmp_path.path()).await?;
+        tokio::io::copy(&mut source, &mut tmp).await?;
+        tmp.flush().await?;
+        tmp_path.persist(dest).await
+    }
+}
+
+async fn write_atomic(dest: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let tmp_path = TempPath::new(dest);
+    tokio::fs::write(tmp_path.path(), data).await?;
+    tmp_path.persist(dest).await
+}
+
+/// Temp file removed when dropped unless renamed into place, so failed or
+/// cancelled writes don't leave it behind
+struct TempPath(Option<PathBuf>);
+
+impl TempPath {
+    fn new(dest: &Path) -> Self {
+        Self(Some(tmp_path_for(dest)))
+    }
+
+    fn path(&self) -> &Path {
+        self.0.as_deref().unwrap_or(Path::new(""))
+    }
+
+    async fn persist(mut self, dest: &Path) -> Result<()> {
+        tokio::fs::rename(self.path(), dest).await?;
+        self.0 = None;
+        Ok(())
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
+
+/// Recursively lists regular files under `dir`, naming them relative to `base` with `/` separators
+fn list_files(base: &Path, dir: &Path, out: &mut Vec<RemoteObject>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            list_files(base, &path, out)?;
+            continue;
+        }
+
+        let Ok(relative) = path.strip_prefix(base) else {
+            continue;
+        };
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        // Skip in-flight uploads from other writers
+        if name.ends_with(".tmp") {
+            continue;
+        }
+
+        out.push(RemoteObject {
+            name,
+            size: metadata.len(),
+            last_modified: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+        });
+    }
+    Ok(())
+}
+
+async fn list_dir(dir: PathBuf) -> Result<Vec<RemoteObject>> {
+    tokio::task::spawn_blocking(move || {
+        let mut objects = Vec::new();
+        if dir.is_dir() {
+            list_files(&dir, &dir, &mut objects)?;
+        }
+        Ok(objects)
+    })
+    .await
+    .map_err(|e| VektError::RemoteError(format!("Listing task failed: {}", e)))?
+}
+
+#[async_trait]
+impl Remote for FsRemote {
+    fn location(&self) -> String {
+        format!("file://{}", self.root.display())
+    }
+
+    async fn validate_access(&self) -> Result<()> {
+        let metadata = tokio::fs::metadata(&self.root).await.map_err(|e| {
+            VektError::RemoteError(format!(
+                "Failed to access remote directory {}: {}",
+                self.root.display(),
+                e
+            ))
+        })?;
+        if !metadata.is_dir() {
+            return Err(VektError::RemoteError(format!(
+                "Remote path {} is not a directory",
+                self.root.display()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn list_blobs(&self) -> Result<Vec<RemoteObject>> {
+        list_dir(self.root.join("blobs")).await
+    }
+
+    async fn has_blob(&self, hash: &str) -> Result<bool> {
+        Ok(tokio::fs::try_exists(self.blob_path(hash)?).await?)
+    }
+
+    async fn get_blob(&self, hash: &str, dest: &Path) -> Result<()> {
+        let src = self.blob_path(hash)?;
+        let download_error = |e: std::io::Error| {
+            VektError::RemoteError(format!("Failed to download blob {}: {}", hash, e))
+        };
+
+        let mut source = File::open(&src).await.map_err(download_error)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dest)
+            .await?;
+
+        // Continue after whatever a previous interrupted copy already wrote
+        let offset = file.metadata().await?.len();
+        source
+            .seek(SeekFrom::Start(offset))
+            .await
+            .map_err(download_error)?;
+        let mut source = Throttled::new(source, self.throttle.clone());
+        tokio::io::copy(&mut source, &mut file)
+            .await
+            .map_err(download_error)?;
+        file.sync_all().await?;
+        Ok(())
+    }
+
+    async fn put_blob(&self, hash: &str, src: &Path) -> Result<()> {
+        let dest = self.blob_path(hash)?;
+        self.copy_atomic(src, &dest)
+            .await
+            .map_err(|e| VektError::RemoteError(format!("Failed to upload blob {}: {}", hash, e)))
+    }
+
+    async fn delete_blob(&self, hash: &str) -> Result<()> {
+        tokio::fs::remove_file(self.blob_path(hash)?).await?;
+        Ok(())
+    }
+
+    async fn list_manifests(&self) -> Result<Vec<RemoteObject>> {
+        list_dir(self.root.join("manifests")).await
+    }
+
+    async fn has_manifest(&self, name: &str) -> Result<bool> {
+        Ok(tokio::fs::try_exists(self.manifest_path(name)?).await?)
+    }
+
+    async fn get_manifest(&self, name: &str) -> Result<Vec<u8>> {
+        tokio::fs::read(self.manifest_path(name)?)
+            .await
+            .map_err(|e| {
+                VektError::RemoteError(format!(
+                    "Failed to download manifest '{}': {}. Ensure the manifest exists on remote.",
+                    name, e
+                ))
+            })
+    }
+
+    async fn put_manifest(&self, name: &str, data: &[u8]) -> Result<()> {
+        write_atomic(&self.manifest_path(name)?, data).await
+    }
+
+    async fn delete_manifest(&self, name: &str) -> Result<()> {
+        tokio::fs::remove_file(self.manifest_path(name)?).await?;
+        Ok(())
+    }
+
+    async fn get_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        validate_path_safe(name)?;
+        match tokio::fs::read(self.root.join("meta").join(name)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn put_meta(&self, name: &str, data: &[u8]) -> Result<()> {
+        validate_path_safe(name)?;
+        write_atomic(&self.root.join("meta").join(name), data).await
+    }
+}
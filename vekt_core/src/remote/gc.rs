@@ -0,0 +1,182 @@
+use super::transfer::with_retries;
+use super::{GC_EPOCH_META, KnownBlobs, RemoteClient, RemoteObject};
+use crate::errors::{Result, VektError};
+use crate::storage::VektManifest;
+use futures::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Settings for garbage collecting blobs on a remote
+#[derive(Debug, Clone)]
+pub struct RemoteGcOptions {
+    /// Report what would be deleted without deleting anything
+    pub dry_run: bool,
+    /// Blobs modified more recently than this are kept, since a concurrent push may
+    /// have uploaded them without having uploaded its manifest yet
+    pub grace_period: Duration,
+    /// Additional hashes to keep, e.g. those referenced by local manifests
+    pub retain: HashSet<String>,
+}
+
+impl Default for RemoteGcOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            grace_period: Duration::from_secs(24 * 3600),
+            retain: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RemoteGcStats {
+    /// Unreferenced blobs deleted, or that would be deleted in a dry run
+    pub deleted: usize,
+    pub deleted_bytes: u64,
+    /// Blobs referenced by a remote manifest or retained explicitly
+    pub kept: usize,
+    /// Unreferenced blobs kept because they are inside the grace period
+    pub recent: usize,
+}
+
+impl RemoteClient {
+    /// Deletes blobs on the remote that no manifest under `manifests/` references.
+    /// Aborts without deleting anything if any remote manifest cannot be read.
+    ///
+    /// A push that found an old unreferenced blob already present skips uploading it, so
+    /// manifests written while the collection runs are read again before anything is
+    /// deleted, and pushes that overlap the deletions re-check their blobs (see `push_all`).
+    pub async fn gc(&self, options: &RemoteGcOptions) -> Result<RemoteGcStats> {
+        if self.remote.is_read_only() && !options.dry_run {
+            return Err(VektError::ReadOnlyRemote(format!(
+                "cannot garbage collect {}",
+                self.remote.location()
+            )));
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let scanned = self.remote.list_manifests().await?;
+        let mut referenced = options.retain.clone();
+        referenced.extend(self.referenced_by(&scanned).await?);
+
+        let cutoff = now.saturating_sub(options.grace_period).as_secs();
+
+        let mut stats = RemoteGcStats::default();
+        let mut garbage = Vec::new();
+        for blob in self.remote.list_blobs().await? {
+            if referenced.contains(&blob.name) {
+                stats.kept += 1;
+            } else if blob.last_modified.is_none_or(|modified| modified > cutoff) {
+                // Without a timestamp the blob's age is unknown, so it gets the benefit of the doubt
+                stats.recent += 1;
+            } else {
+                garbage.push(blob);
+            }
+        }
+        stats.deleted = garbage.len();
+        stats.deleted_bytes = garbage.iter().map(|blob| blob.size).sum();
+
+        if options.dry_run || garbage.is_empty() {
+            return Ok(stats);
+        }
+
+        // Tell other clones their caches of remote blobs are stale before deleting any
+        self.remote
+            .put_meta(GC_EPOCH_META, now.as_secs().to_string().as_bytes())
+            .await?;
+
+        // Pushes that finished since the scan may reference blobs they found already present
+        let scanned: HashMap<String, RemoteObject> = scanned
+            .into_iter()
+            .map(|object| (object.name.clone(), object))
+            .collect();
+        let changed: Vec<RemoteObject> = self
+            .remote
+            .list_manifests()
+            .await?
+            .into_iter()
+            .filter(|object| {
+                scanned.get(&object.name).is_none_or(|before| {
+                    before.size != object.size
+                        || object
+                            .last_modified
+                            .is_none_or(|modified| modified >= now.as_secs())
+                })
+            })
+            .collect();
+        if !changed.is_empty() {
+            let referenced = self.referenced_by(&changed).await?;
+            garbage.retain(|blob| !referenced.contains(&blob.name));
+            stats.kept += stats.deleted - garbage.len();
+            stats.deleted = garbage.len();
+            stats.deleted_bytes = garbage.iter().map(|blob| blob.size).sum();
+            if garbage.is_empty() {
+                return Ok(stats);
+            }
+        }
+
+        let deletions = stream::iter(&garbage)
+            .map(|blob| async move {
+                let hash = &blob.name;
+                let result = with_retries(
+                    self.options.retries,
+                    &format!("Deleting blob {}", hash),
+                    || self.remote.delete_blob(hash),
+                )
+                .await;
+                (blob, result)
+            })
+            .buffer_unordered(self.options.concurrency);
+        let results: Vec<_> = deletions.collect().await;
+
+        let mut known = KnownBlobs::load(&self.repo, &self.remote.location());
+        known.sync_gc_epoch(now.as_secs());
+        known.save()?;
+
+        let mut first_error = None;
+        for (blob, result) in results {
+            match result {
+                Ok(()) => {}
+                // A `vekt serve` remote keeps blobs its own repository still needs
+                Err(VektError::ConflictDetected(reason)) => {
+                    tracing::debug!(hash = %blob.name, %reason, "remote kept blob");
+                    stats.deleted -= 1;
+                    stats.deleted_bytes -= blob.size;
+                    stats.kept += 1;
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(stats),
+        }
+    }
+
+    /// Hashes referenced by the given remote manifests
+    async fn referenced_by(&self, manifests: &[RemoteObject]) -> Result<HashSet<String>> {
+        let mut referenced = HashSet::new();
+        for object in manifests {
+            let name = &object.name;
+            let bytes = with_retries(
+                self.options.retries,
+                &format!("Downloading manifest {}", name),
+                || self.remote.get_manifest(name),
+            )
+            .await?;
+            // A manifest we can't read might reference anything, so nothing is safe to delete
+            let manifest: VektManifest = serde_json::from_slice(&bytes).map_err(|e| {
+                VektError::InvalidManifest(format!(
+                    "Failed to parse remote manifest '{}', aborting GC: {}",
+                    name, e
+                ))
+            })?;
+            referenced.extend(manifest.tensors.into_values().map(|tensor| tensor.hash));
+        }
+        Ok(referenced)
+    }
+}
@@ -0,0 +1,291 @@
+use super::{Remote, RemoteObject, Throttle, TransferOptions};
+use crate::errors::{Result, VektError};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use reqwest::header::RANGE;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+
+/// Read-only remote served as static files by any web server, using the
+/// `blobs/<hash>` and `manifests/<name>` layout that push writes
+pub struct HttpRemote {
+    base_url: String,
+    client: reqwest::Client,
+    throttle: Option<Arc<Throttle>>,
+}
+
+impl HttpRemote {
+    /// `base_url` must end with a slash (see `validate_http_url`)
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url,
+            client: reqwest::Client::new(),
+            throttle: None,
+        }
+    }
+
+    /// Applies request timeouts and bandwidth limits
+    pub fn with_transfer_options(mut self, options: &TransferOptions) -> Result<Self> {
+        if let Some(timeout) = options.timeout {
+            self.client = reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .map_err(|e| {
+                    VektError::RemoteError(format!("Failed to build HTTP client: {}", e))
+                })?;
+        }
+        self.throttle = options.throttle();
+        Ok(self)
+    }
+
+    pub(super) fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub(super) fn throttle(&self) -> Option<Arc<Throttle>> {
+        self.throttle.clone()
+    }
+
+    pub(super) fn url(&self, key: &str) -> String {
+        format!("{}{}", self.base_url, key)
+    }
+
+    fn read_only(&self, operation: &str) -> VektError {
+        VektError::ReadOnlyRemote(format!(
+            "cannot {} on {}. Plain HTTP remotes only support downloads",
+            operation, self.base_url
+        ))
+    }
+
+    fn unsupported(&self, operation: &str) -> VektError {
+        VektError::Unsupported(format!(
+            "cannot {} on {}. Static web servers can't list directories; fetch manifests by name",
+            operation, self.base_url
+        ))
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let response = self
+            .client
+            .head(self.url(key))
+            .send()
+            .await
+            .map_err(|e| request_error(format!("Failed to check '{}'", key), e))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(false),
+            status if status.is_success() => Ok(true),
+            status => Err(status_error(format!("Failed to check '{}'", key), status)),
+        }
+    }
+}
+
+/// Wraps a failed request; timeouts and connection failures are transient
+pub(super) fn request_error(context: String, error: reqwest::Error) -> VektError {
+    let message = format!("{}: {}", context, error);
+    if error.is_timeout() || error.is_connect() || error.is_request() || error.is_body() {
+        VektError::RemoteUnavailable(message)
+    } else {
+        VektError::RemoteError(message)
+    }
+}
+
+/// Wraps an unexpected status; throttling and server errors are transient
+pub(super) fn status_error(context: String, status: StatusCode) -> VektError {
+    let message = format!("{}, status: {}", context, status);
+    if status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+    {
+        VektError::RemoteUnavailable(message)
+    } else {
+        VektError::RemoteError(message)
+    }
+}
+
+#[async_trait]
+impl Remote for HttpRemote {
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn location(&self) -> String {
+        self.base_url.clone()
+    }
+
+    async fn validate_access(&self) -> Result<()> {
+        let response = self.client.head(&self.base_url).send().await.map_err(|e| {
+            VektError::RemoteError(format!("Failed to reach {}: {}", self.base_url, e))
+        })?;
+
+        // Static servers commonly answer 404 for directory URLs; only auth failures are fatal
+        if matches!(
+            response.status(),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        ) {
+            return Err(VektError::RemoteError(format!(
+                "Access to {} denied, status: {}",
+                self.base_url,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn list_blobs(&self) -> Result<Vec<RemoteObject>> {
+        Err(self.unsupported("list blobs"))
+    }
+
+    async fn has_blob(&self, hash: &str) -> Result<bool> {
+        self.exists(&format!("blobs/{}", hash)).await
+    }
+
+    async fn existing_blobs(&self, hashes: &[String]) -> Result<HashSet<String>> {
+        // Static servers can't list directories, so every blob is checked individually
+        let mut present = HashSet::new();
+        for hash in hashes {
+            if self.has_blob(hash).await? {
+                present.insert(hash.clone());
+            }
+        }
+        Ok(present)
+    }
+
+    async fn get_blob(&self, hash: &str, dest: &Path) -> Result<()> {
+        // Resume a previous partial download if one is lying around
+        let offset = match tokio::fs::metadata(dest).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+
+        let mut request = self.client.get(self.url(&format!("blobs/{}", hash)));
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| request_error(format!("Failed to download blob {}", hash), e))?;
+
+        let append = match response.status() {
+            StatusCode::PARTIAL_CONTENT => true,
+            // The partial file is already complete; let the caller verify it
+            StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => return Ok(()),
+            status if status.is_success() => false,
+            status => {
+                return Err(status_error(
+                    format!("Failed to download blob {}", hash),
+                    status,
+                ));
+            }
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(dest)
+            .await
+            .map_err(|e| {
+                VektError::Io(std::io::Error::other(format!(
+                    "Failed to create temp file for blob {}: {}",
+                    hash, e
+                )))
+            })?;
+
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| request_error(format!("Failed to download blob {}", hash), e))?
+        {
+            file.write_all(&chunk).await?;
+            if let Some(throttle) = &self.throttle {
+                throttle.consume(chunk.len() as u64).await;
+            }
+        }
+
+        // Ensure data is flushed
+        file.sync_all().await?;
+        Ok(())
+    }
+
+    async fn put_blob(&self, _hash: &str, _src: &Path) -> Result<()> {
+        Err(self.read_only("upload blobs"))
+    }
+
+    async fn delete_blob(&self, _hash: &str) -> Result<()> {
+        Err(self.read_only("delete blobs"))
+    }
+
+    async fn list_manifests(&self) -> Result<Vec<RemoteObject>> {
+        Err(self.unsupported("list manifests"))
+    }
+
+    async fn has_manifest(&self, name: &str) -> Result<bool> {
+        self.exists(&format!("manifests/{}", name)).await
+    }
+
+    async fn get_manifest(&self, name: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(self.url(&format!("manifests/{}", name)))
+            .send()
+            .await
+            .map_err(|e| request_error(format!("Failed to download manifest '{}'", name), e))?;
+
+        if !response.status().is_success() {
+            return Err(status_error(
+                format!(
+                    "Failed to download manifest '{}'. Ensure the manifest exists on remote",
+                    name
+                ),
+                response.status(),
+            ));
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| request_error(format!("Failed to download manifest '{}'", name), e))?;
+        Ok(bytes.to_vec())
+    }
+
+    async fn put_manifest(&self, _name: &str, _data: &[u8]) -> Result<()> {
+        Err(self.read_only("upload manifests"))
+    }
+
+    async fn delete_manifest(&self, _name: &str) -> Result<()> {
+        Err(self.read_only("delete manifests"))
+    }
+
+    async fn get_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let key = format!("meta/{}", name);
+        let response = self
+            .client
+            .get(self.url(&key))
+            .send()
+            .await
+            .map_err(|e| request_error(format!("Failed to read '{}'", key), e))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| request_error(format!("Failed to read '{}'", key), e))?;
+                Ok(Some(bytes.to_vec()))
+            }
+            status => Err(status_error(format!("Failed to read '{}'", key), status)),
+        }
+    }
+
+    async fn put_meta(&self, _name: &str, _data: &[u8]) -> Result<()> {
+        Err(self.read_only("write metadata"))
+    }
+}
@@ -0,0 +1,731 @@
+use super::transfer::Throttled;
+use super::{Remote, RemoteObject, Throttle, TransferOptions};
+use crate::blobs;
+use crate::errors::{Result, VektError};
+use crate::storage::RemoteConfig;
+use crate::utils::write_file_atomic;
+use crate::validation::{validate_key_prefix, validate_s3_url};
+use async_trait::async_trait;
+use s3::bucket::Bucket;
+use s3::creds::Credentials;
+use s3::error::S3Error;
+use s3::region::Region;
+use s3::serde_types::Part;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::{Arc, Once};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Blobs at least this large are uploaded in multipart chunks
+const DEFAULT_MULTIPART_THRESHOLD: u64 = 64 * 1024 * 1024;
+const DEFAULT_MULTIPART_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+
+/// S3 limits: every part but the last is 5 MiB to 5 GiB, and an upload has at most
+/// 10,000 parts
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+const MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+const MAX_PARTS: u64 = 10_000;
+
+/// Remote backed by an S3 bucket or an S3-compatible service (MinIO, Ceph, R2, ...)
+pub struct S3Remote {
+    bucket: Bucket,
+    /// Key prefix inside the bucket, empty or ending in `/`
+    prefix: String,
+    multipart_threshold: u64,
+    multipart_chunk_size: u64,
+    throttle: Option<Arc<Throttle>>,
+    /// Where `UploadState` files are kept
+    upload_state_dir: PathBuf,
+}
+
+/// Progress of an interrupted multipart upload, persisted under `.vekt/uploads`
+/// so the next push only sends the parts that are still missing
+#[derive(Serialize, Deserialize, Debug)]
+struct UploadState {
+    key: String,
+    upload_id: String,
+    size: u64,
+    chunk_size: u64,
+    parts: Vec<UploadedPart>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct UploadedPart {
+    part_number: u32,
+    etag: String,
+}
+
+impl UploadState {
+    fn load(path: &Path) -> Option<Self> {
+        let data = std::fs::read(path).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(self)?;
+        write_file_atomic(path, &json)?;
+        Ok(())
+    }
+}
+
+impl S3Remote {
+    /// Connects to the bucket in `config`. Multipart uploads keep their resume state
+    /// in `upload_state_dir`, normally the repository's `.vekt/uploads`.
+    pub fn new(config: &RemoteConfig, upload_state_dir: PathBuf) -> Result<Self> {
+        let (bucket_name, url_prefix) = validate_s3_url(&config.url)?;
+        let prefix = match &config.prefix {
+            Some(prefix) => Some(validate_key_prefix(prefix)?),
+            None => url_prefix,
+        };
+
+        let region_name = config
+            .region
+            .clone()
+            .or_else(|| std::env::var("AWS_REGION").ok());
+
+        let region = match &config.endpoint {
+            Some(endpoint) => Region::Custom {
+                region: region_name.unwrap_or_else(|| "us-east-1".to_string()),
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+            },
+            None => match region_name {
+                Some(name) => Region::from_str(&name).map_err(|e| {
+                    VektError::RemoteError(format!("Invalid S3 region '{}': {}", name, e))
+                })?,
+                None => Region::UsEast1,
+            },
+        };
+
+        let creds = resolve_credentials(config)?;
+
+        // rust-s3 sleeps and retries every failed request, including 404s;
+        // RemoteClient retries only transient failures with its own backoff.
+        // The setting is process-wide, so it is applied once, before the first bucket.
+        static DISABLE_S3_RETRIES: Once = Once::new();
+        DISABLE_S3_RETRIES.call_once(|| s3::set_retries(0));
+
+        let mut bucket = Bucket::new(&bucket_name, region, creds).map_err(|e| {
+            VektError::RemoteError(format!("Failed to initialize S3 bucket: {}", e))
+        })?;
+
+        // Most S3-compatible gateways only route path-style requests
+        if config.path_style.unwrap_or(config.endpoint.is_some()) {
+            bucket = bucket.with_path_style();
+        }
+
+        let multipart_chunk_size = config
+            .multipart_chunk_size
+            .unwrap_or(DEFAULT_MULTIPART_CHUNK_SIZE);
+        if !(MIN_PART_SIZE..=MAX_PART_SIZE).contains(&multipart_chunk_size) {
+            return Err(VektError::RemoteError(format!(
+                "multipart_chunk_size must be between {} (5 MiB) and {} (5 GiB) bytes, got {}",
+                MIN_PART_SIZE, MAX_PART_SIZE, multipart_chunk_size
+            )));
+        }
+
+        Ok(Self {
+            bucket: *bucket,
+            prefix: prefix.unwrap_or_default(),
+            multipart_threshold: config
+                .multipart_threshold
+                .unwrap_or(DEFAULT_MULTIPART_THRESHOLD),
+            multipart_chunk_size,
+            throttle: None,
+            upload_state_dir,
+        })
+    }
+
+    /// Applies request timeouts and bandwidth limits
+    pub fn with_transfer_options(mut self, options: &TransferOptions) -> Result<Self> {
+        if let Some(timeout) = options.timeout {
+            self.bucket = *self.bucket.with_request_timeout(timeout).map_err(|e| {
+                VektError::RemoteError(format!("Failed to configure request timeout: {}", e))
+            })?;
+        }
+        self.throttle = options.throttle();
+        Ok(self)
+    }
+
+    /// Base URL requests are sent to, including the bucket for path-style addressing
+    pub fn url(&self) -> String {
+        self.bucket.url()
+    }
+
+    fn key(&self, path: &str) -> String {
+        format!("{}{}", self.prefix, path)
+    }
+
+    /// Local state file tracking the multipart upload of `key` to this bucket
+    fn upload_state_path(&self, key: &str) -> PathBuf {
+        let id = blobs::compute_blob_hash(format!("{}/{}", self.bucket.url(), key).as_bytes());
+        self.upload_state_dir.join(format!("{}.json", id))
+    }
+
+    /// Part size for a blob of `size` bytes: the configured chunk size, grown for blobs
+    /// that would otherwise need more than `MAX_PARTS` parts
+    fn part_size(&self, size: u64) -> u64 {
+        self.multipart_chunk_size
+            .max(size.div_ceil(MAX_PARTS).next_multiple_of(1024 * 1024))
+    }
+
+    /// Returns the persisted state for `key` if it matches this upload and the
+    /// multipart upload is still open on the remote
+    async fn resumable_state(
+        &self,
+        key: &str,
+        size: u64,
+        state_path: &Path,
+    ) -> Option<UploadState> {
+        let state = UploadState::load(state_path)?;
+        if state.key != key || state.size != size || state.chunk_size != self.part_size(size) {
+            return None;
+        }
+
+        let uploads = self
+            .bucket
+            .list_multiparts_uploads(Some(key), None)
+            .await
+            .ok()?;
+        uploads
+            .iter()
+            .flat_map(|page| &page.uploads)
+            .any(|upload| upload.key == key && upload.id == state.upload_id)
+            .then_some(state)
+    }
+
+    /// Uploads a large blob in chunks, recording each finished part so an
+    /// interrupted upload resumes where it stopped
+    async fn put_blob_multipart(&self, hash: &str, src: &Path, size: u64) -> Result<()> {
+        let key = self.key(&format!("blobs/{}", hash));
+        let state_path = self.upload_state_path(&key);
+
+        let mut state = match self.resumable_state(&key, size, &state_path).await {
+            Some(state) => state,
+            None => {
+                let response = self
+                    .bucket
+                    .initiate_multipart_upload(&key, "application/octet-stream")
+                    .await
+                    .map_err(|e| {
+                        s3_error(
+                            format!("Failed to start multipart upload of blob {}", hash),
+                            e,
+                        )
+                    })?;
+                let state = UploadState {
+                    key: key.clone(),
+                    upload_id: response.upload_id,
+                    size,
+                    chunk_size: self.part_size(size),
+                    parts: Vec::new(),
+                };
+                state.save(&state_path)?;
+                state
+            }
+        };
+
+        let mut file = File::open(src).await.map_err(|e| {
+            VektError::Io(std::io::Error::other(format!(
+                "Failed to open blob {}: {}",
+                hash, e
+            )))
+        })?;
+
+        let chunk_size = state.chunk_size;
+        let total_parts = size.div_ceil(chunk_size);
+        for part_number in 1..=total_parts as u32 {
+            if state.parts.iter().any(|p| p.part_number == part_number) {
+                continue;
+            }
+
+            let offset = (part_number as u64 - 1) * chunk_size;
+            let len = chunk_size.min(size - offset);
+            let mut chunk = vec![0u8; len as usize];
+            file.seek(SeekFrom::Start(offset)).await?;
+            file.read_exact(&mut chunk).await?;
+            if let Some(throttle) = &self.throttle {
+                throttle.consume(len).await;
+            }
+
+            let part = self
+                .bucket
+                .put_multipart_chunk(
+                    chunk,
+                    &key,
+                    part_number,
+                    &state.upload_id,
+                    "application/octet-stream",
+                )
+                .await
+                .map_err(|e| {
+                    s3_error(
+                        format!(
+                            "Failed to upload part {}/{} of blob {}",
+                            part_number, total_parts, hash
+                        ),
+                        e,
+                    )
+                })?;
+
+            state.parts.push(UploadedPart {
+                part_number,
+                etag: part.etag,
+            });
+            state.save(&state_path)?;
+        }
+
+        state.parts.sort_by_key(|p| p.part_number);
+        let parts = state
+            .parts
+            .iter()
+            .map(|p| Part {
+                part_number: p.part_number,
+                etag: p.etag.clone(),
+            })
+            .collect();
+
+        self.bucket
+            .complete_multipart_upload(&key, &state.upload_id, parts)
+            .await
+            .map_err(|e| {
+                s3_error(
+                    format!("Failed to complete multipart upload of blob {}", hash),
+                    e,
+                )
+            })?;
+
+        let _ = std::fs::remove_file(&state_path);
+        Ok(())
+    }
+
+    async fn list_prefix(&self, prefix: &str) -> Result<Vec<RemoteObject>> {
+        let prefix = self.key(prefix);
+        let results = self
+            .bucket
+            .list(prefix.clone(), None)
+            .await
+            .map_err(|e| s3_error(format!("Failed to list '{}'", prefix), e))?;
+
+        Ok(results
+            .into_iter()
+            .flat_map(|page| page.contents)
+            .filter_map(|object| {
+                let name = object.key.strip_prefix(&prefix)?.to_string();
+                Some(RemoteObject {
+                    name,
+                    size: object.size,
+                    last_modified: parse_timestamp(&object.last_modified),
+                })
+            })
+            .collect())
+    }
+
+    async fn exists(&self, path: &str) -> Result<bool> {
+        let key = self.key(path);
+        match self.bucket.head_object(&key).await {
+            Ok((_, 200)) => Ok(true),
+            Ok(_) | Err(S3Error::HttpFailWithBody(404, _)) => Ok(false),
+            Err(e) => Err(s3_error(format!("Failed to check '{}'", key), e)),
+        }
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        let key = self.key(path);
+        self.bucket
+            .delete_object(&key)
+            .await
+            .map_err(|e| s3_error(format!("Failed to delete '{}'", key), e))?;
+        Ok(())
+    }
+}
+
+/// Output of a credential helper, in the AWS `credential_process` JSON format
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HelperCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    #[serde(default)]
+    session_token: Option<String>,
+}
+
+/// Resolves the credentials for a remote from, in order of precedence, its credential
+/// helper, its credentials file or profile, or the default environment/profile chain
+fn resolve_credentials(config: &RemoteConfig) -> Result<Credentials> {
+    if config.credential_helper.is_some()
+        && (config.profile.is_some() || config.credentials_file.is_some())
+    {
+        return Err(VektError::CredentialError(
+            "A remote cannot use both a credential helper and a profile or credentials file"
+                .to_string(),
+        ));
+    }
+
+    let creds = if let Some(command) = &config.credential_helper {
+        run_credential_helper(command)?
+    } else if let Some(path) = &config.credentials_file {
+        Credentials::from_credentials_file(path, config.profile.as_deref()).map_err(|e| {
+            VektError::CredentialError(format!(
+                "Failed to read credentials file {}: {}",
+                path.display(),
+                e
+            ))
+        })?
+    } else if let Some(profile) = &config.profile {
+        Credentials::from_profile(Some(profile)).map_err(|e| {
+            VektError::CredentialError(format!("Failed to load AWS profile '{}': {}", profile, e))
+        })?
+    } else {
+        Credentials::default()
+            .map_err(|e| VektError::CredentialError(format!(
+                "Failed to load AWS credentials. Ensure AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are set, or configure ~/.aws/credentials: {}",
+                e
+            )))?
+    };
+
+    // Verify credentials are not empty
+    if creds.access_key.is_none() || creds.secret_key.is_none() {
+        return Err(VektError::CredentialError(
+            "AWS credentials are empty. Please configure valid credentials.".to_string(),
+        ));
+    }
+
+    Ok(creds)
+}
+
+/// Runs a credential helper through the platform shell and parses its JSON output
+fn run_credential_helper(command: &str) -> Result<Credentials> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").arg("/C").arg(command).output()
+    } else {
+        Command::new("sh").arg("-c").arg(command).output()
+    }
+    .map_err(|e| {
+        VektError::CredentialError(format!(
+            "Failed to run credential helper '{}': {}",
+            command, e
+        ))
+    })?;
+
+    if !output.status.success() {
+        return Err(VektError::CredentialError(format!(
+            "Credential helper '{}' failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    // Never echo the helper output in errors, it contains secrets
+    let helper: HelperCredentials = serde_json::from_slice(&output.stdout).map_err(|_| {
+        VektError::CredentialError(format!(
+            "Credential helper '{}' did not print AccessKeyId/SecretAccessKey JSON",
+            command
+        ))
+    })?;
+
+    Credentials::new(
+        Some(&helper.access_key_id),
+        Some(&helper.secret_access_key),
+        None,
+        helper.session_token.as_deref(),
+        None,
+    )
+    .map_err(|e| VektError::CredentialError(format!("Invalid helper credentials: {}", e)))
+}
+
+/// Wraps a failed S3 request, marking throttling, server errors and network
+/// failures as transient so they are retried
+fn s3_error(context: String, error: S3Error) -> VektError {
+    let transient = match &error {
+        S3Error::HttpFailWithBody(status, _) => *status >= 500 || *status == 408 || *status == 429,
+        S3Error::Reqwest(_) => true,
+        _ => false,
+    };
+    let message = format!("{}: {}", context, error);
+    if transient {
+        VektError::RemoteUnavailable(message)
+    } else {
+        VektError::RemoteError(message)
+    }
+}
+
+/// Parses the ISO 8601 timestamps S3 returns in listings into Unix seconds
+fn parse_timestamp(value: &str) -> Option<u64> {
+    use time::OffsetDateTime;
+    use time::format_description::well_known::Rfc3339;
+
+    let parsed = OffsetDateTime::parse(value, &Rfc3339).ok()?;
+    u64::try_from(parsed.unix_timestamp()).ok()
+}
+
+#[async_trait]
+impl Remote for S3Remote {
+    fn location(&self) -> String {
+        format!("{}/{}", self.bucket.url(), self.prefix)
+    }
+
+    async fn validate_access(&self) -> Result<()> {
+        self.bucket
+            .list_page(
+                self.prefix.clone(),
+                Some("/".to_string()),
+                None,
+                None,
+                Some(1),
+            )
+            .await
+            .map_err(|e| {
+                s3_error(
+                    "Failed to access S3 bucket. Check bucket name and permissions".to_string(),
+                    e,
+                )
+            })?;
+        Ok(())
+    }
+
+    async fn list_blobs(&self) -> Result<Vec<RemoteObject>> {
+        self.list_prefix("blobs/").await
+    }
+
+    async fn has_blob(&self, hash: &str) -> Result<bool> {
+        self.exists(&format!("blobs/{}", hash)).await
+    }
+
+    async fn get_blob(&self, hash: &str, dest: &Path) -> Result<()> {
+        let remote_path = self.key(&format!("blobs/{}", hash));
+
+        // Resume a previous partial download if one is lying around
+        let offset = match tokio::fs::metadata(dest).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dest)
+            .await
+            .map_err(|e| {
+                VektError::Io(std::io::Error::other(format!(
+                    "Failed to create temp file for blob {}: {}",
+                    hash, e
+                )))
+            })?;
+        let mut file = Throttled::new(file, self.throttle.clone());
+
+        let result = if offset > 0 {
+            self.bucket
+                .get_object_range_to_writer(&remote_path, offset, None, &mut file)
+                .await
+        } else {
+            self.bucket
+                .get_object_to_writer(&remote_path, &mut file)
+                .await
+        };
+
+        match result {
+            Ok(_) => {}
+            // The partial file is already complete; let the caller verify it
+            Err(S3Error::HttpFailWithBody(416, _)) if offset > 0 => {}
+            Err(e) => {
+                return Err(s3_error(format!("Failed to download blob {}", hash), e));
+            }
+        }
+
+        // Ensure data is flushed
+        file.get_mut().sync_all().await.map_err(|e| {
+            VektError::Io(std::io::Error::other(format!(
+                "Failed to sync blob {}: {}",
+                hash, e
+            )))
+        })?;
+
+        Ok(())
+    }
+
+    async fn put_blob(&self, hash: &str, src: &Path) -> Result<()> {
+        let size = tokio::fs::metadata(src).await?.len();
+        if size > 0 && size >= self.multipart_threshold {
+            return self.put_blob_multipart(hash, src, size).await;
+        }
+
+        let remote_path = self.key(&format!("blobs/{}", hash));
+
+        let file = File::open(src).await.map_err(|e| {
+            VektError::Io(std::io::Error::other(format!(
+                "Failed to open blob {}: {}",
+                hash, e
+            )))
+        })?;
+        let mut file = Throttled::new(file, self.throttle.clone());
+
+        let response = self
+            .bucket
+            .put_object_stream(&mut file, &remote_path)
+            .await
+            .map_err(|e| s3_error(format!("Failed to upload blob {}", hash), e))?;
+
+        if response.status_code() != 200 {
+            return Err(VektError::RemoteError(format!(
+                "Failed to upload blob {}, status: {}",
+                hash,
+                response.status_code()
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn delete_blob(&self, hash: &str) -> Result<()> {
+        self.delete(&format!("blobs/{}", hash)).await
+    }
+
+    async fn abort_incomplete_uploads(&self, older_than: Duration) -> Result<usize> {
+        let prefix = self.key("blobs/");
+        let pages = self
+            .bucket
+            .list_multiparts_uploads(Some(&prefix), None)
+            .await
+            .map_err(|e| s3_error("Failed to list multipart uploads".to_string(), e))?;
+
+        let cutoff = SystemTime::now()
+            .checked_sub(older_than)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut aborted = HashSet::new();
+        for upload in pages.iter().flat_map(|page| &page.uploads) {
+            // Uploads with unparseable timestamps are left alone rather than risk a live one
+            let Some(initiated) = parse_timestamp(&upload.initiated) else {
+                continue;
+            };
+            if initiated > cutoff {
+                continue;
+            }
+            self.bucket
+                .abort_upload(&upload.key, &upload.id)
+                .await
+                .map_err(|e| {
+                    s3_error(
+                        format!("Failed to abort multipart upload of {}", upload.key),
+                        e,
+                    )
+                })?;
+            aborted.insert(upload.id.clone());
+        }
+
+        // Forget local resume state for uploads that no longer exist
+        if let Ok(entries) = std::fs::read_dir(&self.upload_state_dir) {
+            for entry in entries.flatten() {
+                if let Some(state) = UploadState::load(&entry.path())
+                    && aborted.contains(&state.upload_id)
+                {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+
+        Ok(aborted.len())
+    }
+
+    async fn list_manifests(&self) -> Result<Vec<RemoteObject>> {
+        self.list_prefix("manifests/").await
+    }
+
+    async fn has_manifest(&self, name: &str) -> Result<bool> {
+        self.exists(&format!("manifests/{}", name)).await
+    }
+
+    async fn get_manifest(&self, name: &str) -> Result<Vec<u8>> {
+        let response_data = self
+            .bucket
+            .get_object(self.key(&format!("manifests/{}", name)))
+            .await
+            .map_err(|e| {
+                s3_error(
+                    format!(
+                        "Failed to download manifest '{}'. Ensure the manifest exists on remote",
+                        name
+                    ),
+                    e,
+                )
+            })?;
+        Ok(response_data.to_vec())
+    }
+
+    async fn put_manifest(&self, name: &str, data: &[u8]) -> Result<()> {
+        // S3 PUT is atomic, so readers never observe a partially written manifest
+        self.bucket
+            .put_object(self.key(&format!("manifests/{}", name)), data)
+            .await
+            .map_err(|e| s3_error(format!("Failed to upload manifest {}", name), e))?;
+        Ok(())
+    }
+
+    async fn delete_manifest(&self, name: &str) -> Result<()> {
+        self.delete(&format!("manifests/{}", name)).await
+    }
+
+    async fn get_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let key = self.key(&format!("meta/{}", name));
+        match self.bucket.get_object(&key).await {
+            Ok(response) => Ok(Some(response.to_vec())),
+            Err(S3Error::HttpFailWithBody(404, _)) => Ok(None),
+            Err(e) => Err(s3_error(format!("Failed to read '{}'", key), e)),
+        }
+    }
+
+    async fn put_meta(&self, name: &str, data: &[u8]) -> Result<()> {
+        let key = self.key(&format!("meta/{}", name));
+        self.bucket
+            .put_object(&key, data)
+            .await
+            .map_err(|e| s3_error(format!("Failed to write '{}'", key), e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:01:40.000Z"), Some(100));
+        assert_eq!(parse_timestamp("not a date"), None);
+    }
+
+    #[test]
+    fn test_multipart_chunk_size_limits() {
+        let remote = |chunk_size| {
+            let config = RemoteConfig {
+                multipart_chunk_size: Some(chunk_size),
+                credential_helper: Some(
+                    r#"echo '{"AccessKeyId": "key", "SecretAccessKey": "secret"}'"#.to_string(),
+                ),
+                ..RemoteConfig::new("s3://models")
+            };
+            S3Remote::new(&config, std::env::temp_dir())
+        };
+        assert!(remote(MIN_PART_SIZE - 1).is_err());
+        assert!(remote(MAX_PART_SIZE + 1).is_err());
+
+        // Blobs too large for 10,000 default parts get larger parts instead
+        let remote = remote(MIN_PART_SIZE).unwrap();
+        assert_eq!(remote.part_size(1 << 30), MIN_PART_SIZE);
+        let huge = 1 << 40;
+        let part_size = remote.part_size(huge);
+        assert!(huge.div_ceil(part_size) <= MAX_PARTS);
+        assert!(part_size > MIN_PART_SIZE);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::config::Settings;
+use crate::errors::{Result, VektError};
+use crate::storage::RemoteConfig;
+use rand::Rng;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, ready};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::Sleep;
+
+const DEFAULT_RETRIES: u32 = 3;
+const BACKOFF_BASE: Duration = Duration::from_millis(250);
+const BACKOFF_MAX: Duration = Duration::from_secs(20);
+
+/// How blobs are moved to and from a remote
+#[derive(Debug, Clone)]
+pub struct TransferOptions {
+    /// Blobs transferred at the same time
+    pub concurrency: usize,
+    /// Extra attempts for operations failing with `VektError::RemoteUnavailable`
+    pub retries: u32,
+    /// Limit on each request to the remote, unlimited if unset
+    pub timeout: Option<Duration>,
+    /// Combined transfer rate in bytes per second, unlimited if unset
+    pub bandwidth_limit: Option<u64>,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: Settings::default().concurrency,
+            retries: DEFAULT_RETRIES,
+            timeout: None,
+            bandwidth_limit: None,
+        }
+    }
+}
+
+impl TransferOptions {
+    /// Options for a remote, taking unset ones from the repository `settings`
+    pub fn from_config(config: &RemoteConfig, settings: &Settings) -> Self {
+        let defaults = Self::default();
+        Self {
+            concurrency: config.concurrency.unwrap_or(settings.concurrency).max(1),
+            retries: config.retries.unwrap_or(defaults.retries),
+            timeout: config.timeout_secs.map(Duration::from_secs),
+            bandwidth_limit: config.bandwidth_limit.filter(|limit| *limit > 0),
+        }
+    }
+
+    /// Throttle shared by all transfers of one remote, if a bandwidth limit is set
+    pub fn throttle(&self) -> Option<Arc<Throttle>> {
+        self.bandwidth_limit
+            .map(|limit| Arc::new(Throttle::new(limit)))
+    }
+}
+
+/// Runs `op`, retrying transient failures with exponential backoff and jitter
+pub(crate) async fn with_retries<T, F, Fut>(retries: u32, what: &str, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(VektError::RemoteUnavailable(reason)) if attempt < retries => {
+                let delay = backoff(attempt);
+                tracing::warn!(
+                    error = %reason,
+                    delay_ms = delay.as_millis() as u64,
+                    attempt = attempt + 1,
+                    retries,
+                    "{} failed, retrying",
+                    what
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Delay before retry `attempt`: doubles each time up to a cap, randomized between
+/// half and the full value so concurrent transfers don't retry in lockstep
+fn backoff(attempt: u32) -> Duration {
+    let ceiling = BACKOFF_BASE
+        .saturating_mul(1 << attempt.min(16))
+        .min(BACKOFF_MAX);
+    ceiling.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+/// Token bucket limiting the combined rate of every stream that shares it
+pub struct Throttle {
+    bytes_per_sec: u64,
+    next_free: Mutex<Instant>,
+}
+
+impl Throttle {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            next_free: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Accounts for `bytes` just transferred and returns how long to pause
+    /// before transferring more
+    fn reserve(&self, bytes: u64) -> Duration {
+        let now = Instant::now();
+        let mut next_free = self.next_free.lock().unwrap_or_else(|e| e.into_inner());
+        let start = (*next_free).max(now);
+        *next_free = start + Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec as f64);
+        next_free.saturating_duration_since(now)
+    }
+
+    /// Waits until sending `bytes` more stays within the limit
+    pub async fn consume(&self, bytes: u64) {
+        let delay = self.reserve(bytes);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Reader or writer whose throughput is limited by an optional shared `Throttle`
+pub(crate) struct Throttled<T> {
+    inner: T,
+    throttle: Option<Arc<Throttle>>,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl<T> Throttled<T> {
+    pub(crate) fn new(inner: T, throttle: Option<Arc<Throttle>>) -> Self {
+        Self {
+            inner,
+            throttle,
+            delay: None,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Waits out the pause owed for the previous read or write
+    fn poll_delay(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(delay) = &mut self.delay {
+            ready!(delay.as_mut().poll(cx));
+            self.delay = None;
+        }
+        Poll::Ready(())
+    }
+
+    fn account(&mut self, bytes: usize) {
+        if let Some(throttle) = &self.throttle {
+            let delay = throttle.reserve(bytes as u64);
+            if !delay.is_zero() {
+                self.delay = Some(Box::pin(tokio::time::sleep(delay)));
+            }
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Throttled<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_delay(cx));
+        let before = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        this.account(buf.filled().len() - before);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Throttled<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_delay(cx));
+        let written = ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        this.account(written);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        for attempt in 0..4 {
+            let ceiling = BACKOFF_BASE * (1 << attempt);
+            let delay = backoff(attempt);
+            assert!(delay >= ceiling / 2 && delay <= ceiling);
+        }
+        assert!(backoff(40) <= BACKOFF_MAX);
+    }
+
+    #[test]
+    fn test_throttle_spaces_out_transfers() {
+        let throttle = Throttle::new(1000);
+        assert!(throttle.reserve(500) <= Duration::from_millis(500));
+        // The second reservation queues behind the first
+        assert!(throttle.reserve(500) > Duration::from_millis(900));
+    }
+}
@@ -0,0 +1,211 @@
+use super::http::{request_error, status_error};
+use super::transfer::Throttled;
+use super::{HttpRemote, Remote, RemoteObject, TransferOptions};
+use crate::errors::{Result, VektError};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use reqwest::header::CONTENT_TYPE;
+use std::collections::HashSet;
+use std::path::Path;
+use tokio_util::io::ReaderStream;
+
+/// Hashes sent per existence query, keeping request bodies small
+const EXISTS_BATCH: usize = 1000;
+
+/// Remote backed by a `vekt serve` instance. Downloads use the same requests as a
+/// plain HTTP remote; the server adds listings, batch existence checks and uploads.
+pub struct VektRemote {
+    url: String,
+    http: HttpRemote,
+}
+
+impl VektRemote {
+    /// `url` is the configured `vekt://` URL, `base_url` its `http(s)://` equivalent
+    /// (see `validate_vekt_url`)
+    pub fn new(url: &str, base_url: String) -> Self {
+        Self {
+            url: url.to_string(),
+            http: HttpRemote::new(base_url),
+        }
+    }
+
+    /// Applies request timeouts and bandwidth limits
+    pub fn with_transfer_options(mut self, options: &TransferOptions) -> Result<Self> {
+        self.http = self.http.with_transfer_options(options)?;
+        Ok(self)
+    }
+
+    async fn list(&self, key: &str) -> Result<Vec<RemoteObject>> {
+        let context = || format!("Failed to list '{}' on {}", key, self.url);
+        let response = self
+            .http
+            .client()
+            .get(self.http.url(key))
+            .send()
+            .await
+            .map_err(|e| request_error(context(), e))?;
+        if !response.status().is_success() {
+            return Err(status_error(context(), response.status()));
+        }
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| request_error(context(), e))?;
+        serde_json::from_slice(&body)
+            .map_err(|e| VektError::RemoteError(format!("{}: invalid response: {}", context(), e)))
+    }
+
+    async fn put(&self, key: &str, body: reqwest::Body) -> Result<()> {
+        let context = || format!("Failed to upload '{}'", key);
+        let response = self
+            .http
+            .client()
+            .put(self.http.url(key))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| request_error(context(), e))?;
+        match response.status() {
+            StatusCode::FORBIDDEN => Err(VektError::ReadOnlyRemote(format!(
+                "{} is served read-only",
+                self.url
+            ))),
+            status if status.is_success() => Ok(()),
+            status => Err(status_error(context(), status)),
+        }
+    }
+
+    /// Deletes `key`; objects that are already gone count as deleted
+    async fn delete(&self, key: &str) -> Result<()> {
+        let context = || format!("Failed to delete '{}'", key);
+        let response = self
+            .http
+            .client()
+            .delete(self.http.url(key))
+            .send()
+            .await
+            .map_err(|e| request_error(context(), e))?;
+        match response.status() {
+            StatusCode::FORBIDDEN => Err(VektError::ReadOnlyRemote(format!(
+                "{} is served read-only",
+                self.url
+            ))),
+            StatusCode::NOT_FOUND => Ok(()),
+            StatusCode::CONFLICT => Err(VektError::ConflictDetected(format!(
+                "{}: still referenced by the served repository",
+                context()
+            ))),
+            status if status.is_success() => Ok(()),
+            status => Err(status_error(context(), status)),
+        }
+    }
+}
+
+#[async_trait]
+impl Remote for VektRemote {
+    fn location(&self) -> String {
+        self.url.clone()
+    }
+
+    async fn validate_access(&self) -> Result<()> {
+        let response = self
+            .http
+            .client()
+            .get(self.http.url(""))
+            .send()
+            .await
+            .map_err(|e| VektError::RemoteError(format!("Failed to reach {}: {}", self.url, e)))?;
+        if !response.status().is_success() {
+            return Err(VektError::RemoteError(format!(
+                "{} is not a vekt server, status: {}",
+                self.url,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn list_blobs(&self) -> Result<Vec<RemoteObject>> {
+        self.list("blobs").await
+    }
+
+    async fn has_blob(&self, hash: &str) -> Result<bool> {
+        self.http.has_blob(hash).await
+    }
+
+    async fn existing_blobs(&self, hashes: &[String]) -> Result<HashSet<String>> {
+        let mut present = HashSet::new();
+        for batch in hashes.chunks(EXISTS_BATCH) {
+            let context = || format!("Failed to check blobs on {}", self.url);
+            let response = self
+                .http
+                .client()
+                .post(self.http.url("blobs/exists"))
+                .header(CONTENT_TYPE, "application/json")
+                .body(serde_json::to_vec(batch)?)
+                .send()
+                .await
+                .map_err(|e| request_error(context(), e))?;
+            if !response.status().is_success() {
+                return Err(status_error(context(), response.status()));
+            }
+            let body = response
+                .bytes()
+                .await
+                .map_err(|e| request_error(context(), e))?;
+            let found: Vec<String> = serde_json::from_slice(&body).map_err(|e| {
+                VektError::RemoteError(format!("{}: invalid response: {}", context(), e))
+            })?;
+            present.extend(found);
+        }
+        Ok(present)
+    }
+
+    async fn get_blob(&self, hash: &str, dest: &Path) -> Result<()> {
+        self.http.get_blob(hash, dest).await
+    }
+
+    async fn put_blob(&self, hash: &str, src: &Path) -> Result<()> {
+        let file = tokio::fs::File::open(src).await?;
+        let stream = ReaderStream::new(Throttled::new(file, self.http.throttle()));
+        self.put(
+            &format!("blobs/{}", hash),
+            reqwest::Body::wrap_stream(stream),
+        )
+        .await
+    }
+
+    async fn delete_blob(&self, hash: &str) -> Result<()> {
+        self.delete(&format!("blobs/{}", hash)).await
+    }
+
+    async fn list_manifests(&self) -> Result<Vec<RemoteObject>> {
+        self.list("manifests").await
+    }
+
+    async fn has_manifest(&self, name: &str) -> Result<bool> {
+        self.http.has_manifest(name).await
+    }
+
+    async fn get_manifest(&self, name: &str) -> Result<Vec<u8>> {
+        self.http.get_manifest(name).await
+    }
+
+    async fn put_manifest(&self, name: &str, data: &[u8]) -> Result<()> {
+        self.put(&format!("manifests/{}", name), data.to_vec().into())
+            .await
+    }
+
+    async fn delete_manifest(&self, name: &str) -> Result<()> {
+        self.delete(&format!("manifests/{}", name)).await
+    }
+
+    async fn get_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.http.get_meta(name).await
+    }
+
+    async fn put_meta(&self, name: &str, data: &[u8]) -> Result<()> {
+        self.put(&format!("meta/{}", name), data.to_vec().into())
+            .await
+    }
+}
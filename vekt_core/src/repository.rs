@@ -0,0 +1,142 @@
+//! A vekt repository: a directory holding a `.vekt` directory with the blob store,
+//! config and lock. Operations take the repository explicitly, so one process can work
+//! with several repositories at once without depending on the working directory.
+
+use crate::config::{
+    CONFIG_VERSION, LayeredSettings, Settings, SettingsLayer, UserConfig, load_versioned,
+};
+use crate::errors::{Result, VektError};
+use crate::gc::find_manifests;
+use crate::reader::TensorReader;
+use crate::remote::RemoteClient;
+use crate::storage::{VektConfig, VektManifest};
+use crate::utils::{LockFile, ensure_vekt_dir, find_vekt_root, write_file_atomic};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repository {
+    root: PathBuf,
+    /// User and environment layers used instead of the user config file and `VEKT_*`
+    settings_layers: Option<Box<(SettingsLayer, SettingsLayer)>>,
+}
+
+impl Repository {
+    /// Opens the repository rooted at `root`, which must contain a `.vekt` directory
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = std::path::absolute(root.as_ref())?;
+        if !root.join(".vekt").is_dir() {
+            return Err(VektError::RepoNotFound);
+        }
+        Ok(Self {
+            root,
+            settings_layers: None,
+        })
+    }
+
+    /// Creates the `.vekt` layout under `root` if it is missing and opens it
+    pub fn init(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        let vekt_dir = root.join(".vekt");
+        ensure_vekt_dir(&vekt_dir)?;
+        std::fs::create_dir_all(vekt_dir.join("blobs"))?;
+        Self::open(root)
+    }
+
+    /// Finds the repository the way the CLI does: `VEKT_ROOT` if set, otherwise the
+    /// nearest directory containing `.vekt` from the working directory up
+    pub fn discover() -> Result<Self> {
+        Self::open(find_vekt_root().ok_or(VektError::RepoNotFound)?)
+    }
+
+    /// Directory containing `.vekt`, which manifest names on remotes are relative to
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The `.vekt` directory
+    pub fn vekt_dir(&self) -> PathBuf {
+        self.root.join(".vekt")
+    }
+
+    /// Directory holding blobs named by their hash
+    pub fn store(&self) -> PathBuf {
+        self.vekt_dir().join("blobs")
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.vekt_dir().join("config.json")
+    }
+
+    /// Loads `.vekt/config.json`, or the default config if there is none.
+    /// Files written with an older schema are migrated in memory.
+    pub fn config(&self) -> Result<VektConfig> {
+        let path = self.config_path();
+        if !path.exists() {
+            return Ok(VektConfig::default());
+        }
+        load_versioned(&path)
+    }
+
+    /// Uses `user` and `env` in place of the user config file and `VEKT_*` variables,
+    /// so settings depend only on what the caller passes and the repository config
+    pub fn with_settings_layers(mut self, user: SettingsLayer, env: SettingsLayer) -> Self {
+        self.settings_layers = Some(Box::new((user, env)));
+        self
+    }
+
+    /// The user, repository and environment settings layers
+    pub fn layered_settings(&self) -> Result<LayeredSettings> {
+        let (user, env) = match self.settings_layers.as_deref() {
+            Some((user, env)) => (user.clone(), env.clone()),
+            None => (UserConfig::load()?.settings, SettingsLayer::from_env()?),
+        };
+        Ok(LayeredSettings {
+            user,
+            repo: self.config()?.settings,
+            env,
+        })
+    }
+
+    /// Settings in effect for this repository
+    pub fn settings(&self) -> Result<Settings> {
+        Ok(self.layered_settings()?.resolve())
+    }
+
+    /// Writes `.vekt/config.json` with the current schema version
+    pub fn save_config(&self, config: &VektConfig) -> Result<()> {
+        ensure_vekt_dir(&self.vekt_dir())?;
+        let json = serde_json::to_string_pretty(&VektConfig {
+            version: CONFIG_VERSION,
+            ..config.clone()
+        })?;
+        write_file_atomic(&self.config_path(), json.as_bytes()).map_err(|e| {
+            VektError::Io(std::io::Error::other(format!(
+                "Failed to write config file: {}",
+                e
+            )))
+        })?;
+        Ok(())
+    }
+
+    /// Takes the repository lock, held until the returned guard is dropped
+    pub fn lock(&self) -> Result<LockFile> {
+        LockFile::lock_in(&self.root)
+    }
+
+    /// Every `*.vekt.json` manifest in the repository, sorted by path
+    pub fn manifests(&self) -> Result<Vec<PathBuf>> {
+        find_manifests(&self.root)
+    }
+
+    /// Reads tensors of `manifest` from this repository's store
+    pub fn reader(&self, manifest: VektManifest) -> TensorReader {
+        TensorReader::new(self, manifest)
+    }
+
+    /// Client for the configured remote or group `name`. A group reads from its
+    /// members in order and is read-only; push to each member's own client instead.
+    pub fn remote(&self, name: &str) -> Result<RemoteClient> {
+        let config = self.config()?;
+        RemoteClient::with_fallbacks(self, &config.resolve_remotes(name)?)
+    }
+}
@@ -0,0 +1,442 @@
+//! HTTP server exposing a repository to `vekt://` remotes.
+//!
+//! Blobs are served from and written to the repository's `.vekt/blobs` store,
+//! manifests are the `*.vekt.json` files of its working tree, named by their path
+//! relative to the repository root. Routes mirror the layout other remotes use:
+//!
+//! - `GET /blobs`, `GET /manifests`: JSON listings
+//! - `POST /blobs/exists`: JSON array of hashes in, the present ones out
+//! - `GET|HEAD|PUT|DELETE /blobs/<hash>` and `/manifests/<name>`
+//! - `GET|PUT /meta/<name>`: bookkeeping such as the remote GC marker
+//! - `GET /files`, `GET|HEAD /files/<name>.safetensors`: each manifest as the file
+//!   `vekt restore` would write, with Range support (see `files`)
+//!
+//! Blobs and manifests are admitted and deleted under the repository lock, the one
+//! `vekt gc` holds, so a collection never races a write. Blobs the served repository
+//! itself references, from its working tree or git history, are never deleted.
+//!
+//! There is no authentication: anyone who can reach the server can read, push and
+//! delete. `vekt serve` binds to localhost unless told otherwise.
+
+mod files;
+
+use crate::blobs;
+use crate::compression;
+use crate::errors::{Result, VektError};
+use crate::gc::{find_manifests, referenced_hashes};
+use crate::remote::RemoteObject;
+use crate::repository::Repository;
+use crate::storage::VektManifest;
+use crate::utils::{LockFile, write_file_atomic};
+use crate::validation::validate_path_safe;
+use axum::body::{Body, Bytes};
+use axum::extract::{Path as UrlPath, Request, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::StreamExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+
+/// How a repository is exposed
+#[derive(Debug, Clone, Default)]
+pub struct ServerOptions {
+    /// Reject uploads and deletions
+    pub read_only: bool,
+}
+
+struct ServerState {
+    root: PathBuf,
+    store: PathBuf,
+    options: ServerOptions,
+    /// The repository's `restore_alignment`, so files match what `vekt restore` writes
+    alignment: u64,
+    layouts: files::LayoutCache,
+    /// Serializes this server's own writes, which would otherwise contend for the lock file
+    write_lock: Mutex<()>,
+}
+
+/// How long a write waits for a local command holding the repository lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+type SharedState = Arc<ServerState>;
+
+/// Builds the routes serving `repo`
+pub fn router(repo: &Repository, options: ServerOptions) -> Result<Router> {
+    let state = Arc::new(ServerState {
+        store: repo.store(),
+        root: repo.root().to_path_buf(),
+        options,
+        alignment: repo.settings()?.restore_alignment,
+        layouts: Default::default(),
+        write_lock: Mutex::new(()),
+    });
+    Ok(Router::new()
+        .route(
+            "/",
+            get(|| async { concat!("vekt ", env!("CARGO_PKG_VERSION")) }),
+        )
+        .route("/blobs", get(list_blobs))
+        .route("/blobs/exists", post(existing_blobs))
+        .route(
+            "/blobs/{hash}",
+            get(get_blob).put(put_blob).delete(delete_blob),
+        )
+        .route("/manifests", get(list_manifests))
+        .route(
+            "/manifests/{*name}",
+            get(get_manifest).put(put_manifest).delete(delete_manifest),
+        )
+        .route("/meta/{name}", get(get_meta).put(put_meta))
+        .route("/files", get(files::list_files))
+        .route("/files/{*name}", get(files::get_file))
+        .with_state(state))
+}
+
+/// Serves `repo` on `listener` until the task is dropped
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    repo: Repository,
+    options: ServerOptions,
+) -> Result<()> {
+    axum::serve(listener, router(&repo, options)?).await?;
+    Ok(())
+}
+
+/// Error answered to the client, with the status derived from the `VektError`
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+impl From<VektError> for ApiError {
+    fn from(error: VektError) -> Self {
+        let status = match &error {
+            VektError::PathTraversal(_)
+            | VektError::InvalidManifest(_)
+            | VektError::HashMismatch { .. } => StatusCode::BAD_REQUEST,
+            VektError::ReadOnlyRemote(_) => StatusCode::FORBIDDEN,
+            VektError::BlobNotFound(_) => StatusCode::NOT_FOUND,
+            VektError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, error.to_string())
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(error: std::io::Error) -> Self {
+        VektError::Io(error).into()
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, self.1).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+impl ServerState {
+    fn check_writable(&self) -> ApiResult<()> {
+        if self.options.read_only {
+            return Err(
+                VektError::ReadOnlyRemote("server started with --read-only".to_string()).into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Runs a short write under the repository lock, waiting while a local command holds it.
+    /// Blocking; call from `blocking`.
+    fn locked<T>(&self, work: impl FnOnce() -> ApiResult<T>) -> ApiResult<T> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        let _lock = loop {
+            match LockFile::lock_in(&self.root) {
+                Ok(lock) => break lock,
+                Err(VektError::LockExists) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                Err(VektError::LockExists) => {
+                    return Err(ApiError(
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        "Repository is locked by another vekt process".to_string(),
+                    ));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+        work()
+    }
+
+    fn blob_path(&self, hash: &str) -> ApiResult<PathBuf> {
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ApiError(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid blob hash '{}'", hash),
+            ));
+        }
+        Ok(blobs::blob_path_in(&self.store, hash))
+    }
+
+    /// Manifests live in the working tree, so names may not reach into `.vekt` or `.git`
+    fn manifest_path(&self, name: &str) -> ApiResult<PathBuf> {
+        validate_path_safe(name)?;
+        let hidden = name
+            .split('/')
+            .any(|part| part.is_empty() || part == ".vekt" || part == ".git");
+        if hidden || !name.ends_with(".vekt.json") {
+            return Err(ApiError(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid manifest name '{}'", name),
+            ));
+        }
+        Ok(self.root.join(name))
+    }
+
+    fn meta_path(&self, name: &str) -> ApiResult<PathBuf> {
+        validate_path_safe(name)?;
+        Ok(self.root.join(".vekt").join("meta").join(name))
+    }
+}
+
+fn remote_object(name: String, path: &Path) -> Option<RemoteObject> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(RemoteObject {
+        name,
+        size: metadata.len(),
+        last_modified: metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
+    })
+}
+
+/// Runs blocking filesystem work off the async runtime
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> ApiResult<T> + Send + 'static,
+) -> ApiResult<T> {
+    tokio::task::spawn_blocking(work).await.map_err(|e| {
+        ApiError(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Server task failed: {}", e),
+        )
+    })?
+}
+
+/// Answers with the file at `path`, including `HEAD` and `Range` requests
+async fn serve_file(path: PathBuf, request: Request) -> Response {
+    let Ok(response) = ServeFile::new(path).oneshot(request).await;
+    response.into_response()
+}
+
+async fn list_blobs(State(state): State<SharedState>) -> ApiResult<Json<Vec<RemoteObject>>> {
+    blocking(move || {
+        let mut objects = Vec::new();
+        if state.store.is_dir() {
+            for entry in std::fs::read_dir(&state.store)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                // Skip in-flight writes
+                if name.ends_with(".tmp") {
+                    continue;
+                }
+                objects.extend(remote_object(name, &entry.path()));
+            }
+        }
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Json(objects))
+    })
+    .await
+}
+
+async fn existing_blobs(
+    State(state): State<SharedState>,
+    Json(hashes): Json<Vec<String>>,
+) -> ApiResult<Json<Vec<String>>> {
+    blocking(move || {
+        let present = hashes
+            .into_iter()
+            .filter(|hash| state.blob_path(hash).is_ok_and(|path| path.is_file()))
+            .collect();
+        Ok(Json(present))
+    })
+    .await
+}
+
+async fn get_blob(
+    State(state): State<SharedState>,
+    UrlPath(hash): UrlPath<String>,
+    request: Request,
+) -> ApiResult<Response> {
+    let path = state.blob_path(&hash)?;
+    // Clients check blobs against their hash, so compressed ones are stored decoded first
+    let store = state.store.clone();
+    blocking(move || Ok(compression::inflate_blob_in(&store, &hash)?)).await?;
+    Ok(serve_file(path, request).await)
+}
+
+/// Streams the upload to a temp file and admits it only if its content matches `hash`
+async fn put_blob(
+    State(state): State<SharedState>,
+    UrlPath(hash): UrlPath<String>,
+    body: Body,
+) -> ApiResult<StatusCode> {
+    state.check_writable()?;
+    state.blob_path(&hash)?;
+    tokio::fs::create_dir_all(&state.store).await?;
+
+    let tmp_path = blobs::staging_path_in(&state.store, &hash);
+    let result = async {
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        let mut stream = body.into_data_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| {
+                ApiError(
+                    StatusCode::BAD_REQUEST,
+                    format!("Upload interrupted: {}", e),
+                )
+            })?;
+            file.write_all(&chunk).await?;
+        }
+        file.sync_all().await?;
+
+        let (state, path, hash) = (state.clone(), tmp_path.clone(), hash.clone());
+        blocking(move || {
+            let actual = blobs::hash_file_with_outboard(&path, &state.store, &hash)?;
+            if actual != hash {
+                return Err(VektError::HashMismatch {
+                    expected: hash,
+                    actual,
+                }
+                .into());
+            }
+            state.locked(|| Ok(blobs::commit_blob_file_in(&state.store, &path, &hash)?))?;
+            Ok(StatusCode::CREATED)
+        })
+        .await
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+    }
+    result
+}
+
+async fn delete_blob(
+    State(state): State<SharedState>,
+    UrlPath(hash): UrlPath<String>,
+) -> ApiResult<StatusCode> {
+    state.check_writable()?;
+    let path = state.blob_path(&hash)?;
+    blocking(move || {
+        state.locked(|| {
+            // A remote gc only knows the manifests it can list, not the repository's history
+            if path.is_file() && referenced_hashes(&state.root)?.contains(&hash) {
+                return Err(ApiError(
+                    StatusCode::CONFLICT,
+                    format!("Blob {} is referenced by the served repository", hash),
+                ));
+            }
+            Ok(std::fs::remove_file(&path)?)
+        })?;
+        Ok(StatusCode::NO_CONTENT)
+    })
+    .await
+}
+
+async fn list_manifests(State(state): State<SharedState>) -> ApiResult<Json<Vec<RemoteObject>>> {
+    blocking(move || {
+        let objects = find_manifests(&state.root)?
+            .into_iter()
+            .filter_map(|path| {
+                let relative = path.strip_prefix(&state.root).ok()?;
+                let name = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                remote_object(name, &path)
+            })
+            .collect();
+        Ok(Json(objects))
+    })
+    .await
+}
+
+async fn get_manifest(
+    State(state): State<SharedState>,
+    UrlPath(name): UrlPath<String>,
+    request: Request,
+) -> ApiResult<Response> {
+    Ok(serve_file(state.manifest_path(&name)?, request).await)
+}
+
+/// Stores a manifest after checking it parses, so clients never pull a broken one
+async fn put_manifest(
+    State(state): State<SharedState>,
+    UrlPath(name): UrlPath<String>,
+    body: Bytes,
+) -> ApiResult<StatusCode> {
+    state.check_writable()?;
+    let dest = state.manifest_path(&name)?;
+    serde_json::from_slice::<VektManifest>(&body).map_err(|e| {
+        VektError::InvalidManifest(format!("Failed to parse manifest '{}': {}", name, e))
+    })?;
+    blocking(move || {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        state.locked(|| Ok(write_file_atomic(&dest, &body)?))?;
+        Ok(StatusCode::CREATED)
+    })
+    .await
+}
+
+async fn delete_manifest(
+    State(state): State<SharedState>,
+    UrlPath(name): UrlPath<String>,
+) -> ApiResult<StatusCode> {
+    state.check_writable()?;
+    let path = state.manifest_path(&name)?;
+    blocking(move || {
+        state.locked(|| Ok(std::fs::remove_file(&path)?))?;
+        Ok(StatusCode::NO_CONTENT)
+    })
+    .await
+}
+
+async fn get_meta(
+    State(state): State<SharedState>,
+    UrlPath(name): UrlPath<String>,
+    request: Request,
+) -> ApiResult<Response> {
+    Ok(serve_file(state.meta_path(&name)?, request).await)
+}
+
+async fn put_meta(
+    State(state): State<SharedState>,
+    UrlPath(name): UrlPath<String>,
+    body: Bytes,
+) -> ApiResult<StatusCode> {
+    state.check_writable()?;
+    let dest = state.meta_path(&name)?;
+    blocking(move || {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_file_atomic(&dest, &body)?;
+        Ok(StatusCode::CREATED)
+    })
+    .await
+}
@@ -0,0 +1,466 @@
+//! Manifests presented as the safetensors files `restore` would write, so loaders
+//! can read a header and then only the tensors they need with HTTP Range requests.
+
+use super::{ApiError, ApiResult, ServerState, SharedState, blocking, remote_object};
+use crate::blobs;
+use crate::compression;
+use crate::errors::VektError;
+use crate::gc::find_manifests;
+use crate::remote::RemoteObject;
+use crate::storage::{FileLayout, VektManifest};
+use axum::Json;
+use axum::body::{Body, Bytes};
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, RANGE};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const MANIFEST_SUFFIX: &str = ".vekt.json";
+const FILE_SUFFIX: &str = ".safetensors";
+
+/// Query parameters of a file request
+#[derive(Deserialize)]
+pub(super) struct FileQuery {
+    /// Comma-separated tensor name filter, as for `vekt restore --layers`
+    layers: Option<String>,
+}
+
+/// A parsed manifest and its unfiltered layout, as of the manifest's modification time
+struct CachedManifest {
+    modified: SystemTime,
+    manifest: Arc<VektManifest>,
+    layout: Arc<FileLayout>,
+}
+
+/// Manifests of recently requested files, keyed by path. Loaders issue many small range
+/// requests per file, so a manifest is only parsed again once it changes on disk.
+/// Filtered layouts are built per request, so `?layers=` can't grow the cache.
+#[derive(Default)]
+pub(super) struct LayoutCache {
+    entries: Mutex<HashMap<PathBuf, CachedManifest>>,
+}
+
+impl ServerState {
+    fn layout(&self, manifest_path: &Path, layers: Option<String>) -> ApiResult<Arc<FileLayout>> {
+        let modified = std::fs::metadata(manifest_path)?.modified()?;
+        let (manifest, layout) = {
+            let mut entries = self
+                .layouts
+                .entries
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            match entries.get(manifest_path) {
+                Some(cached) if cached.modified == modified => {
+                    (cached.manifest.clone(), cached.layout.clone())
+                }
+                _ => {
+                    let manifest = Arc::new(VektManifest::load(manifest_path)?);
+                    let layout = Arc::new(manifest.layout(None, self.alignment)?);
+                    entries.insert(
+                        manifest_path.to_path_buf(),
+                        CachedManifest {
+                            modified,
+                            manifest: manifest.clone(),
+                            layout: layout.clone(),
+                        },
+                    );
+                    (manifest, layout)
+                }
+            }
+        };
+
+        match layers {
+            Some(filter) => Ok(Arc::new(manifest.layout(Some(&filter), self.alignment)?)),
+            None => Ok(layout),
+        }
+    }
+}
+
+/// Lists a virtual file for every manifest in the working tree
+pub(super) async fn list_files(
+    State(state): State<SharedState>,
+) -> ApiResult<Json<Vec<RemoteObject>>> {
+    blocking(move || {
+        let mut files = Vec::new();
+        for path in find_manifests(&state.root)? {
+            let Some(name) = path
+                .strip_prefix(&state.root)
+                .ok()
+                .and_then(|relative| relative.to_str())
+                .and_then(|relative| relative.strip_suffix(MANIFEST_SUFFIX))
+            else {
+                continue;
+            };
+            let name = format!("{}{}", name.replace('\\', "/"), FILE_SUFFIX);
+            // Manifests that fail to parse are not offered as files
+            if let Ok(layout) = state.layout(&path, None)
+                && let Some(mut object) = remote_object(name, &path)
+            {
+                object.size = layout.size;
+                files.push(object);
+            }
+        }
+        Ok(Json(files))
+    })
+    .await
+}
+
+/// Serves `<name>.safetensors` assembled from the blobs of `<name>.vekt.json`
+pub(super) async fn get_file(
+    State(state): State<SharedState>,
+    UrlPath(name): UrlPath<String>,
+    Query(query): Query<FileQuery>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let Some(stem) = name.strip_suffix(FILE_SUFFIX) else {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("'{}' is not a safetensors file", name),
+        ));
+    };
+    let manifest_path = state.manifest_path(&format!("{}{}", stem, MANIFEST_SUFFIX))?;
+
+    let layout_state = state.clone();
+    let layout = blocking(move || layout_state.layout(&manifest_path, query.layers)).await?;
+
+    let (start, end) = match parse_range(headers.get(RANGE), layout.size) {
+        Ok(Some(range)) => range,
+        Ok(None) => (0, layout.size),
+        Err(()) => {
+            return Ok((
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(CONTENT_RANGE, format!("bytes */{}", layout.size))],
+            )
+                .into_response());
+        }
+    };
+    let partial = end - start < layout.size;
+
+    // Fail before the response starts rather than cutting the body short
+    let pieces = plan_range(&layout, start, end);
+    let (checked, store) = (layout.clone(), state.store.clone());
+    blocking(move || check_blobs(&checked, &store, start, end)).await?;
+
+    let mut response =
+        Body::from_stream(stream_pieces(state.store.clone(), pieces)).into_response();
+    if partial {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+    }
+    let headers = response.headers_mut();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+    headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(CONTENT_LENGTH, HeaderValue::from(end - start));
+    if partial {
+        let range = format!("bytes {}-{}/{}", start, end - 1, layout.size);
+        headers.insert(
+            CONTENT_RANGE,
+            HeaderValue::from_str(&range).expect("range header is ASCII"),
+        );
+    }
+    Ok(response)
+}
+
+/// Checks the blobs overlapping `start..end` are present with the expected sizes,
+/// storing compressed ones decoded first since ranges are read from the blob files.
+/// Their content is verified as it is streamed, but by then the response has started,
+/// so a missing or truncated blob is caught here instead. Blocking; call from `blocking`.
+fn check_blobs(layout: &FileLayout, store: &Path, start: u64, end: u64) -> ApiResult<()> {
+    for segment in layout.segments_in(start, end) {
+        let path = blobs::blob_path_in(store, &segment.hash);
+        let missing = || {
+            VektError::BlobNotFound(format!(
+                "Blob {} for tensor '{}' is not in the server's store",
+                segment.hash, segment.tensor
+            ))
+        };
+        if !path.exists() {
+            return Err(missing().into());
+        }
+        compression::inflate_blob_in(store, &segment.hash)?;
+        let metadata = std::fs::metadata(&path).map_err(|_| missing())?;
+        if metadata.len() != segment.len {
+            return Err(VektError::TensorCorruption(format!(
+                "Blob {} for tensor '{}' has {} bytes, expected {}",
+                segment.hash,
+                segment.tensor,
+                metadata.len(),
+                segment.len
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Part of a response body
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Bytes(Bytes),
+    Blob { hash: String, offset: u64, len: u64 },
+}
+
+/// Blob bytes read and verified at a time while streaming; large blobs are verified
+/// against their outboard, so this bounds memory rather than hashing work
+const STREAM_CHUNK: u64 = 1 << 20;
+
+/// Splits the byte range `start..end` of a reconstructed file into header bytes,
+/// alignment padding and slices of the overlapping blobs
+fn plan_range(layout: &FileLayout, start: u64, end: u64) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let header_len = layout.header.len() as u64;
+    let mut position = start;
+
+    if position < header_len {
+        let header_end = end.min(header_len);
+        pieces.push(Piece::Bytes(Bytes::copy_from_slice(
+            &layout.header[position as usize..header_end as usize],
+        )));
+        position = header_end;
+    }
+
+    for segment in layout.segments_in(start, end) {
+        if segment.offset > position {
+            pieces.push(Piece::Bytes(Bytes::from(vec![
+                0u8;
+                (segment.offset - position)
+                    as usize
+            ])));
+            position = segment.offset;
+        }
+        let slice_end = (segment.offset + segment.len).min(end);
+        pieces.push(Piece::Blob {
+            hash: segment.hash.clone(),
+            offset: position - segment.offset,
+            len: slice_end - position,
+        });
+        position = slice_end;
+    }
+    if position < end {
+        pieces.push(Piece::Bytes(Bytes::from(vec![
+            0u8;
+            (end - position) as usize
+        ])));
+    }
+
+    pieces
+}
+
+/// Streams the pieces, verifying blob bytes against their hash as they are read
+fn stream_pieces(store: PathBuf, pieces: Vec<Piece>) -> BoxStream<'static, std::io::Result<Bytes>> {
+    stream::iter(pieces)
+        .flat_map(move |piece| match piece {
+            Piece::Bytes(bytes) => stream::once(async { Ok(bytes) }).boxed(),
+            Piece::Blob { hash, offset, len } => {
+                let store = store.clone();
+                stream::once(async move {
+                    let path = blobs::blob_path_in(&store, &hash);
+                    let file = Arc::new(tokio::fs::File::open(path).await?.into_std().await);
+                    let chunks = (offset..offset + len).step_by(STREAM_CHUNK as usize);
+                    Ok::<_, std::io::Error>(stream::iter(chunks).then(move |chunk_start| {
+                        let (store, hash, file) = (store.clone(), hash.clone(), file.clone());
+                        let chunk_len = STREAM_CHUNK.min(offset + len - chunk_start);
+                        async move {
+                            tokio::task::spawn_blocking(move || {
+                                blobs::read_verified_range_in(
+                                    &store,
+                                    &hash,
+                                    &file,
+                                    chunk_start,
+                                    chunk_len,
+                                )
+                            })
+                            .await
+                            .map_err(std::io::Error::other)?
+                            .map(Bytes::from)
+                        }
+                    }))
+                })
+                .try_flatten()
+                .boxed()
+            }
+        })
+        .boxed()
+}
+
+/// Parses a `Range` header against a file of `size` bytes into a half-open byte range.
+/// Returns `Ok(None)` when the whole file should be sent: no header, a malformed one,
+/// or several ranges, which are answered in full rather than as multipart.
+fn parse_range(
+    header: Option<&HeaderValue>,
+    size: u64,
+) -> std::result::Result<Option<(u64, u64)>, ()> {
+    let Some(spec) = header
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("bytes="))
+    else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((first, last)) = spec.split_once('-') else {
+        return Ok(None);
+    };
+
+    let (first, last) = (first.trim(), last.trim());
+    let range = if first.is_empty() {
+        // Suffix range: the last N bytes
+        let Ok(suffix) = last.parse::<u64>() else {
+            return Ok(None);
+        };
+        if suffix == 0 || size == 0 {
+            return Err(());
+        }
+        (size.saturating_sub(suffix), size)
+    } else {
+        let Ok(start) = first.parse::<u64>() else {
+            return Ok(None);
+        };
+        let end = if last.is_empty() {
+            size
+        } else {
+            match last.parse::<u64>() {
+                Ok(last) if last >= start => last.saturating_add(1).min(size),
+                _ => return Ok(None),
+            }
+        };
+        if start >= size {
+            return Err(());
+        }
+        (start, end)
+    };
+    Ok(Some(range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ManifestTensor, Segment};
+    use std::collections::BTreeMap;
+
+    fn range(value: &str, size: u64) -> std::result::Result<Option<(u64, u64)>, ()> {
+        parse_range(Some(&HeaderValue::from_str(value).unwrap()), size)
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(range("bytes=0-7", 100), Ok(Some((0, 8))));
+        assert_eq!(range("bytes=90-", 100), Ok(Some((90, 100))));
+        assert_eq!(range("bytes=-10", 100), Ok(Some((90, 100))));
+        assert_eq!(range("bytes=50-1000", 100), Ok(Some((50, 100))));
+        assert_eq!(range("bytes=100-", 100), Err(()));
+        assert_eq!(range("bytes=0-1,5-6", 100), Ok(None));
+        assert_eq!(range("items=0-1", 100), Ok(None));
+        assert_eq!(parse_range(None, 100), Ok(None));
+    }
+
+    #[test]
+    fn test_plan_range_reads_only_overlapping_blobs() {
+        let layout = FileLayout {
+            header: vec![1; 10],
+            segments: vec![
+                Segment {
+                    tensor: "a".into(),
+                    hash: "aa".into(),
+                    offset: 16,
+                    len: 4,
+                },
+                Segment {
+                    tensor: "b".into(),
+                    hash: "bb".into(),
+                    offset: 24,
+                    len: 8,
+                },
+            ],
+            size: 32,
+        };
+        // Header tail, padding, then the start of the first blob
+        assert_eq!(
+            plan_range(&layout, 8, 18),
+            vec![
+                Piece::Bytes(Bytes::from(vec![1, 1])),
+                Piece::Bytes(Bytes::from(vec![0; 6])),
+                Piece::Blob {
+                    hash: "aa".into(),
+                    offset: 0,
+                    len: 2
+                },
+            ]
+        );
+        // Padding with no blob in range
+        assert_eq!(
+            plan_range(&layout, 11, 14),
+            vec![Piece::Bytes(Bytes::from(vec![0; 3]))]
+        );
+        // Inside the second blob only
+        assert_eq!(
+            plan_range(&layout, 26, 30),
+            vec![Piece::Blob {
+                hash: "bb".into(),
+                offset: 2,
+                len: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn test_layer_filters_do_not_grow_the_layout_cache() {
+        let root = std::env::temp_dir().join(format!("vekt_layouts_{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&root).unwrap();
+        let tensors: BTreeMap<String, ManifestTensor> = ["a", "b"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let tensor = ManifestTensor {
+                    shape: vec![4],
+                    dtype: "U8".to_string(),
+                    hash: name.repeat(64),
+                    index,
+                    extra: Default::default(),
+                };
+                (name.to_string(), tensor)
+            })
+            .collect();
+        let manifest = VektManifest {
+            tensors,
+            version: "1.0".to_string(),
+            total_size: 8,
+        };
+        let path = root.join("model.vekt.json");
+        std::fs::write(&path, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        let state = ServerState {
+            store: root.join(".vekt/blobs"),
+            root: root.clone(),
+            options: Default::default(),
+            alignment: 8,
+            layouts: Default::default(),
+            write_lock: Mutex::new(()),
+        };
+        let full = state.layout(&path, None).unwrap();
+        for filter in ["a", "b", "a,b", "c"] {
+            let filtered = state.layout(&path, Some(filter.to_string())).unwrap();
+            assert!(filtered.size <= full.size);
+        }
+        assert_eq!(
+            state
+                .layout(&path, Some("a".into()))
+                .unwrap()
+                .segments
+                .len(),
+            1
+        );
+        assert_eq!(state.layouts.entries.lock().unwrap().len(), 1);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
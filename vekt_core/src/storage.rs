@@ -1,13 +1,18 @@
 use crate::blobs;
+use crate::compression;
+use crate::config::{CONFIG_VERSION, SettingsLayer};
 use crate::errors::{Result, VektError};
-use crate::utils::{ensure_vekt_dir, find_vekt_root, write_file_atomic};
+use crate::progress::{Hooks, Stage};
+use crate::repository::Repository;
 use crate::validation::{validate_tensor_name, verify_blob_hash};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
 // Metadata for a single tensor in raw format in safetensor file
 #[derive(Serialize, Deserialize, Debug)]
@@ -44,9 +49,131 @@ pub struct VektManifest {
     pub total_size: usize,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VektConfig {
-    pub remotes: HashMap<String, String>,
+    /// Schema version, see `config::migrate`
+    #[serde(default = "crate::config::config_version")]
+    pub version: u32,
+
+    #[serde(deserialize_with = "deserialize_remotes")]
+    pub remotes: HashMap<String, RemoteConfig>,
+
+    /// Named groups of remotes in priority order. Pushes replicate to every member;
+    /// reads try members in order and fall back to the next one.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub groups: HashMap<String, Vec<String>>,
+
+    /// Repository settings, overriding the user config file
+    #[serde(default, skip_serializing_if = "SettingsLayer::is_empty")]
+    pub settings: SettingsLayer,
+}
+
+impl Default for VektConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            remotes: HashMap::new(),
+            groups: HashMap::new(),
+            settings: SettingsLayer::default(),
+        }
+    }
+}
+
+/// Settings for a single remote. Older configs store just the URL string,
+/// which deserializes into a `RemoteConfig` with every other setting unset.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RemoteConfig {
+    pub url: String,
+
+    /// Custom S3 endpoint, e.g. `http://minio.internal:9000` for MinIO, Ceph or R2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+
+    /// S3 region. Falls back to `AWS_REGION`, then `us-east-1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// Use path-style (`endpoint/bucket/key`) instead of virtual-host addressing.
+    /// Defaults to path-style when a custom endpoint is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_style: Option<bool>,
+
+    /// Key prefix inside the bucket, so several teams can share one bucket.
+    /// Takes precedence over a prefix given in the URL path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+
+    /// Named profile to read from the credentials file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// AWS-style credentials file to read instead of `~/.aws/credentials`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials_file: Option<PathBuf>,
+
+    /// Command printing credentials as JSON in the AWS `credential_process` format.
+    /// Secrets are only ever resolved at runtime and never stored in the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_helper: Option<String>,
+
+    /// Blobs at least this many bytes are uploaded in resumable multipart chunks (default 64 MiB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multipart_threshold: Option<u64>,
+
+    /// Size of each multipart chunk in bytes (default 16 MiB, S3 requires at least 5 MiB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multipart_chunk_size: Option<u64>,
+
+    /// Number of blobs transferred at the same time (default 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+
+    /// Retries for transient failures, with exponential backoff (default 3)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+
+    /// Timeout for each request in seconds, unlimited if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Combined transfer rate limit in bytes per second, unlimited if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bandwidth_limit: Option<u64>,
+}
+
+impl RemoteConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        RemoteConfig {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+}
+
+fn deserialize_remotes<'de, D>(
+    deserializer: D,
+) -> std::result::Result<HashMap<String, RemoteConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RemoteEntry {
+        Url(String),
+        Config(Box<RemoteConfig>),
+    }
+
+    let entries = HashMap::<String, RemoteEntry>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|(name, entry)| {
+            let remote = match entry {
+                RemoteEntry::Url(url) => RemoteConfig::new(url),
+                RemoteEntry::Config(remote) => *remote,
+            };
+            (name, remote)
+        })
+        .collect())
 }
 
 impl VektManifest {
@@ -67,34 +194,24 @@ impl VektManifest {
         }
     }
 
-    pub fn print_summary(&self) {
-        println!("vekt Manifest Summary:");
-        println!("Version: {}", self.version);
-        println!("Total Tensors: {}", self.tensors.len());
-        println!("Total Size: {} bytes", self.total_size);
-        println!("Tensors:");
-
-        let mut sorted_tensors: Vec<(&String, &ManifestTensor)> = self.tensors.iter().collect();
-        sorted_tensors.sort_by_key(|k| k.1.index);
-
-        for (name, tensor) in sorted_tensors {
-            println!(
-                "- [{}] {}: shape={:?}, dtype={}, hash={}",
-                tensor.index, name, tensor.shape, tensor.dtype, tensor.hash
-            );
-        }
+    /// Reads and validates the manifest at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read(path).map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!("Failed to read {}: {}", path.display(), e),
+            )
+        })?;
+        let manifest: VektManifest = serde_json::from_slice(&content).map_err(|e| {
+            VektError::InvalidManifest(format!("Failed to parse {}: {}", path.display(), e))
+        })?;
+        manifest.validate_and_migrate()
     }
 
-    pub fn restore(&self, output_path: &std::path::Path, filter: Option<&str>) -> Result<()> {
-        // Validate all tensor names before processing to prevent path traversal
-        for name in self.tensors.keys() {
-            validate_tensor_name(name)?;
-        }
-
-        let file = File::create(output_path)?;
-        let mut writer = std::io::BufWriter::new(file);
-
-        // Filter tensors
+    /// Returns the names of the tensors matching `filter`, sorted by their original index.
+    /// The filter is a comma-separated list of terms; a tensor is kept if its name
+    /// contains any of them. `None` selects every tensor.
+    pub fn select_tensors(&self, filter: Option<&str>) -> Vec<&String> {
         let mut sorted_tensor_names: Vec<&String> = self
             .tensors
             .keys()
@@ -110,14 +227,50 @@ impl VektManifest {
 
         // Fix Issue #4: Sort by original index to ensure deterministic restoration
         sorted_tensor_names.sort_by_key(|name| self.tensors[*name].index);
+        sorted_tensor_names
+    }
+
+    /// Returns the unique blob hashes needed to restore the tensors matching `filter`
+    /// that are not present in `repo`'s store, in restore order.
+    pub fn missing_blobs(&self, repo: &Repository, filter: Option<&str>) -> Vec<String> {
+        let store = repo.store();
+        let mut seen = HashSet::new();
+        self.select_tensors(filter)
+            .into_iter()
+            .map(|name| &self.tensors[name].hash)
+            .filter(|hash| seen.insert(hash.as_str()) && !blobs::blob_exists_in(&store, hash))
+            .cloned()
+            .collect()
+    }
+
+    /// Computes the byte layout of the safetensors file `restore` writes for the
+    /// tensors matching `filter`: the header, and where each blob's data goes.
+    /// Tensors sharing a blob share its data, which is aligned to `alignment` bytes,
+    /// a power of two; pass the repository's `restore_alignment` setting so every
+    /// reader of the layout sees the same bytes `restore` writes.
+    pub fn layout(&self, filter: Option<&str>, alignment: u64) -> Result<FileLayout> {
+        if !alignment.is_power_of_two() {
+            return Err(VektError::InvalidConfig(format!(
+                "alignment must be a power of two, got {}",
+                alignment
+            )));
+        }
+        let alignment = alignment as usize;
+
+        // Validate all tensor names before processing to prevent path traversal
+        for name in self.tensors.keys() {
+            validate_tensor_name(name)?;
+        }
+
+        let sorted_tensor_names = self.select_tensors(filter);
 
         let mut header_map: RawHeader = IndexMap::new();
-        let mut current_offset = 0;
+        let mut current_offset: usize = 0;
+        let mut data_segments = Vec::new();
 
         // Hash -> (start_offset, end_offset)
         let mut written_hashes: HashMap<String, (usize, usize)> = HashMap::new();
 
-        // Pass 1: Build the Header (calculate offsets with alignment)
         for name in &sorted_tensor_names {
             let tensor = &self.tensors[*name];
 
@@ -133,8 +286,7 @@ impl VektManifest {
                 continue;
             }
 
-            let padding = (8 - (current_offset % 8)) % 8;
-            current_offset += padding;
+            current_offset = current_offset.next_multiple_of(alignment);
 
             let size = tensor.shape.iter().product::<usize>()
                 * crate::utils::get_dtype_size(&tensor.dtype);
@@ -148,109 +300,263 @@ impl VektManifest {
                 extra: tensor.extra.clone(),
             };
             header_map.insert((*name).clone(), meta);
+            data_segments.push(((*name).clone(), tensor.hash.clone(), start, size));
 
             written_hashes.insert(tensor.hash.clone(), (start, end));
             current_offset += size;
         }
 
         let header_json = serde_json::to_string(&header_map)?;
-        let header_len = header_json.len() as u64;
-        let header_bytes = header_json.as_bytes();
-
-        writer.write_all(&header_len.to_le_bytes())?;
-        writer.write_all(header_bytes)?;
+        let mut header = Vec::with_capacity(8 + header_json.len());
+        header.extend_from_slice(&(header_json.len() as u64).to_le_bytes());
+        header.extend_from_slice(header_json.as_bytes());
+
+        let data_start = header.len() as u64;
+        let segments = data_segments
+            .into_iter()
+            .map(|(tensor, hash, start, size)| Segment {
+                tensor,
+                hash,
+                offset: data_start + start as u64,
+                len: size as u64,
+            })
+            .collect();
 
-        // Pass 2: Write Data (with alignment padding and deduplication)
-        written_hashes.clear(); // Reset to track what we have effectively written in this pass
-        let mut current_write_pos = 0;
+        Ok(FileLayout {
+            size: data_start + current_offset as u64,
+            header,
+            segments,
+        })
+    }
 
-        for name in &sorted_tensor_names {
-            let tensor = &self.tensors[*name];
+    /// Writes the safetensors file for the tensors matching `filter`, reading blobs
+    /// from `repo`'s store
+    pub fn restore(
+        &self,
+        repo: &Repository,
+        output_path: &std::path::Path,
+        filter: Option<&str>,
+    ) -> Result<()> {
+        self.restore_with(repo, output_path, filter, &Hooks::default())
+    }
 
-            if written_hashes.contains_key(&tensor.hash) {
-                // Data already written for this hash
-                continue;
-            }
+    /// Like `restore`, reporting progress to and stopping when cancelled through `hooks`.
+    /// Tensor data is aligned as the repository's `restore_alignment` setting says.
+    pub fn restore_with(
+        &self,
+        repo: &Repository,
+        output_path: &std::path::Path,
+        filter: Option<&str>,
+        hooks: &Hooks,
+    ) -> Result<()> {
+        let layout = self.layout(filter, repo.settings()?.restore_alignment)?;
+        write_layout(&layout, &repo.store(), output_path, hooks)
+    }
+}
 
-            // Add Padding
-            let padding = (8 - (current_write_pos % 8)) % 8;
-            if padding > 0 {
-                let zeros = vec![0u8; padding];
-                writer.write_all(&zeros)?;
-                current_write_pos += padding;
-            }
+/// Writes the file described by `layout`, copying and verifying each blob from `store`
+fn write_layout(
+    layout: &FileLayout,
+    store: &Path,
+    output_path: &Path,
+    hooks: &Hooks,
+) -> Result<()> {
+    let total_bytes: u64 = layout.segments.iter().map(|segment| segment.len).sum();
+    let _span = tracing::info_span!(
+        "restore",
+        output = %output_path.display(),
+        tensors = layout.segments.len(),
+        bytes = total_bytes
+    )
+    .entered();
+    let started = std::time::Instant::now();
+    hooks
+        .progress
+        .started(Stage::Restore, layout.segments.len(), Some(total_bytes));
+
+    let file = File::create(output_path)?;
+    let mut writer = std::io::BufWriter::new(file);
+    writer.write_all(&layout.header)?;
+    let mut current_write_pos = layout.header.len() as u64;
+
+    for segment in &layout.segments {
+        hooks.cancel.check()?;
+
+        // Add Padding
+        let padding = (segment.offset - current_write_pos) as usize;
+        if padding > 0 {
+            let zeros = vec![0u8; padding];
+            writer.write_all(&zeros)?;
+        }
 
-            // Use centralized blob path resolution
-            let blob_path = blobs::get_blob_path(&tensor.hash);
-            if !blob_path.exists() {
-                return Err(VektError::BlobNotFound(format!(
-                    "Blob {} not found for tensor '{}'",
-                    tensor.hash, name
-                )));
-            }
+        // Use centralized blob path resolution
+        let blob_path = blobs::blob_path_in(store, &segment.hash);
+        if !blob_path.exists() {
+            return Err(VektError::BlobNotFound(format!(
+                "Blob {} not found for tensor '{}'",
+                segment.hash, segment.tensor
+            )));
+        }
 
-            // CRITICAL: Verify blob hash to detect corruption
-            let blob_data = std::fs::read(&blob_path).map_err(|e| {
+        // CRITICAL: Verify blob hash to detect corruption
+        let blob_data = match compression::decode_blob_in(store, &segment.hash)? {
+            Some(decoded) => decoded,
+            None => std::fs::read(&blob_path).map_err(|e| {
                 VektError::Io(std::io::Error::other(format!(
                     "Failed to read blob {}: {}",
-                    tensor.hash, e
+                    segment.hash, e
                 )))
-            })?;
+            })?,
+        };
+
+        verify_blob_hash(&blob_data, &segment.hash)?;
+        if blob_data.len() as u64 != segment.len {
+            return Err(VektError::TensorCorruption(format!(
+                "Tensor '{}': shape and dtype give {} bytes, but blob {} has {}",
+                segment.tensor,
+                segment.len,
+                segment.hash,
+                blob_data.len()
+            )));
+        }
 
-            verify_blob_hash(&blob_data, &tensor.hash)?;
+        // Write verified blob data
+        writer.write_all(&blob_data)?;
+        current_write_pos = segment.offset + segment.len;
+        tracing::debug!(hash = %segment.hash, bytes = segment.len, tensor = %segment.tensor, "restored tensor");
+        hooks
+            .progress
+            .blob_done(Stage::Restore, &segment.hash, segment.len);
+    }
 
-            // Write verified blob data
-            writer.write_all(&blob_data)?;
-            current_write_pos += blob_data.len();
-            written_hashes.insert(tensor.hash.clone(), (0, 0)); // Value irrelevant, just marking as written
-        }
+    writer.flush()?;
+    hooks.progress.finished(Stage::Restore);
+    tracing::info!(
+        tensors = layout.segments.len(),
+        bytes = total_bytes,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "restore complete"
+    );
 
-        writer.flush()?;
+    Ok(())
+}
 
-        Ok(())
+/// Byte layout of a safetensors file reconstructed from a manifest
+#[derive(Debug, Clone)]
+pub struct FileLayout {
+    /// Header length prefix followed by the JSON header
+    pub header: Vec<u8>,
+    /// Tensor data in file order, one per distinct blob. Gaps between the
+    /// header and segments are zero padding.
+    pub segments: Vec<Segment>,
+    /// Total file size in bytes
+    pub size: u64,
+}
+
+/// Blob data placed at a fixed position of a reconstructed file
+#[derive(Debug, Clone)]
+pub struct Segment {
+    /// First tensor stored in this blob
+    pub tensor: String,
+    pub hash: String,
+    /// Absolute offset within the file
+    pub offset: u64,
+    pub len: u64,
+}
+
+impl FileLayout {
+    /// Segments overlapping the byte range `start..end`, in file order
+    pub fn segments_in(&self, start: u64, end: u64) -> &[Segment] {
+        let first = self
+            .segments
+            .partition_point(|segment| segment.offset + segment.len <= start);
+        let last = self
+            .segments
+            .partition_point(|segment| segment.offset < end);
+        &self.segments[first..last.max(first)]
+    }
+
+    /// Fills `buf` with the file's bytes starting at `offset`, returning how many were
+    /// read (fewer at the end of the file). `read_blob(segment, offset, buf)` supplies
+    /// `buf.len()` bytes of a segment's blob from `offset` within it, so callers decide
+    /// how blobs are opened, cached or fetched; only overlapping blobs are touched.
+    pub fn read_at(
+        &self,
+        offset: u64,
+        buf: &mut [u8],
+        mut read_blob: impl FnMut(&Segment, u64, &mut [u8]) -> Result<()>,
+    ) -> Result<usize> {
+        if offset >= self.size {
+            return Ok(0);
+        }
+        let end = self.size.min(offset + buf.len() as u64);
+        let buf = &mut buf[..(end - offset) as usize];
+        // Padding is whatever no header or segment overwrites
+        buf.fill(0);
+
+        let header_len = self.header.len() as u64;
+        if offset < header_len {
+            let header_end = end.min(header_len);
+            buf[..(header_end - offset) as usize]
+                .copy_from_slice(&self.header[offset as usize..header_end as usize]);
+        }
+
+        for segment in self.segments_in(offset, end) {
+            let from = offset.max(segment.offset);
+            let to = end.min(segment.offset + segment.len);
+            read_blob(
+                segment,
+                from - segment.offset,
+                &mut buf[(from - offset) as usize..(to - offset) as usize],
+            )?;
+        }
+        Ok(buf.len())
     }
 }
 
 impl VektConfig {
-    pub fn load() -> Result<Self> {
-        let root = find_vekt_root().ok_or(VektError::RepoNotFound)?;
-        let path = root.join(".vekt").join("config.json");
-        if !path.exists() {
-            return Ok(VektConfig::default());
-        }
-        let file = File::open(&path).map_err(|e| {
-            VektError::Io(std::io::Error::other(format!(
-                "Failed to open config file at {}: {}",
-                path.display(),
-                e
-            )))
-        })?;
-        let reader = std::io::BufReader::new(file);
-        let config = serde_json::from_reader(reader).map_err(|e| {
-            VektError::InvalidManifest(format!(
-                "Failed to parse config file at {}: {}",
-                path.display(),
-                e
-            ))
-        })?;
-        Ok(config)
+    pub fn add_remote(&mut self, name: String, remote: RemoteConfig) {
+        self.remotes.insert(name, remote);
     }
 
-    pub fn save(&self) -> Result<()> {
-        let dir = std::env::current_dir()?.join(".vekt");
-        ensure_vekt_dir(&dir)?;
-        let config_path = dir.join("config.json");
-        let json = serde_json::to_string_pretty(self)?;
-        write_file_atomic(&config_path, json.as_bytes()).map_err(|e| {
-            VektError::Io(std::io::Error::other(format!(
-                "Failed to write config file: {}",
-                e
-            )))
-        })?;
+    /// Defines or replaces a group of existing remotes, listed in priority order
+    pub fn add_group(&mut self, name: String, members: Vec<String>) -> Result<()> {
+        if self.remotes.contains_key(&name) {
+            return Err(VektError::RemoteError(format!(
+                "'{}' is already the name of a remote",
+                name
+            )));
+        }
+        if members.is_empty() {
+            return Err(VektError::RemoteError(format!(
+                "Group '{}' needs at least one remote",
+                name
+            )));
+        }
+        if let Some(missing) = members.iter().find(|m| !self.remotes.contains_key(*m)) {
+            return Err(VektError::RemoteNotFound(missing.clone()));
+        }
+        self.groups.insert(name, members);
         Ok(())
     }
 
-    pub fn add_remote(&mut self, name: String, url: String) {
-        self.remotes.insert(name, url);
+    /// Resolves a remote or group name to the remotes it stands for, in priority order
+    pub fn resolve_remotes(&self, name: &str) -> Result<Vec<(&str, &RemoteConfig)>> {
+        if let Some((name, remote)) = self.remotes.get_key_value(name) {
+            return Ok(vec![(name.as_str(), remote)]);
+        }
+        let members = self
+            .groups
+            .get(name)
+            .ok_or_else(|| VektError::RemoteNotFound(name.to_string()))?;
+        members
+            .iter()
+            .map(|member| {
+                self.remotes
+                    .get_key_value(member)
+                    .map(|(name, remote)| (name.as_str(), remote))
+                    .ok_or_else(|| VektError::RemoteNotFound(member.clone()))
+            })
+            .collect()
     }
 }
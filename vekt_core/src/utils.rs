@@ -46,32 +46,20 @@ pub fn find_vekt_root() -> Option<PathBuf> {
     None
 }
 
-/// Returns the path to the blobs directory.
-/// Uses the local repository's .vekt/blobs if found, otherwise defaults to ./.vekt/blobs
-/// Also ensures .vekt has a .gitignore file
-pub fn get_store_path() -> PathBuf {
-    let vekt_dir = match find_vekt_root() {
-        Some(root) => root.join(".vekt"),
-        None => std::env::current_dir()
-            .unwrap_or_else(|_| PathBuf::from("."))
-            .join(".vekt"),
-    };
-
-    // Ensure .vekt has .gitignore (ignore errors as this is best-effort)
-    let _ = ensure_vekt_dir(&vekt_dir);
-
-    vekt_dir.join("blobs")
-}
-
 pub fn get_dtype_size(dtype: &str) -> usize {
     match dtype {
+        "F64" => 8,
         "F32" => 4,
         "F16" => 2,
         "BF16" => 2,
+        "F8_E4M3" | "F8_E5M2" => 1,
         "I64" => 8,
         "I32" => 4,
         "I16" => 2,
         "I8" => 1,
+        "U64" => 8,
+        "U32" => 4,
+        "U16" => 2,
         "U8" => 1,
         "BOOL" => 1,
         _ => 1, // Fallback
@@ -86,10 +74,8 @@ impl LockFile {
     /// Maximum age of lock file before considering it stale (5 minutes)
     const STALE_LOCK_THRESHOLD_SECS: u64 = 300;
 
-    pub fn lock() -> Result<Self> {
-        // Use the found root or current dir for locking
-        let root = find_vekt_root()
-            .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    /// Locks the repository at `root`
+    pub fn lock_in(root: &Path) -> Result<Self> {
         let vekt_dir = root.join(".vekt");
         let path = vekt_dir.join("lock");
 
@@ -110,9 +96,9 @@ impl LockFile {
             let age_secs = duration.as_secs();
             if age_secs > Self::STALE_LOCK_THRESHOLD_SECS {
                 // Remove stale lock
-                eprintln!(
-                    "Warning: Removing stale lock file (age: {} seconds). Previous process may have crashed.",
-                    age_secs
+                tracing::warn!(
+                    age_secs,
+                    "removing stale lock file; a previous process may have crashed"
                 );
                 fs::remove_file(&path).map_err(|e| {
                     VektError::Io(io::Error::other(format!(
@@ -1,7 +1,7 @@
 use crate::blobs;
 use crate::errors::{Result, VektError};
 use regex::Regex;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 // Compile regexes once and reuse them
@@ -58,15 +58,20 @@ pub fn validate_tensor_name(name: &str) -> Result<()> {
     Ok(())
 }
 
-/// Validates S3 URL format
-pub fn validate_s3_url(url: &str) -> Result<String> {
+/// Validates S3 URL format (`s3://bucket` or `s3://bucket/key/prefix`).
+/// Returns the bucket name and the normalized key prefix, if any.
+pub fn validate_s3_url(url: &str) -> Result<(String, Option<String>)> {
     if !url.starts_with("s3://") {
         return Err(VektError::InvalidRemoteUrl(
             "URL must start with s3://".to_string(),
         ));
     }
 
-    let bucket_name = url.trim_start_matches("s3://").trim_end_matches('/');
+    let location = url.trim_start_matches("s3://").trim_end_matches('/');
+    let (bucket_name, prefix) = match location.split_once('/') {
+        Some((bucket, prefix)) => (bucket, Some(validate_key_prefix(prefix)?)),
+        None => (location, None),
+    };
 
     // Use regex for cleaner S3 bucket name validation
     if !get_s3_bucket_regex().is_match(bucket_name) {
@@ -76,7 +81,95 @@ pub fn validate_s3_url(url: &str) -> Result<String> {
         )));
     }
 
-    Ok(bucket_name.to_string())
+    Ok((bucket_name.to_string(), prefix))
+}
+
+/// Validates a key prefix inside a bucket and normalizes it to `team/models/` form
+pub fn validate_key_prefix(prefix: &str) -> Result<String> {
+    let trimmed = prefix.trim_matches('/');
+    if trimmed.is_empty() {
+        return Err(VektError::InvalidRemoteUrl(
+            "Key prefix must not be empty".to_string(),
+        ));
+    }
+
+    validate_path_safe(trimmed)?;
+    if trimmed.split('/').any(|segment| segment.is_empty())
+        || !get_tensor_name_regex().is_match(trimmed)
+    {
+        return Err(VektError::InvalidRemoteUrl(format!(
+            "Invalid key prefix '{}'. Only alphanumeric, dots, underscores, hyphens, and single forward slashes allowed.",
+            prefix
+        )));
+    }
+
+    Ok(format!("{}/", trimmed))
+}
+
+/// Validates a `file://` URL and returns the absolute directory it points to
+pub fn validate_file_url(url: &str) -> Result<PathBuf> {
+    let Some(path) = url.strip_prefix("file://") else {
+        return Err(VektError::InvalidRemoteUrl(
+            "URL must start with file://".to_string(),
+        ));
+    };
+
+    // file:///C:/remote on Windows carries an extra leading slash before the drive
+    let path = if cfg!(windows) {
+        path.trim_start_matches('/')
+    } else {
+        path
+    };
+
+    let path = PathBuf::from(path);
+    if path.as_os_str().is_empty() || !path.is_absolute() {
+        return Err(VektError::InvalidRemoteUrl(format!(
+            "file:// URLs must use an absolute path, e.g. file:///mnt/shared/vekt (got '{}')",
+            url
+        )));
+    }
+
+    Ok(path)
+}
+
+/// Validates an `http://` or `https://` URL and returns it with a trailing slash,
+/// so object paths can be appended directly
+pub fn validate_http_url(url: &str) -> Result<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| {
+            VektError::InvalidRemoteUrl("URL must start with http:// or https://".to_string())
+        })?;
+
+    let host = rest.split('/').next().unwrap_or_default();
+    if host.is_empty() || host.contains(char::is_whitespace) {
+        return Err(VektError::InvalidRemoteUrl(format!(
+            "Invalid host in URL '{}'",
+            url
+        )));
+    }
+
+    if url.ends_with('/') {
+        Ok(url.to_string())
+    } else {
+        Ok(format!("{}/", url))
+    }
+}
+
+/// Validates a `vekt://` (or TLS `vekts://`) server URL and returns the equivalent
+/// `http(s)://` base URL with a trailing slash
+pub fn validate_vekt_url(url: &str) -> Result<String> {
+    let http_url = if let Some(rest) = url.strip_prefix("vekt://") {
+        format!("http://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("vekts://") {
+        format!("https://{}", rest)
+    } else {
+        return Err(VektError::InvalidRemoteUrl(
+            "URL must start with vekt:// or vekts://".to_string(),
+        ));
+    };
+    validate_http_url(&http_url)
 }
 
 /// Verifies blob integrity by comparing hash
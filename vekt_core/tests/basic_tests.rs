@@ -4,6 +4,7 @@ use std::fs::File;
 use std::io::Write;
 
 // Import from the public API of the crate
+use vekt_core::repository::Repository;
 use vekt_core::storage::{RawHeader, RawTensorMetaData};
 use vekt_core::{ModelArchiver, SafetensorFile};
 
@@ -82,8 +83,16 @@ fn test_safetensor_process() {
     );
     let header_len = 128;
     let safetensor_file = SafetensorFile::new(mmap, header, header_len);
-    let manifest = safetensor_file.process(true).unwrap();
+    let root = std::env::temp_dir().join(format!("vekt_basic_test_{}", rand::random::<u64>()));
+    let repo = Repository::init(&root).unwrap();
+    let manifest = safetensor_file.process(&repo, true).unwrap();
     assert_eq!(manifest.tensors.len(), 1);
     assert!(manifest.tensors.contains_key("tensor1"));
     assert_eq!(manifest.tensors["tensor1"].index, 0);
+    assert!(
+        repo.store()
+            .join(&manifest.tensors["tensor1"].hash)
+            .exists()
+    );
+    std::fs::remove_dir_all(root).ok();
 }
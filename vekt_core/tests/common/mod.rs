@@ -0,0 +1,332 @@
+//! Minimal S3-compatible stand-in for exercising the S3 remote against localhost,
+//! in the spirit of a local MinIO. It only understands path-style requests and
+//! ignores request signatures.
+
+use axum::body::Bytes;
+use axum::http::{HeaderMap, Method, StatusCode, Uri, header};
+use axum::response::{IntoResponse, Response};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Object data keyed by `(bucket, key)`
+type ObjectMap = BTreeMap<(String, String), Vec<u8>>;
+
+struct PendingUpload {
+    bucket: String,
+    key: String,
+    parts: BTreeMap<u32, Vec<u8>>,
+}
+
+#[derive(Clone, Default)]
+pub struct S3Stub {
+    objects: Arc<Mutex<ObjectMap>>,
+    requests: Arc<Mutex<Vec<String>>>,
+    access_keys: Arc<Mutex<Vec<String>>>,
+    uploads: Arc<Mutex<BTreeMap<String, PendingUpload>>>,
+    next_upload_id: Arc<Mutex<u64>>,
+    fail_part: Arc<Mutex<Option<u32>>>,
+    ranges: Arc<Mutex<Vec<usize>>>,
+    unavailable: Arc<Mutex<usize>>,
+}
+
+impl S3Stub {
+    /// Starts the stand-in on an ephemeral port and returns it with its endpoint URL
+    pub async fn start() -> (Self, String) {
+        let stub = S3Stub::default();
+        let handler_stub = stub.clone();
+        let app = axum::Router::new()
+            .fallback(
+                move |method: Method, uri: Uri, headers: HeaderMap, body: Bytes| {
+                    let stub = handler_stub.clone();
+                    async move { stub.handle(method, uri, headers, body) }
+                },
+            )
+            // Multipart parts are at least 5 MiB, over axum's default body limit
+            .layer(axum::extract::DefaultBodyLimit::disable());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (stub, format!("http://{}", addr))
+    }
+
+    /// Keys stored in `bucket`, in lexicographic order
+    pub fn keys(&self, bucket: &str) -> Vec<String> {
+        self.objects
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|(b, _)| b == bucket)
+            .map(|(_, k)| k.clone())
+            .collect()
+    }
+
+    /// `METHOD /path` for every request received so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Makes every upload of the given multipart part number fail until cleared
+    pub fn fail_part(&self, part_number: Option<u32>) {
+        *self.fail_part.lock().unwrap() = part_number;
+    }
+
+    /// Answers the next `count` requests with 503 Slow Down
+    pub fn fail_next(&self, count: usize) {
+        *self.unavailable.lock().unwrap() = count;
+    }
+
+    /// Number of multipart uploads started but neither completed nor aborted
+    pub fn pending_uploads(&self) -> usize {
+        self.uploads.lock().unwrap().len()
+    }
+
+    /// Start offsets of ranged GETs served so far
+    pub fn ranges(&self) -> Vec<usize> {
+        self.ranges.lock().unwrap().clone()
+    }
+
+    /// Access key IDs seen in request signatures, in order
+    pub fn access_keys(&self) -> Vec<String> {
+        self.access_keys.lock().unwrap().clone()
+    }
+
+    fn handle(&self, method: Method, uri: Uri, headers: HeaderMap, body: Bytes) -> Response {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", method, uri.path()));
+
+        {
+            let mut unavailable = self.unavailable.lock().unwrap();
+            if *unavailable > 0 {
+                *unavailable -= 1;
+                return StatusCode::SERVICE_UNAVAILABLE.into_response();
+            }
+        }
+
+        // "AWS4-HMAC-SHA256 Credential=<access key>/<date>/<region>/s3/aws4_request, ..."
+        if let Some(access_key) = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split("Credential=").nth(1))
+            .and_then(|v| v.split('/').next())
+        {
+            self.access_keys
+                .lock()
+                .unwrap()
+                .push(access_key.to_string());
+        }
+
+        let path = uri.path().trim_start_matches('/');
+        let (bucket, key) = match path.split_once('/') {
+            Some((bucket, key)) => (bucket.to_string(), decode(key)),
+            None => (path.to_string(), String::new()),
+        };
+        let query = parse_query(uri.query().unwrap_or_default());
+
+        if query.contains_key("uploads") || query.contains_key("uploadId") {
+            return self.handle_multipart(method, bucket, key, &query, body);
+        }
+
+        let mut objects = self.objects.lock().unwrap();
+        match method {
+            Method::GET if key.is_empty() => {
+                let prefix = query.get("prefix").cloned().unwrap_or_default();
+                list_response(&bucket, &prefix, &objects)
+            }
+            Method::GET | Method::HEAD => match objects.get(&(bucket, key)) {
+                Some(data) if method == Method::HEAD => (
+                    StatusCode::OK,
+                    [(header::CONTENT_LENGTH, data.len().to_string())],
+                )
+                    .into_response(),
+                Some(data) => match requested_range(&headers) {
+                    Some(start) if start >= data.len() => {
+                        StatusCode::RANGE_NOT_SATISFIABLE.into_response()
+                    }
+                    Some(start) => {
+                        self.ranges.lock().unwrap().push(start);
+                        (StatusCode::PARTIAL_CONTENT, data[start..].to_vec()).into_response()
+                    }
+                    None => (StatusCode::OK, data.clone()).into_response(),
+                },
+                None => StatusCode::NOT_FOUND.into_response(),
+            },
+            Method::PUT => {
+                objects.insert((bucket, key), body.to_vec());
+                (StatusCode::OK, [(header::ETAG, "\"stub\"")]).into_response()
+            }
+            Method::DELETE => {
+                objects.remove(&(bucket, key));
+                StatusCode::NO_CONTENT.into_response()
+            }
+            _ => StatusCode::NOT_IMPLEMENTED.into_response(),
+        }
+    }
+}
+
+impl S3Stub {
+    fn handle_multipart(
+        &self,
+        method: Method,
+        bucket: String,
+        key: String,
+        query: &BTreeMap<String, String>,
+        body: Bytes,
+    ) -> Response {
+        let mut uploads = self.uploads.lock().unwrap();
+        let upload_id = query.get("uploadId").cloned().unwrap_or_default();
+
+        match method {
+            Method::GET => {
+                let prefix = query.get("prefix").cloned().unwrap_or_default();
+                let entries: String = uploads
+                    .iter()
+                    .filter(|(_, u)| u.bucket == bucket && u.key.starts_with(&prefix))
+                    .map(|(id, u)| {
+                        format!(
+                            "<Upload><Key>{}</Key><UploadId>{}</UploadId><Initiated>2024-01-01T00:00:00.000Z</Initiated><StorageClass>STANDARD</StorageClass></Upload>",
+                            u.key, id
+                        )
+                    })
+                    .collect();
+                let xml = format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><ListMultipartUploadsResult><Bucket>{}</Bucket><Prefix>{}</Prefix><IsTruncated>false</IsTruncated>{}</ListMultipartUploadsResult>",
+                    bucket, prefix, entries
+                );
+                (StatusCode::OK, xml).into_response()
+            }
+            Method::POST if query.contains_key("uploads") => {
+                let mut next = self.next_upload_id.lock().unwrap();
+                *next += 1;
+                let id = format!("upload-{}", next);
+                uploads.insert(
+                    id.clone(),
+                    PendingUpload {
+                        bucket: bucket.clone(),
+                        key: key.clone(),
+                        parts: BTreeMap::new(),
+                    },
+                );
+                let xml = format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><InitiateMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                    bucket, key, id
+                );
+                (StatusCode::OK, xml).into_response()
+            }
+            Method::PUT => {
+                let part_number: u32 = query
+                    .get("partNumber")
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or_default();
+                if *self.fail_part.lock().unwrap() == Some(part_number) {
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+                let Some(upload) = uploads.get_mut(&upload_id) else {
+                    return StatusCode::NOT_FOUND.into_response();
+                };
+                upload.parts.insert(part_number, body.to_vec());
+                let etag = format!("\"part-{}\"", part_number);
+                (StatusCode::OK, [(header::ETAG, etag)]).into_response()
+            }
+            Method::POST => {
+                let Some(upload) = uploads.remove(&upload_id) else {
+                    return StatusCode::NOT_FOUND.into_response();
+                };
+                let data = upload.parts.into_values().flatten().collect();
+                self.objects
+                    .lock()
+                    .unwrap()
+                    .insert((upload.bucket.clone(), upload.key.clone()), data);
+                let xml = format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?><CompleteMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key><ETag>\"stub\"</ETag></CompleteMultipartUploadResult>",
+                    upload.bucket, upload.key
+                );
+                (StatusCode::OK, xml).into_response()
+            }
+            Method::DELETE => match uploads.remove(&upload_id) {
+                Some(_) => StatusCode::NO_CONTENT.into_response(),
+                None => StatusCode::NOT_FOUND.into_response(),
+            },
+            _ => StatusCode::NOT_IMPLEMENTED.into_response(),
+        }
+    }
+
+    /// Returns the stored bytes of an object
+    pub fn object(&self, bucket: &str, key: &str) -> Option<Vec<u8>> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(&(bucket.to_string(), key.to_string()))
+            .cloned()
+    }
+}
+
+fn list_response(bucket: &str, prefix: &str, objects: &ObjectMap) -> Response {
+    let contents: String = objects
+        .iter()
+        .filter(|((b, k), _)| b == bucket && k.starts_with(prefix))
+        .map(|((_, k), data)| {
+            format!(
+                "<Contents><Key>{}</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified><ETag>\"stub\"</ETag><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                k,
+                data.len()
+            )
+        })
+        .collect();
+    let xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><ListBucketResult><Name>{}</Name><Prefix>{}</Prefix><MaxKeys>1000</MaxKeys><IsTruncated>false</IsTruncated>{}</ListBucketResult>",
+        bucket, prefix, contents
+    );
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/xml")],
+        xml,
+    )
+        .into_response()
+}
+
+/// Start offset of a `Range: bytes=<start>-[<end>]` header; end offsets are ignored
+fn requested_range(headers: &HeaderMap) -> Option<usize> {
+    headers
+        .get(header::RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes=")?
+        .split('-')
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((k, v)) => (decode(k), decode(v)),
+            None => (decode(pair), String::new()),
+        })
+        .collect()
+}
+
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && let Ok(byte) = u8::from_str_radix(&value[i + 1..i + 3], 16)
+        {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -1,41 +1,33 @@
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
 
 use vekt_core::ModelArchiver;
 use vekt_core::SafetensorFile;
+use vekt_core::config::SettingsLayer;
+use vekt_core::errors::VektError;
 use vekt_core::gc;
+use vekt_core::progress::{CancellationToken, Hooks, Progress, Stage};
+use vekt_core::repository::Repository;
 
-fn setup_test_env() -> (PathBuf, PathBuf) {
+fn setup_test_env() -> Repository {
     let mut dir = env::temp_dir();
     dir.push("vekt_test_run");
     let rnd: u64 = rand::random();
     dir.push(format!("{}", rnd)); // distinct dir
-    fs::create_dir_all(&dir).unwrap();
-
-    let root = dir.clone();
-    unsafe {
-        env::set_var("VEKT_ROOT", &root);
-    }
 
     // Create .vekt structure
-    let vekt_dir = root.join(".vekt");
-    fs::create_dir(&vekt_dir).unwrap();
-    fs::create_dir(vekt_dir.join("blobs")).unwrap();
-
-    (root, vekt_dir)
-}
-
-fn cleanup(root: PathBuf) {
-    let _ = fs::remove_dir_all(root);
-    unsafe {
-        env::remove_var("VEKT_ROOT");
-    }
+    Repository::init(&dir)
+        .unwrap()
+        // Settings come from the repository alone, not the user config or VEKT_* variables
+        .with_settings_layers(SettingsLayer::default(), SettingsLayer::default())
 }
 
 #[test]
 fn test_full_workflow() {
-    let (root, _vekt_dir) = setup_test_env();
+    let repo = setup_test_env();
+    let root = repo.root().to_path_buf();
 
     // Create dummy safetensors
     let model_path = root.join("model.safetensors");
@@ -46,7 +38,6 @@ fn test_full_workflow() {
         // 8 bytes length
         let header_json = r#"{"t": {"dtype":"F32","shape":[1],"data_offsets":[0,4]}}"#;
         let len = header_json.len() as u64;
-        use std::io::Write;
         f.write_all(&len.to_le_bytes()).unwrap();
         f.write_all(header_json.as_bytes()).unwrap();
         f.write_all(&[0u8, 1u8, 2u8, 3u8]).unwrap(); // 4 bytes data
@@ -54,7 +45,7 @@ fn test_full_workflow() {
 
     // 1. Process (Archive)
     let file = SafetensorFile::open(model_path.to_str().unwrap()).expect("Failed to open");
-    let manifest = file.process(true).expect("Failed to process");
+    let manifest = file.process(&repo, true).expect("Failed to process");
 
     // Verify blob exists
     let hash = &manifest.tensors["t"].hash;
@@ -68,7 +59,8 @@ fn test_full_workflow() {
 
     // 2. Restore
     let restore_path = root.join("restored.safetensors");
-    vekt_core::SafetensorFile::restore(&manifest, &restore_path, None).expect("Restore failed");
+    vekt_core::SafetensorFile::restore(&repo, &manifest, &restore_path, None)
+        .expect("Restore failed");
 
     assert!(restore_path.exists());
 
@@ -93,9 +85,214 @@ fn test_full_workflow() {
     // If we delete manifest, GC should remove blob
     fs::remove_file(manifest_path).unwrap();
 
-    let stats = gc::run_gc(&root).expect("GC failed");
+    let stats = gc::run_gc(&repo).expect("GC failed");
     assert_eq!(stats.deleted, 1, "GC should delete 1 blob");
     assert!(!blob_path.exists(), "Blob should be gone");
 
-    cleanup(root);
+    let _ = fs::remove_dir_all(root);
+}
+
+/// Records blob events as (stage, skipped, bytes)
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<(Stage, bool, u64)>>,
+    finished: Mutex<Vec<Stage>>,
+}
+
+impl Recorder {
+    fn count(&self, stage: Stage, skipped: bool) -> usize {
+        let events = self.events.lock().unwrap();
+        events
+            .iter()
+            .filter(|(s, k, _)| *s == stage && *k == skipped)
+            .count()
+    }
+}
+
+impl Progress for Recorder {
+    fn blob_done(&self, stage: Stage, _hash: &str, bytes: u64) {
+        self.events.lock().unwrap().push((stage, false, bytes));
+    }
+
+    fn blob_skipped(&self, stage: Stage, _hash: &str, bytes: u64) {
+        self.events.lock().unwrap().push((stage, true, bytes));
+    }
+
+    fn finished(&self, stage: Stage) {
+        self.finished.lock().unwrap().push(stage);
+    }
+}
+
+#[test]
+fn test_progress_and_cancellation() {
+    let repo = setup_test_env();
+    let root = repo.root().to_path_buf();
+    let model_path = root.join("model.safetensors");
+    {
+        let mut f = fs::File::create(&model_path).unwrap();
+        let header_json = r#"{"a": {"dtype":"U8","shape":[4],"data_offsets":[0,4]}, "b": {"dtype":"U8","shape":[2],"data_offsets":[4,6]}}"#;
+        f.write_all(&(header_json.len() as u64).to_le_bytes())
+            .unwrap();
+        f.write_all(header_json.as_bytes()).unwrap();
+        f.write_all(&[1, 2, 3, 4, 5, 6]).unwrap();
+    }
+    let file = SafetensorFile::open(model_path.to_str().unwrap()).unwrap();
+
+    // A cancelled token stops the add before anything is stored
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let cancelled = Hooks::default().with_cancel(cancel);
+    assert!(matches!(
+        file.process_with(&repo, true, &cancelled),
+        Err(VektError::Cancelled(_))
+    ));
+    assert_eq!(fs::read_dir(repo.store()).unwrap().count(), 0);
+
+    let recorder = Arc::new(Recorder::default());
+    let hooks = Hooks::default().with_progress(recorder.clone());
+    let manifest = file.process_with(&repo, true, &hooks).unwrap();
+    assert_eq!(recorder.count(Stage::Hash, false), 2);
+    assert_eq!(recorder.count(Stage::Store, false), 2);
+
+    // Adding again finds every blob already stored
+    file.process_with(&repo, true, &hooks).unwrap();
+    assert_eq!(recorder.count(Stage::Store, true), 2);
+
+    let restored = root.join("restored.safetensors");
+    manifest
+        .restore_with(&repo, &restored, None, &hooks)
+        .unwrap();
+    let restore_bytes: u64 = recorder
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(stage, _, _)| *stage == Stage::Restore)
+        .map(|(_, _, bytes)| bytes)
+        .sum();
+    assert_eq!(restore_bytes, 6);
+    assert_eq!(
+        *recorder.finished.lock().unwrap(),
+        [
+            Stage::Hash,
+            Stage::Store,
+            Stage::Hash,
+            Stage::Store,
+            Stage::Restore
+        ]
+    );
+
+    let _ = fs::remove_dir_all(root);
+}
+
+/// Collects what a subscriber writes so the test can read the log lines back
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_add_emits_summary_event() {
+    let repo = setup_test_env();
+    let root = repo.root().to_path_buf();
+    let model_path = root.join("model.safetensors");
+    {
+        let mut f = fs::File::create(&model_path).unwrap();
+        let header_json = r#"{"t": {"dtype":"U8","shape":[3],"data_offsets":[0,3]}}"#;
+        f.write_all(&(header_json.len() as u64).to_le_bytes())
+            .unwrap();
+        f.write_all(header_json.as_bytes()).unwrap();
+        f.write_all(&[7, 8, 9]).unwrap();
+    }
+    let file = SafetensorFile::open(model_path.to_str().unwrap()).unwrap();
+
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_max_level(tracing::Level::INFO)
+        .with_writer(move || writer.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, || file.process(&repo, true).unwrap());
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let summary: serde_json::Value = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .find(|event: &serde_json::Value| event["fields"]["message"] == "add complete")
+        .expect("no add summary logged");
+    assert_eq!(summary["fields"]["bytes"], 3);
+    assert_eq!(summary["fields"]["blobs_stored"], 1);
+    assert_eq!(summary["span"]["name"], "add");
+
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn test_add_compresses_blobs_when_configured() {
+    let repo = setup_test_env();
+    let root = repo.root().to_path_buf();
+    let mut config = repo.config().unwrap();
+    config.settings.set("compression", "true").unwrap();
+    config.settings.set("compression_level", "3").unwrap();
+    repo.save_config(&config).unwrap();
+
+    // One tensor zstd shrinks and one it cannot, which stays stored raw
+    let write = |name: &str, fill: u8| {
+        let path = root.join(name);
+        let mut f = fs::File::create(&path).unwrap();
+        let header_json = r#"{"zeros": {"dtype":"U8","shape":[4096],"data_offsets":[0,4096]}, "noise": {"dtype":"U8","shape":[4096],"data_offsets":[4096,8192]}}"#;
+        f.write_all(&(header_json.len() as u64).to_le_bytes())
+            .unwrap();
+        f.write_all(header_json.as_bytes()).unwrap();
+        f.write_all(&[fill; 4096]).unwrap();
+        let noise: Vec<u8> = (0..4096).map(|_| rand::random()).collect();
+        f.write_all(&noise).unwrap();
+        path
+    };
+    let stored = |hash: &str| fs::metadata(repo.store().join(hash)).unwrap().len();
+
+    let model_path = write("model.safetensors", 0);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, true)
+        .unwrap();
+    assert!(stored(&manifest.tensors["zeros"].hash) < 4096);
+    assert_eq!(stored(&manifest.tensors["noise"].hash), 4096);
+
+    // Restored tensors come back decoded
+    let restore_path = root.join("restored.safetensors");
+    SafetensorFile::restore(&repo, &manifest, &restore_path, None).unwrap();
+    let restored = SafetensorFile::open(restore_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, false)
+        .unwrap();
+    assert_eq!(
+        restored.tensors["zeros"].hash,
+        manifest.tensors["zeros"].hash
+    );
+    assert_eq!(
+        restored.tensors["noise"].hash,
+        manifest.tensors["noise"].hash
+    );
+
+    // An explicit choice overrides the setting
+    let raw_path = write("raw.safetensors", 7);
+    let raw = SafetensorFile::open(raw_path.to_str().unwrap())
+        .unwrap()
+        .with_compression(false)
+        .process(&repo, true)
+        .unwrap();
+    assert_eq!(stored(&raw.tensors["zeros"].hash), 4096);
+
+    let _ = fs::remove_dir_all(&root);
 }
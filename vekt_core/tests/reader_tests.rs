@@ -0,0 +1,179 @@
+use indexmap::IndexMap;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use vekt_core::blobs;
+use vekt_core::compression;
+use vekt_core::errors::VektError;
+use vekt_core::reader::TensorReader;
+use vekt_core::storage::{ManifestTensor, VektManifest};
+
+fn temp_store(name: &str) -> PathBuf {
+    let mut dir = env::temp_dir();
+    dir.push("vekt_reader_test");
+    dir.push(format!("{}_{}", name, rand::random::<u64>()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes `data` as a blob into `store` and returns its hash
+fn put_blob(store: &std::path::Path, data: &[u8]) -> String {
+    let hash = blobs::compute_blob_hash(data);
+    fs::write(blobs::blob_path_in(store, &hash), data).unwrap();
+    hash
+}
+
+fn manifest(tensors: &[(&str, &str, Vec<usize>, &str)]) -> VektManifest {
+    let mut map = BTreeMap::new();
+    for (index, (name, dtype, shape, hash)) in tensors.iter().enumerate() {
+        map.insert(
+            name.to_string(),
+            ManifestTensor {
+                shape: shape.clone(),
+                dtype: dtype.to_string(),
+                hash: hash.to_string(),
+                index,
+                extra: IndexMap::new(),
+            },
+        );
+    }
+    VektManifest {
+        tensors: map,
+        version: "1.0".to_string(),
+        total_size: 0,
+    }
+}
+
+#[test]
+fn test_reads_tensors_in_index_order() {
+    let store = temp_store("order");
+    let weight: Vec<u8> = [1.0f32, 2.0, 3.0, 4.0]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let bias = vec![7u8, 8];
+    let weight_hash = put_blob(&store, &weight);
+    let bias_hash = put_blob(&store, &bias);
+
+    // Names sort the other way round, so order must come from `index`
+    let reader = TensorReader::with_store(
+        manifest(&[
+            ("z.weight", "F32", vec![2, 2], &weight_hash),
+            ("a.bias", "U8", vec![2], &bias_hash),
+        ]),
+        store.clone(),
+    );
+    assert_eq!(reader.names(), vec!["z.weight", "a.bias"]);
+
+    let tensor = reader.tensor("z.weight").unwrap();
+    assert_eq!(tensor.shape, vec![2, 2]);
+    assert_eq!(tensor.dtype, "F32");
+    assert!(tensor.is_mapped());
+    assert_eq!(tensor.bytes().unwrap(), &weight[..]);
+
+    let all: Vec<_> = reader.tensors().map(|t| t.unwrap()).collect();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[1].name, "a.bias");
+    assert_eq!(all[1].bytes().unwrap(), &bias[..]);
+
+    assert!(matches!(
+        reader.tensor("missing"),
+        Err(VektError::InvalidTensorName(_))
+    ));
+    fs::remove_dir_all(store).ok();
+}
+
+#[test]
+fn test_hash_is_verified_on_access() {
+    let store = temp_store("verify");
+    let data = vec![1u8, 2, 3, 4];
+    let hash = put_blob(&store, &data);
+    let reader = TensorReader::with_store(manifest(&[("t", "U8", vec![4], &hash)]), store.clone());
+
+    // Same size, different content: opening succeeds, reading the bytes does not
+    fs::write(blobs::blob_path_in(&store, &hash), [9u8, 9, 9, 9]).unwrap();
+    let tensor = reader.tensor("t").unwrap();
+    assert_eq!(tensor.bytes_unverified(), &[9, 9, 9, 9]);
+    assert!(matches!(
+        tensor.bytes(),
+        Err(VektError::HashMismatch { .. })
+    ));
+
+    // Wrong size is caught when opening
+    fs::write(blobs::blob_path_in(&store, &hash), [1u8, 2]).unwrap();
+    assert!(matches!(
+        reader.tensor("t"),
+        Err(VektError::TensorCorruption(_))
+    ));
+
+    fs::remove_file(blobs::blob_path_in(&store, &hash)).unwrap();
+    assert!(matches!(
+        reader.tensor("t"),
+        Err(VektError::BlobNotFound(_))
+    ));
+    fs::remove_dir_all(store).ok();
+}
+
+#[test]
+fn test_compressed_blobs_are_decoded() {
+    let store = temp_store("compressed");
+    let data = vec![42u8; 4096];
+    let hash = blobs::compute_blob_hash(&data);
+    let compressed =
+        compression::save_blob_with_compression(&blobs::blob_path_in(&store, &hash), &data, true)
+            .unwrap();
+    assert!(compressed);
+
+    let reader =
+        TensorReader::with_store(manifest(&[("t", "U8", vec![4096], &hash)]), store.clone());
+    let tensor = reader.tensor("t").unwrap();
+    assert!(!tensor.is_mapped());
+    assert_eq!(tensor.bytes().unwrap(), &data[..]);
+    fs::remove_dir_all(store).ok();
+}
+
+#[test]
+fn test_slices_are_verified_without_hashing_the_whole_blob() {
+    let store = temp_store("slice");
+    // 512 rows of 4 KiB, large enough for an outboard tree
+    let (rows, row_size) = (512, 4096);
+    let data: Vec<u8> = (0..rows * row_size).map(|i| (i % 251) as u8).collect();
+    let hash = put_blob(&store, &data);
+    let reader = TensorReader::with_store(
+        manifest(&[("embed", "F32", vec![rows, row_size / 4], &hash)]),
+        store.clone(),
+    );
+
+    // The first read builds the outboard, verifying the blob once
+    let slice = reader.slice("embed", 10..12).unwrap();
+    assert_eq!(slice.shape, vec![2, row_size / 4]);
+    assert_eq!(slice.dtype, "F32");
+    assert_eq!(slice.data, &data[10 * row_size..12 * row_size]);
+    assert!(blobs::outboard_path_in(&store, &hash).exists());
+    assert_eq!(
+        reader.read_range("embed", 1000..1010).unwrap(),
+        &data[1000..1010]
+    );
+
+    // Corrupt row 400: rows elsewhere still read, that one is caught
+    let mut corrupted = data.clone();
+    corrupted[400 * row_size + 7] ^= 0xFF;
+    fs::write(blobs::blob_path_in(&store, &hash), &corrupted).unwrap();
+    assert_eq!(
+        reader.slice("embed", 10..12).unwrap().data,
+        &data[10 * row_size..12 * row_size]
+    );
+    assert!(matches!(
+        reader.slice("embed", 399..401),
+        Err(VektError::TensorCorruption(_))
+    ));
+
+    assert!(matches!(
+        reader.slice("embed", 500..513),
+        Err(VektError::OutOfRange(_))
+    ));
+    assert!(reader.slice("embed", 5..5).unwrap().data.is_empty());
+    fs::remove_dir_all(store).ok();
+}
@@ -0,0 +1,1509 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod common;
+
+use common::S3Stub;
+use std::time::Duration;
+use vekt_core::config::SettingsLayer;
+use vekt_core::errors::VektError;
+use vekt_core::progress::{CancellationToken, Hooks, Progress, Stage};
+use vekt_core::remote::{Remote, RemoteClient, RemoteGcOptions, RemoteObject};
+use vekt_core::repository::Repository;
+use vekt_core::server::ServerOptions;
+use vekt_core::storage::{RemoteConfig, VektConfig};
+use vekt_core::{ModelArchiver, SafetensorFile};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut dir = env::temp_dir();
+    dir.push("vekt_remote_test");
+    let rnd: u64 = rand::random();
+    dir.push(format!("{}_{}", name, rnd));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn temp_repo(name: &str) -> Repository {
+    Repository::init(temp_dir(name))
+        .unwrap()
+        // Settings come from the repository alone, not the user config or VEKT_* variables
+        .with_settings_layers(SettingsLayer::default(), SettingsLayer::default())
+}
+
+/// An S3 remote with the credentials the stub accepts, so tests never touch the
+/// process environment
+fn stub_remote(url: &str) -> RemoteConfig {
+    RemoteConfig {
+        credential_helper: Some(
+            r#"echo '{"AccessKeyId": "test-access-key", "SecretAccessKey": "test-secret-key"}'"#
+                .to_string(),
+        ),
+        ..RemoteConfig::new(url)
+    }
+}
+
+fn file_url(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+fn write_model(path: &Path) {
+    let mut f = fs::File::create(path).unwrap();
+    let header_json = r#"{"encoder.weight": {"dtype":"U8","shape":[4],"data_offsets":[0,4]}, "decoder.weight": {"dtype":"U8","shape":[4],"data_offsets":[8,12]}}"#;
+    f.write_all(&(header_json.len() as u64).to_le_bytes())
+        .unwrap();
+    f.write_all(header_json.as_bytes()).unwrap();
+    f.write_all(&[1, 2, 3, 4, 0, 0, 0, 0, 5, 6, 7, 8]).unwrap();
+}
+
+#[tokio::test]
+async fn test_file_remote_push_pull_roundtrip() {
+    let source = temp_repo("source");
+    let target = temp_repo("target");
+    let remote_dir = temp_dir("remote");
+
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+
+    let client = RemoteClient::new(&source, &file_url(&remote_dir)).unwrap();
+    client.validate_access().await.unwrap();
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    let manifests = client.remote().list_manifests().await.unwrap();
+    assert_eq!(manifests.len(), 1);
+    assert_eq!(manifests[0].name, "model.vekt.json");
+    assert_eq!(client.remote().list_blobs().await.unwrap().len(), 2);
+
+    // A fresh repository only fetches the blobs the filtered restore needs
+    let client = RemoteClient::new(&target, &file_url(&remote_dir)).unwrap();
+    let pulled = client.fetch_manifest("model.vekt.json").await.unwrap();
+    assert_eq!(pulled.missing_blobs(&target, None).len(), 2);
+    assert_eq!(
+        client
+            .fetch_missing(&pulled, Some("encoder"))
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(pulled.missing_blobs(&target, None).len(), 1);
+    // The source repository is untouched by the target's fetches
+    assert!(manifest.missing_blobs(&source, None).is_empty());
+
+    let restored = target.root().join("encoder.safetensors");
+    pulled.restore(&target, &restored, Some("encoder")).unwrap();
+    let restored_file = SafetensorFile::open(restored.to_str().unwrap()).unwrap();
+    assert_eq!(restored_file.header.len(), 1);
+
+    // Full pull fetches the remainder
+    client.pull("model.vekt.json").await.unwrap();
+    assert!(pulled.missing_blobs(&target, None).is_empty());
+
+    for dir in [source.root(), target.root(), &remote_dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+/// Counts uploaded, downloaded and skipped blobs
+#[derive(Default)]
+struct TransferCounts {
+    done: AtomicUsize,
+    skipped: AtomicUsize,
+}
+
+impl Progress for TransferCounts {
+    fn blob_done(&self, _stage: Stage, _hash: &str, _bytes: u64) {
+        self.done.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn blob_skipped(&self, _stage: Stage, _hash: &str, _bytes: u64) {
+        self.skipped.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_transfer_progress_and_cancellation() {
+    let source = temp_repo("progress_source");
+    let target = temp_repo("progress_target");
+    let remote_dir = temp_dir("progress_remote");
+
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+
+    let counts = Arc::new(TransferCounts::default());
+    let client = RemoteClient::new(&source, &file_url(&remote_dir))
+        .unwrap()
+        .with_hooks(Hooks::default().with_progress(counts.clone()));
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    assert_eq!(counts.done.load(Ordering::SeqCst), 2);
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    assert_eq!(counts.skipped.load(Ordering::SeqCst), 2);
+
+    // A cancelled pull downloads nothing and can simply be run again
+    let cancel = CancellationToken::new();
+    let client = RemoteClient::new(&target, &file_url(&remote_dir))
+        .unwrap()
+        .with_hooks(Hooks::default().with_cancel(cancel.clone()));
+    cancel.cancel();
+    assert!(matches!(
+        client.pull("model.vekt.json").await,
+        Err(VektError::Cancelled(_))
+    ));
+    assert_eq!(fs::read_dir(target.store()).unwrap().count(), 0);
+
+    let pulled = RemoteClient::new(&target, &file_url(&remote_dir))
+        .unwrap()
+        .pull("model.vekt.json")
+        .await
+        .unwrap();
+    assert!(pulled.missing_blobs(&target, None).is_empty());
+
+    for dir in [source.root(), target.root(), &remote_dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[test]
+fn test_file_remote_requires_absolute_path() {
+    let repo = temp_repo("urls");
+    assert!(matches!(
+        RemoteClient::new(&repo, "file://relative/path"),
+        Err(VektError::InvalidRemoteUrl(_))
+    ));
+    assert!(matches!(
+        RemoteClient::new(&repo, "ftp://example.com/store"),
+        Err(VektError::InvalidRemoteUrl(_))
+    ));
+    let _ = fs::remove_dir_all(repo.root());
+}
+
+/// Serves `dir` as static files on an ephemeral localhost port and returns its base URL
+async fn serve_static(dir: PathBuf) -> String {
+    let app = axum::Router::new().fallback_service(tower_http::services::ServeDir::new(dir));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}/vekt", addr)
+}
+
+#[tokio::test]
+async fn test_http_remote_pull_and_resume() {
+    let source = temp_repo("http_source");
+    let target = temp_repo("http_target");
+    let served = temp_dir("http_served");
+    let remote_dir = served.join("vekt");
+
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+    RemoteClient::new(&source, &file_url(&remote_dir))
+        .unwrap()
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    let client = RemoteClient::new(&target, &serve_static(served.clone()).await).unwrap();
+    client.validate_access().await.unwrap();
+    let pulled = client.fetch_manifest("model.vekt.json").await.unwrap();
+
+    // Leave a truncated temp file behind as if a previous download was interrupted
+    let encoder_hash = pulled.tensors["encoder.weight"].hash.clone();
+    let blob_path = vekt_core::blobs::blob_path_in(&target.store(), &encoder_hash);
+    fs::write(blob_path.with_extension("tmp"), [1u8, 2]).unwrap();
+
+    client.pull("model.vekt.json").await.unwrap();
+    assert!(pulled.missing_blobs(&target, None).is_empty());
+    assert_eq!(fs::read(&blob_path).unwrap(), vec![1u8, 2, 3, 4]);
+
+    // Pushing to a plain HTTP remote is rejected up front
+    assert!(matches!(
+        client.push(&pulled, "model.vekt.json", false).await,
+        Err(VektError::ReadOnlyRemote(_))
+    ));
+    // Static servers can't list directories, which callers must be able to tell apart
+    assert!(matches!(
+        client.list_manifests().await,
+        Err(VektError::Unsupported(_))
+    ));
+
+    for dir in [source.root(), target.root(), &served] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn test_s3_compatible_endpoint_with_prefixes() {
+    let (stub, endpoint) = S3Stub::start().await;
+    let source = temp_repo("s3_source");
+    let target = temp_repo("s3_target");
+
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+
+    // Two teams share one bucket: one prefix from the URL, one from the settings
+    let team_a = RemoteConfig {
+        endpoint: Some(endpoint.clone()),
+        region: Some("minio-local".to_string()),
+        ..stub_remote("s3://shared-bucket/ml/team-a")
+    };
+    let team_b = RemoteConfig {
+        endpoint: Some(endpoint.clone()),
+        prefix: Some("ml/team-b/".to_string()),
+        ..stub_remote("s3://shared-bucket")
+    };
+
+    let client_a = RemoteClient::from_config(&source, &team_a).unwrap();
+    client_a.validate_access().await.unwrap();
+    client_a
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    let client_b = RemoteClient::from_config(&source, &team_b).unwrap();
+    assert!(client_b.remote().list_manifests().await.unwrap().is_empty());
+    client_b
+        .push(&manifest, "other.vekt.json", false)
+        .await
+        .unwrap();
+
+    let keys = stub.keys("shared-bucket");
+    assert!(keys.contains(&"ml/team-a/manifests/model.vekt.json".to_string()));
+    assert!(keys.contains(&"ml/team-b/manifests/other.vekt.json".to_string()));
+    assert_eq!(
+        keys.iter()
+            .filter(|k| k.starts_with("ml/team-a/blobs/"))
+            .count(),
+        2
+    );
+    // Path-style addressing puts the bucket in the request path
+    assert!(
+        stub.requests()
+            .iter()
+            .all(|r| r.contains(" /shared-bucket"))
+    );
+
+    let listed = client_a.remote().list_manifests().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name, "model.vekt.json");
+
+    let pulled = RemoteClient::from_config(&target, &team_a)
+        .unwrap()
+        .pull("model.vekt.json")
+        .await
+        .unwrap();
+    assert!(pulled.missing_blobs(&target, None).is_empty());
+
+    for dir in [source.root(), target.root()] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[test]
+fn test_legacy_remote_urls_load_as_remote_config() {
+    let config: VektConfig = serde_json::from_str(
+        r#"{"remotes": {"origin": "s3://bucket", "minio": {"url": "s3://bucket/team", "endpoint": "http://localhost:9000", "path_style": true}}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.remotes["origin"], RemoteConfig::new("s3://bucket"));
+    assert_eq!(
+        config.remotes["minio"].endpoint.as_deref(),
+        Some("http://localhost:9000")
+    );
+    assert_eq!(config.remotes["minio"].path_style, Some(true));
+
+    // Unset settings are omitted so simple remotes stay readable
+    let json = serde_json::to_string(&config.remotes["origin"]).unwrap();
+    assert_eq!(json, r#"{"url":"s3://bucket"}"#);
+}
+
+#[test]
+fn test_s3_url_prefix_validation() {
+    use vekt_core::validation::validate_s3_url;
+
+    assert_eq!(
+        validate_s3_url("s3://bucket/team/models/").unwrap(),
+        ("bucket".to_string(), Some("team/models/".to_string()))
+    );
+    assert_eq!(
+        validate_s3_url("s3://bucket").unwrap(),
+        ("bucket".to_string(), None)
+    );
+    assert!(validate_s3_url("s3://bucket/../escape").is_err());
+    assert!(validate_s3_url("s3://bucket/a//b").is_err());
+}
+
+#[tokio::test]
+async fn test_per_remote_credentials() {
+    let (stub, endpoint) = S3Stub::start().await;
+    let repo = temp_repo("creds_repo");
+    let model_path = repo.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, true)
+        .unwrap();
+
+    let credentials_file = repo.root().join("credentials");
+    fs::write(
+        &credentials_file,
+        "[default]\naws_access_key_id = default-key\naws_secret_access_key = default-secret\n\n[research]\naws_access_key_id = research-key\naws_secret_access_key = research-secret\n",
+    )
+    .unwrap();
+
+    let from_profile = RemoteConfig {
+        endpoint: Some(endpoint.clone()),
+        credentials_file: Some(credentials_file.clone()),
+        profile: Some("research".to_string()),
+        ..RemoteConfig::new("s3://research-bucket")
+    };
+    let from_helper = RemoteConfig {
+        endpoint: Some(endpoint.clone()),
+        credential_helper: Some(
+            r#"echo '{"Version": 1, "AccessKeyId": "helper-key", "SecretAccessKey": "helper-secret"}'"#
+                .to_string(),
+        ),
+        ..RemoteConfig::new("s3://prod-bucket")
+    };
+
+    // Both remotes are used in the same process with different accounts
+    RemoteClient::from_config(&repo, &from_profile)
+        .unwrap()
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    RemoteClient::from_config(&repo, &from_helper)
+        .unwrap()
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    let keys = stub.access_keys();
+    assert!(keys.contains(&"research-key".to_string()));
+    assert!(keys.contains(&"helper-key".to_string()));
+    assert!(!keys.contains(&"default-key".to_string()));
+
+    // Helper failures surface as credential errors
+    let failing = RemoteConfig {
+        endpoint: Some(endpoint),
+        credential_helper: Some("exit 3".to_string()),
+        ..RemoteConfig::new("s3://prod-bucket")
+    };
+    assert!(matches!(
+        RemoteClient::from_config(&repo, &failing),
+        Err(VektError::CredentialError(_))
+    ));
+
+    // Only references to credentials are ever persisted
+    let json = serde_json::to_string(&from_profile).unwrap();
+    assert!(!json.contains("research-secret"));
+
+    let _ = fs::remove_dir_all(repo.root());
+}
+
+#[tokio::test]
+async fn test_multipart_upload_resumes_after_interruption() {
+    let (stub, endpoint) = S3Stub::start().await;
+    let repo = temp_repo("multipart_repo");
+
+    // A single tensor split into four 5 MiB parts and a short fifth one
+    const PART: usize = 5 * 1024 * 1024;
+    let data: Vec<u8> = (0..4 * PART + 4).map(|i| (i % 251) as u8).collect();
+    let model_path = repo.root().join("model.safetensors");
+    {
+        let mut f = fs::File::create(&model_path).unwrap();
+        let header_json = format!(
+            r#"{{"big": {{"dtype":"U8","shape":[{0}],"data_offsets":[0,{0}]}}}}"#,
+            data.len()
+        );
+        f.write_all(&(header_json.len() as u64).to_le_bytes())
+            .unwrap();
+        f.write_all(header_json.as_bytes()).unwrap();
+        f.write_all(&data).unwrap();
+    }
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, true)
+        .unwrap();
+    let hash = manifest.tensors["big"].hash.clone();
+
+    let config = RemoteConfig {
+        endpoint: Some(endpoint),
+        multipart_threshold: Some(PART as u64),
+        multipart_chunk_size: Some(PART as u64),
+        ..stub_remote("s3://models")
+    };
+    let client = RemoteClient::from_config(&repo, &config).unwrap();
+
+    // The third part fails, leaving resume state for the two finished parts
+    stub.fail_part(Some(3));
+    assert!(
+        client
+            .push(&manifest, "model.vekt.json", false)
+            .await
+            .is_err()
+    );
+    let uploads_dir = repo.vekt_dir().join("uploads");
+    assert_eq!(fs::read_dir(&uploads_dir).unwrap().count(), 1);
+    assert_eq!(stub.pending_uploads(), 1);
+
+    stub.fail_part(None);
+    let part_puts_before = stub.requests().len();
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    // Only parts 3-5 were sent again, and the assembled object is intact
+    let resent_parts = stub.requests()[part_puts_before..]
+        .iter()
+        .filter(|r| r.starts_with("PUT") && r.contains("/blobs/"))
+        .count();
+    assert_eq!(resent_parts, 3);
+    assert_eq!(
+        stub.object("models", &format!("blobs/{}", hash)),
+        Some(data)
+    );
+    assert_eq!(stub.pending_uploads(), 0);
+    assert_eq!(fs::read_dir(&uploads_dir).unwrap().count(), 0);
+
+    // Abandoned uploads can be cleaned up along with their local state
+    stub.fail_part(Some(1));
+    let other = RemoteClient::from_config(
+        &repo,
+        &RemoteConfig {
+            prefix: Some("other".to_string()),
+            ..config.clone()
+        },
+    )
+    .unwrap();
+    assert!(
+        other
+            .push(&manifest, "model.vekt.json", false)
+            .await
+            .is_err()
+    );
+    assert_eq!(stub.pending_uploads(), 1);
+    let aborted = other
+        .remote()
+        .abort_incomplete_uploads(std::time::Duration::from_secs(3600))
+        .await
+        .unwrap();
+    assert_eq!(aborted, 1);
+    assert_eq!(stub.pending_uploads(), 0);
+    assert_eq!(fs::read_dir(&uploads_dir).unwrap().count(), 0);
+
+    let _ = fs::remove_dir_all(repo.root());
+}
+
+#[tokio::test]
+async fn test_pull_rejects_and_quarantines_corrupt_blobs() {
+    let source = temp_repo("verify_source");
+    let target = temp_repo("verify_target");
+    let remote_dir = temp_dir("verify_remote");
+
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+    RemoteClient::new(&source, &file_url(&remote_dir))
+        .unwrap()
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    // Bit rot on the remote copy of one blob
+    let hash = manifest.tensors["encoder.weight"].hash.clone();
+    fs::write(remote_dir.join("blobs").join(&hash), [9u8, 9, 9, 9]).unwrap();
+
+    let client = RemoteClient::new(&target, &file_url(&remote_dir)).unwrap();
+    match client.fetch_blobs(std::slice::from_ref(&hash)).await {
+        Err(VektError::HashMismatch { expected, .. }) => assert_eq!(expected, hash),
+        other => panic!("Expected HashMismatch, got {:?}", other),
+    }
+    assert!(!vekt_core::blobs::blob_exists_in(&target.store(), &hash));
+    let quarantine = target.vekt_dir().join("quarantine");
+    assert_eq!(fs::read_dir(&quarantine).unwrap().count(), 1);
+
+    // A corrupt partial download is discarded and the blob fetched again from scratch
+    let decoder_hash = manifest.tensors["decoder.weight"].hash.clone();
+    let decoder_path = vekt_core::blobs::blob_path_in(&target.store(), &decoder_hash);
+    fs::write(decoder_path.with_extension("tmp"), [0u8, 0]).unwrap();
+    assert_eq!(
+        client
+            .fetch_blobs(std::slice::from_ref(&decoder_hash))
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(fs::read(&decoder_path).unwrap(), vec![5u8, 6, 7, 8]);
+    assert_eq!(fs::read_dir(&quarantine).unwrap().count(), 2);
+
+    for dir in [source.root(), target.root(), &remote_dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn test_s3_pull_resumes_partial_download() {
+    let (stub, endpoint) = S3Stub::start().await;
+    let source = temp_repo("resume_source");
+    let target = temp_repo("resume_target");
+
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+    let config = RemoteConfig {
+        endpoint: Some(endpoint),
+        ..stub_remote("s3://models")
+    };
+    RemoteClient::from_config(&source, &config)
+        .unwrap()
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    let client = RemoteClient::from_config(&target, &config).unwrap();
+    let hash = manifest.tensors["encoder.weight"].hash.clone();
+    let blob_path = vekt_core::blobs::blob_path_in(&target.store(), &hash);
+    fs::write(blob_path.with_extension("tmp"), [1u8, 2, 3]).unwrap();
+
+    client
+        .fetch_blobs(std::slice::from_ref(&hash))
+        .await
+        .unwrap();
+    assert_eq!(stub.ranges(), vec![3]);
+    assert_eq!(fs::read(&blob_path).unwrap(), vec![1u8, 2, 3, 4]);
+
+    for dir in [source.root(), target.root()] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn test_noop_push_uses_batch_checks_and_cache() {
+    let (stub, endpoint) = S3Stub::start().await;
+    let repo = temp_repo("batch_repo");
+
+    // Enough distinct tensors that existence is checked by listing, not per blob
+    let model_path = repo.root().join("model.safetensors");
+    {
+        let count = 40;
+        let header: Vec<String> = (0..count)
+            .map(|i| {
+                format!(
+                    r#""t{}": {{"dtype":"U8","shape":[8],"data_offsets":[{},{}]}}"#,
+                    i,
+                    i * 8,
+                    i * 8 + 8
+                )
+            })
+            .collect();
+        let header_json = format!("{{{}}}", header.join(","));
+        let mut f = fs::File::create(&model_path).unwrap();
+        f.write_all(&(header_json.len() as u64).to_le_bytes())
+            .unwrap();
+        f.write_all(header_json.as_bytes()).unwrap();
+        for i in 0..count {
+            f.write_all(&[i as u8; 8]).unwrap();
+        }
+    }
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, true)
+        .unwrap();
+
+    let client = RemoteClient::from_config(
+        &repo,
+        &RemoteConfig {
+            endpoint: Some(endpoint),
+            ..stub_remote("s3://models")
+        },
+    )
+    .unwrap();
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    let heads = |requests: &[String]| {
+        requests
+            .iter()
+            .filter(|r| r.starts_with("HEAD") && r.contains("/blobs/"))
+            .count()
+    };
+    assert_eq!(heads(&stub.requests()), 0);
+    assert_eq!(stub.keys("models").len(), 41);
+
+    // Nothing changed: no blob requests reach the remote
+    let before = stub.requests().len();
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    let noop_requests = &stub.requests()[before..];
+    assert!(
+        noop_requests.iter().all(|r| !r.contains("/blobs/")),
+        "{:?}",
+        noop_requests
+    );
+
+    // Without the cache, one listing still replaces 40 HEAD requests
+    client.clear_known_blobs().unwrap();
+    let before = stub.requests().len();
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    let refreshed = &stub.requests()[before..];
+    assert_eq!(heads(refreshed), 0);
+    assert!(
+        refreshed
+            .iter()
+            .all(|r| !r.starts_with("PUT") || r.contains("/manifests/"))
+    );
+
+    let _ = fs::remove_dir_all(repo.root());
+}
+
+#[tokio::test]
+async fn test_pull_downloads_tied_tensors_once() {
+    let (stub, endpoint) = S3Stub::start().await;
+    let source = temp_repo("tied_source");
+    let target = temp_repo("tied_target");
+
+    // Tied embeddings: two tensors with the same bytes share one blob
+    let model_path = source.root().join("model.safetensors");
+    {
+        let header_json = r#"{"embed.weight": {"dtype":"U8","shape":[8],"data_offsets":[0,8]}, "lm_head.weight": {"dtype":"U8","shape":[8],"data_offsets":[8,16]}}"#;
+        let mut f = fs::File::create(&model_path).unwrap();
+        f.write_all(&(header_json.len() as u64).to_le_bytes())
+            .unwrap();
+        f.write_all(header_json.as_bytes()).unwrap();
+        f.write_all(&[7u8; 16]).unwrap();
+    }
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+    let hash = manifest.tensors["embed.weight"].hash.clone();
+    assert_eq!(manifest.tensors["lm_head.weight"].hash, hash);
+
+    let remote = RemoteConfig {
+        endpoint: Some(endpoint),
+        ..stub_remote("s3://models")
+    };
+    RemoteClient::from_config(&source, &remote)
+        .unwrap()
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    let client = RemoteClient::from_config(&target, &remote).unwrap();
+    let before = stub.requests().len();
+    let pulled = client.pull("model.vekt.json").await.unwrap();
+    assert!(pulled.missing_blobs(&target, None).is_empty());
+    let downloads = stub.requests()[before..]
+        .iter()
+        .filter(|r| r.starts_with("GET") && r.contains(&hash))
+        .count();
+    assert_eq!(downloads, 1);
+
+    for dir in [source.root(), target.root()] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_manifest_not_present_locally() {
+    let source = temp_repo("fetch_source");
+    let target = temp_repo("fetch_target");
+    let remote_dir = temp_dir("fetch_remote");
+
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+    let client = RemoteClient::new(&source, &file_url(&remote_dir)).unwrap();
+    client.push(&manifest, "b.vekt.json", false).await.unwrap();
+    client.push(&manifest, "a.vekt.json", false).await.unwrap();
+
+    let names: Vec<String> = client
+        .list_manifests()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.name)
+        .collect();
+    assert_eq!(names, ["a.vekt.json", "b.vekt.json"]);
+
+    let client = RemoteClient::new(&target, &file_url(&remote_dir)).unwrap();
+    let fetched = client
+        .fetch("a.vekt.json", target.root(), false)
+        .await
+        .unwrap();
+    assert!(target.root().join("a.vekt.json").exists());
+    assert_eq!(fetched.missing_blobs(&target, None).len(), 2);
+
+    client
+        .fetch("b.vekt.json", target.root(), true)
+        .await
+        .unwrap();
+    assert!(fetched.missing_blobs(&target, None).is_empty());
+
+    assert!(matches!(
+        client
+            .fetch("../escape.vekt.json", target.root(), false)
+            .await,
+        Err(VektError::PathTraversal(_))
+    ));
+
+    for dir in [source.root(), target.root(), &remote_dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn test_push_detects_concurrent_manifest_changes() {
+    let alice = temp_repo("conflict_alice");
+    let bob = temp_repo("conflict_bob");
+    let remote_dir = temp_dir("conflict_remote");
+    let alice_client = RemoteClient::new(&alice, &file_url(&remote_dir)).unwrap();
+    let bob_client = RemoteClient::new(&bob, &file_url(&remote_dir)).unwrap();
+
+    let model_path = alice.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&alice, true)
+        .unwrap();
+    alice_client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    // Bob starts from Alice's version
+    let mut bobs = bob_client.pull("model.vekt.json").await.unwrap();
+    bobs.tensors.remove("decoder.weight");
+
+    // Alice pushes a new version on top of her own
+    let mut alices = manifest;
+    alices.tensors.remove("encoder.weight");
+    alice_client
+        .push(&alices, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    // Bob's push would silently discard Alice's change
+    assert!(matches!(
+        bob_client.push(&bobs, "model.vekt.json", false).await,
+        Err(VektError::ConflictDetected(_))
+    ));
+    // A name nobody has pushed yet is not a conflict
+    assert!(matches!(
+        bob_client.push(&bobs, "other.vekt.json", false).await,
+        Ok(())
+    ));
+    bob_client
+        .push(&bobs, "model.vekt.json", true)
+        .await
+        .unwrap();
+
+    // Now Alice is the one out of date, until she pulls
+    assert!(matches!(
+        alice_client.push(&alices, "model.vekt.json", false).await,
+        Err(VektError::ConflictDetected(_))
+    ));
+    alice_client
+        .fetch_manifest("model.vekt.json")
+        .await
+        .unwrap();
+    alice_client
+        .push(&alices, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    for dir in [alice.root(), bob.root(), &remote_dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn test_transient_s3_errors_are_retried() {
+    let (stub, endpoint) = S3Stub::start().await;
+    let repo = temp_repo("retry_repo");
+    let model_path = repo.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, true)
+        .unwrap();
+
+    let config = RemoteConfig {
+        endpoint: Some(endpoint),
+        concurrency: Some(1),
+        ..stub_remote("s3://models")
+    };
+
+    // Without retries a single 503 fails the push
+    let no_retries = RemoteClient::from_config(
+        &repo,
+        &RemoteConfig {
+            retries: Some(0),
+            ..config.clone()
+        },
+    )
+    .unwrap();
+    stub.fail_next(1);
+    assert!(matches!(
+        no_retries.push(&manifest, "model.vekt.json", false).await,
+        Err(VektError::RemoteUnavailable(_))
+    ));
+
+    let client = RemoteClient::from_config(&repo, &config).unwrap();
+    assert_eq!(client.options().retries, 3);
+    stub.fail_next(3);
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    assert_eq!(stub.keys("models").len(), 3);
+
+    // Permanent failures are reported without retrying
+    let before = stub.requests().len();
+    assert!(matches!(
+        client.fetch_manifest("missing.vekt.json").await,
+        Err(VektError::RemoteError(_))
+    ));
+    assert_eq!(stub.requests().len(), before + 1);
+
+    let _ = fs::remove_dir_all(repo.root());
+}
+
+#[tokio::test]
+async fn test_push_all_uploads_shared_blobs_once() {
+    let (stub, endpoint) = S3Stub::start().await;
+    let repo = temp_repo("push_all_repo");
+    let root = repo.root();
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("b")).unwrap();
+    for dir in ["a", "b"] {
+        write_model(&root.join(dir).join("model.safetensors"));
+    }
+    let manifests: Vec<_> = ["a", "b"]
+        .iter()
+        .map(|dir| {
+            let path = root.join(dir).join("model.safetensors");
+            let manifest = SafetensorFile::open(path.to_str().unwrap())
+                .unwrap()
+                .process(&repo, true)
+                .unwrap();
+            fs::write(
+                root.join(dir).join("model.vekt.json"),
+                serde_json::to_vec(&manifest).unwrap(),
+            )
+            .unwrap();
+            manifest
+        })
+        .collect();
+    assert_eq!(
+        repo.manifests().unwrap(),
+        [
+            root.join("a").join("model.vekt.json"),
+            root.join("b").join("model.vekt.json")
+        ]
+    );
+
+    let client = RemoteClient::from_config(
+        &repo,
+        &RemoteConfig {
+            endpoint: Some(endpoint),
+            ..stub_remote("s3://models")
+        },
+    )
+    .unwrap();
+    let results = client
+        .push_all(
+            &[
+                ("a/model.vekt.json", &manifests[0]),
+                ("b/model.vekt.json", &manifests[1]),
+            ],
+            false,
+        )
+        .await
+        .unwrap();
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+    let blob_puts = stub
+        .requests()
+        .iter()
+        .filter(|r| r.starts_with("PUT") && r.contains("/blobs/"))
+        .count();
+    assert_eq!(blob_puts, 2);
+
+    let names = [
+        "a/model.vekt.json",
+        "b/model.vekt.json",
+        "c/missing.vekt.json",
+    ];
+    let pulled = client.pull_all(&names, true).await.unwrap();
+    assert!(pulled[0].1.is_ok() && pulled[1].1.is_ok());
+    assert!(pulled[2].1.is_err());
+
+    let _ = fs::remove_dir_all(root);
+}
+
+#[tokio::test]
+async fn test_remote_gc_deletes_unreferenced_blobs() {
+    let repo = temp_repo("gc_repo");
+    let other = temp_repo("gc_other");
+    let remote_dir = temp_dir("gc_remote");
+    let model_path = repo.root().join("model.safetensors");
+    write_model(&model_path);
+    let mut manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, true)
+        .unwrap();
+    let client = RemoteClient::new(&repo, &file_url(&remote_dir)).unwrap();
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    // Another clone caches the blobs as present on the remote
+    let other_client = RemoteClient::new(&other, &file_url(&remote_dir)).unwrap();
+    let pulled = other_client.pull("model.vekt.json").await.unwrap();
+    other_client
+        .push(&pulled, "copy.vekt.json", false)
+        .await
+        .unwrap();
+    other_client
+        .remote()
+        .delete_manifest("copy.vekt.json")
+        .await
+        .unwrap();
+
+    let dropped = manifest.tensors.remove("decoder.weight").unwrap().hash;
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+
+    // Fresh blobs are protected by the grace period
+    let stats = client.gc(&RemoteGcOptions::default()).await.unwrap();
+    assert_eq!((stats.deleted, stats.kept, stats.recent), (0, 1, 1));
+
+    let immediate = RemoteGcOptions {
+        grace_period: Duration::ZERO,
+        ..Default::default()
+    };
+    let dry_run = client
+        .gc(&RemoteGcOptions {
+            dry_run: true,
+            ..immediate.clone()
+        })
+        .await
+        .unwrap();
+    assert_eq!((dry_run.deleted, dry_run.deleted_bytes), (1, 4));
+    assert!(client.remote().has_blob(&dropped).await.unwrap());
+
+    let retained = client
+        .gc(&RemoteGcOptions {
+            retain: [dropped.clone()].into(),
+            ..immediate.clone()
+        })
+        .await
+        .unwrap();
+    assert_eq!(retained.deleted, 0);
+
+    client.gc(&immediate).await.unwrap();
+    assert!(!client.remote().has_blob(&dropped).await.unwrap());
+
+    // The other clone's cache is invalidated, so its push re-uploads the deleted blob
+    other_client
+        .push(&pulled, "copy.vekt.json", false)
+        .await
+        .unwrap();
+    assert!(client.remote().has_blob(&dropped).await.unwrap());
+
+    for dir in [repo.root(), other.root(), &remote_dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+/// Delegates to a file remote, landing a manifest the first time blobs are listed, as
+/// a push that found its blobs already present would while a GC scans the remote
+struct ManifestDuringGc {
+    inner: RemoteClient,
+    pending: std::sync::Mutex<Option<(String, Vec<u8>)>>,
+}
+
+#[async_trait::async_trait]
+impl Remote for ManifestDuringGc {
+    fn location(&self) -> String {
+        self.inner.remote().location()
+    }
+    async fn validate_access(&self) -> vekt_core::errors::Result<()> {
+        self.inner.remote().validate_access().await
+    }
+    async fn list_blobs(&self) -> vekt_core::errors::Result<Vec<RemoteObject>> {
+        let pending = self.pending.lock().unwrap().take();
+        if let Some((name, data)) = pending {
+            self.inner.remote().put_manifest(&name, &data).await?;
+        }
+        self.inner.remote().list_blobs().await
+    }
+    async fn has_blob(&self, hash: &str) -> vekt_core::errors::Result<bool> {
+        self.inner.remote().has_blob(hash).await
+    }
+    async fn get_blob(&self, hash: &str, dest: &Path) -> vekt_core::errors::Result<()> {
+        self.inner.remote().get_blob(hash, dest).await
+    }
+    async fn put_blob(&self, hash: &str, src: &Path) -> vekt_core::errors::Result<()> {
+        self.inner.remote().put_blob(hash, src).await
+    }
+    async fn delete_blob(&self, hash: &str) -> vekt_core::errors::Result<()> {
+        self.inner.remote().delete_blob(hash).await
+    }
+    async fn list_manifests(&self) -> vekt_core::errors::Result<Vec<RemoteObject>> {
+        self.inner.remote().list_manifests().await
+    }
+    async fn has_manifest(&self, name: &str) -> vekt_core::errors::Result<bool> {
+        self.inner.remote().has_manifest(name).await
+    }
+    async fn get_manifest(&self, name: &str) -> vekt_core::errors::Result<Vec<u8>> {
+        self.inner.remote().get_manifest(name).await
+    }
+    async fn put_manifest(&self, name: &str, data: &[u8]) -> vekt_core::errors::Result<()> {
+        self.inner.remote().put_manifest(name, data).await
+    }
+    async fn delete_manifest(&self, name: &str) -> vekt_core::errors::Result<()> {
+        self.inner.remote().delete_manifest(name).await
+    }
+    async fn get_meta(&self, name: &str) -> vekt_core::errors::Result<Option<Vec<u8>>> {
+        self.inner.remote().get_meta(name).await
+    }
+    async fn put_meta(&self, name: &str, data: &[u8]) -> vekt_core::errors::Result<()> {
+        self.inner.remote().put_meta(name, data).await
+    }
+}
+
+#[tokio::test]
+async fn test_remote_gc_keeps_blobs_of_manifests_pushed_during_the_scan() {
+    let repo = temp_repo("gc_race_repo");
+    let remote_dir = temp_dir("gc_race_remote");
+    let model_path = repo.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, true)
+        .unwrap();
+    let client = RemoteClient::new(&repo, &file_url(&remote_dir)).unwrap();
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    client
+        .remote()
+        .delete_manifest("model.vekt.json")
+        .await
+        .unwrap();
+
+    // The blobs are unreferenced when the GC reads the manifests, referenced before it deletes
+    let racing = RemoteClient::from_remote(
+        &repo,
+        Box::new(ManifestDuringGc {
+            inner: RemoteClient::new(&repo, &file_url(&remote_dir)).unwrap(),
+            pending: std::sync::Mutex::new(Some((
+                "model.vekt.json".to_string(),
+                serde_json::to_vec(&manifest).unwrap(),
+            ))),
+        }),
+    );
+    let stats = racing
+        .gc(&RemoteGcOptions {
+            grace_period: Duration::ZERO,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!((stats.deleted, stats.kept), (0, 2));
+    for tensor in manifest.tensors.values() {
+        assert!(client.remote().has_blob(&tensor.hash).await.unwrap());
+    }
+
+    for dir in [repo.root(), &remote_dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn test_group_reads_fall_back_to_later_remotes() {
+    let source = temp_repo("group_source");
+    let target = temp_repo("group_target");
+    let primary_dir = temp_dir("group_primary");
+    let mirror_dir = temp_dir("group_mirror");
+
+    let mut config = VektConfig::default();
+    config.add_remote(
+        "down".into(),
+        RemoteConfig::new(file_url(&primary_dir.join("missing"))),
+    );
+    config.add_remote("primary".into(), RemoteConfig::new(file_url(&primary_dir)));
+    config.add_remote("mirror".into(), RemoteConfig::new(file_url(&mirror_dir)));
+    assert!(matches!(
+        config.add_group("all".into(), vec!["primary".into(), "nowhere".into()]),
+        Err(VektError::RemoteNotFound(_))
+    ));
+    config
+        .add_group(
+            "all".into(),
+            vec!["down".into(), "primary".into(), "mirror".into()],
+        )
+        .unwrap();
+    let remotes = config.resolve_remotes("all").unwrap();
+    assert_eq!(remotes.len(), 3);
+    assert_eq!(config.resolve_remotes("mirror").unwrap()[0].0, "mirror");
+
+    // Replicate to every reachable member
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+    for (_, remote) in &remotes[1..] {
+        let client = RemoteClient::from_config(&source, remote).unwrap();
+        client
+            .push(&manifest, "model.vekt.json", false)
+            .await
+            .unwrap();
+    }
+
+    // The primary loses a blob; reads skip the unreachable remote and fill the gap from the mirror
+    let lost = manifest.tensors["decoder.weight"].hash.clone();
+    RemoteClient::from_config(&source, remotes[1].1)
+        .unwrap()
+        .remote()
+        .delete_blob(&lost)
+        .await
+        .unwrap();
+
+    target.save_config(&config).unwrap();
+    let group = target.remote("all").unwrap();
+    assert!(group.remote().is_read_only());
+    let pulled = group.pull("model.vekt.json").await.unwrap();
+    assert!(pulled.missing_blobs(&target, None).is_empty());
+
+    for dir in [source.root(), target.root(), &primary_dir, &mirror_dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+/// Starts `vekt serve` for `repo` and returns its `vekt://` URL
+async fn start_server(repo: &Repository, options: ServerOptions) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(vekt_core::server::serve(listener, repo.clone(), options));
+    format!("vekt://{}", addr)
+}
+
+#[tokio::test]
+async fn test_vekt_server_roundtrip() {
+    let server = temp_repo("serve_root");
+    let source = temp_repo("serve_source");
+    let target = temp_repo("serve_target");
+    let url = start_server(&server, ServerOptions::default()).await;
+
+    let model_path = source.root().join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&source, true)
+        .unwrap();
+
+    let client = RemoteClient::new(&source, &url).unwrap();
+    client.validate_access().await.unwrap();
+    client
+        .push(&manifest, "models/model.vekt.json", false)
+        .await
+        .unwrap();
+
+    // Blobs land in the served store, manifests in its working tree
+    let hashes: Vec<String> = manifest.tensors.values().map(|t| t.hash.clone()).collect();
+    for hash in &hashes {
+        assert!(server.store().join(hash).exists());
+    }
+    assert!(server.root().join("models/model.vekt.json").exists());
+
+    let mut queried = hashes.clone();
+    queried.push("0".repeat(64));
+    let present = client.remote().existing_blobs(&queried).await.unwrap();
+    assert_eq!(present.len(), 2);
+    assert_eq!(client.remote().list_blobs().await.unwrap().len(), 2);
+    let names: Vec<String> = client
+        .list_manifests()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.name)
+        .collect();
+    assert_eq!(names, ["models/model.vekt.json"]);
+
+    let client = RemoteClient::new(&target, &url).unwrap();
+    let pulled = client.pull("models/model.vekt.json").await.unwrap();
+    assert!(pulled.missing_blobs(&target, None).is_empty());
+
+    // Blobs the served repository references can't be deleted through it
+    assert!(matches!(
+        client.remote().delete_blob(&hashes[0]).await,
+        Err(VektError::ConflictDetected(_))
+    ));
+    assert!(server.store().join(&hashes[0]).exists());
+
+    // Uploads whose content doesn't match the hash are refused
+    let bad = target.root().join("bad");
+    fs::write(&bad, b"not what the hash says").unwrap();
+    assert!(client.remote().put_blob(&hashes[0], &bad).await.is_err());
+    assert!(
+        client
+            .remote()
+            .put_manifest("../escape.vekt.json", b"{}")
+            .await
+            .is_err()
+    );
+
+    // A read-only server still serves pulls but rejects pushes
+    let read_only = start_server(&server, ServerOptions { read_only: true }).await;
+    let client = RemoteClient::new(&target, &read_only).unwrap();
+    client.pull("models/model.vekt.json").await.unwrap();
+    assert!(matches!(
+        client.remote().delete_blob(&hashes[0]).await,
+        Err(VektError::ReadOnlyRemote(_))
+    ));
+
+    for dir in [server.root(), source.root(), target.root()] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[tokio::test]
+async fn test_server_writes_wait_for_the_repository_lock() {
+    let server = temp_repo("serve_locked");
+    let url = start_server(&server, ServerOptions::default()).await;
+    let client = RemoteClient::new(&server, &url).unwrap();
+
+    let blob = server.root().join("upload");
+    fs::write(&blob, b"admitted only once gc is done").unwrap();
+    let hash = vekt_core::blobs::compute_file_hash(&blob).unwrap();
+
+    // A local command such as `vekt gc` holds the lock while the upload arrives
+    let lock = server.lock().unwrap();
+    let upload = tokio::spawn({
+        let (client, hash, blob) = (client, hash.clone(), blob.clone());
+        async move { client.remote().put_blob(&hash, &blob).await }
+    });
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!server.store().join(&hash).exists());
+
+    drop(lock);
+    upload.await.unwrap().unwrap();
+    assert!(server.store().join(&hash).exists());
+    assert!(!server.root().join(".vekt/lock").exists());
+
+    let _ = fs::remove_dir_all(server.root());
+}
+
+#[tokio::test]
+async fn test_server_presents_manifests_as_safetensors_files() {
+    let repo = temp_repo("files_root");
+    let root = repo.root();
+    // Served files follow the repository's alignment, as `restore` does
+    let mut config = repo.config().unwrap();
+    config.settings.set("restore_alignment", "64").unwrap();
+    repo.save_config(&config).unwrap();
+
+    let model_path = root.join("model.safetensors");
+    write_model(&model_path);
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .process(&repo, true)
+        .unwrap();
+    fs::write(
+        root.join("model.vekt.json"),
+        serde_json::to_vec(&manifest).unwrap(),
+    )
+    .unwrap();
+    let expected = root.join("expected.safetensors");
+    manifest.restore(&repo, &expected, None).unwrap();
+    let expected = fs::read(expected).unwrap();
+    let encoder_only = root.join("encoder.safetensors");
+    manifest
+        .restore(&repo, &encoder_only, Some("encoder"))
+        .unwrap();
+
+    let url = start_server(&repo, ServerOptions::default()).await;
+    let file_url = format!(
+        "{}/files/model.safetensors",
+        url.replace("vekt://", "http://")
+    );
+    let http = reqwest::Client::new();
+    let get = |range: Option<&str>| {
+        let mut request = http.get(&file_url);
+        if let Some(range) = range {
+            request = request.header("Range", range);
+        }
+        request.send()
+    };
+
+    let response = get(None).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["accept-ranges"], "bytes");
+    assert_eq!(response.bytes().await.unwrap(), expected);
+
+    // A loader reads the header length, then a single tensor
+    let response = get(Some("bytes=0-7")).await.unwrap();
+    assert_eq!(response.status(), 206);
+    assert_eq!(
+        response.headers()["content-range"],
+        format!("bytes 0-7/{}", expected.len())
+    );
+    assert_eq!(response.bytes().await.unwrap(), expected[..8]);
+    let response = get(Some("bytes=-4")).await.unwrap();
+    assert_eq!(
+        response.bytes().await.unwrap(),
+        expected[expected.len() - 4..]
+    );
+
+    let response = get(Some(&format!("bytes={}-", expected.len())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 416);
+
+    let filtered = http
+        .get(format!("{}?layers=encoder", file_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        filtered.bytes().await.unwrap(),
+        fs::read(&encoder_only).unwrap()
+    );
+
+    let listing = http
+        .get(file_url.replace("/model.safetensors", ""))
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    let listing: serde_json::Value = serde_json::from_slice(&listing).unwrap();
+    assert_eq!(listing[0]["name"], "model.safetensors");
+    assert_eq!(listing[0]["size"], expected.len());
+
+    // Blob bytes are checked against their hash, so a corrupted blob aborts the response
+    let decoder_blob = repo.store().join(&manifest.tensors["decoder.weight"].hash);
+    let mut corrupted = fs::read(&decoder_blob).unwrap();
+    corrupted[0] ^= 0xFF;
+    fs::write(&decoder_blob, corrupted).unwrap();
+    let corrupted_read = match get(Some("bytes=-4")).await {
+        Ok(response) => response.bytes().await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+    assert!(corrupted_read.is_err());
+    assert_eq!(
+        get(Some("bytes=0-7")).await.unwrap().bytes().await.unwrap(),
+        expected[..8]
+    );
+
+    // Only ranges touching a missing blob fail
+    fs::remove_file(&decoder_blob).unwrap();
+    assert_eq!(get(Some("bytes=0-7")).await.unwrap().status(), 206);
+    assert_eq!(get(Some("bytes=-4")).await.unwrap().status(), 404);
+
+    let _ = fs::remove_dir_all(root);
+}
+
+#[tokio::test]
+async fn test_compressed_blobs_leave_the_repository_decoded() {
+    let source = temp_repo("compressed_source");
+    let remote_dir = temp_dir("compressed_remote");
+
+    let model_path = source.root().join("model.safetensors");
+    {
+        let mut f = fs::File::create(&model_path).unwrap();
+        let header_json = r#"{"zeros": {"dtype":"U8","shape":[4096],"data_offsets":[0,4096]}}"#;
+        f.write_all(&(header_json.len() as u64).to_le_bytes())
+            .unwrap();
+        f.write_all(header_json.as_bytes()).unwrap();
+        f.write_all(&[0; 4096]).unwrap();
+    }
+    let manifest = SafetensorFile::open(model_path.to_str().unwrap())
+        .unwrap()
+        .with_compression(true)
+        .process(&source, true)
+        .unwrap();
+    let hash = manifest.tensors["zeros"].hash.clone();
+    let local = source.store().join(&hash);
+    assert!(fs::metadata(&local).unwrap().len() < 4096);
+
+    // Remotes hold the blob under its hash, and the local copy stays compressed
+    let client = RemoteClient::new(&source, &file_url(&remote_dir)).unwrap();
+    client
+        .push(&manifest, "model.vekt.json", false)
+        .await
+        .unwrap();
+    let pushed = remote_dir.join("blobs").join(&hash);
+    assert_eq!(vekt_core::blobs::compute_file_hash(&pushed).unwrap(), hash);
+    assert!(fs::metadata(&local).unwrap().len() < 4096);
+
+    // `vekt serve` hands out the decoded bytes
+    let url = start_server(&source, ServerOptions::default()).await;
+    let response = reqwest::get(format!(
+        "{}/blobs/{}",
+        url.replace("vekt://", "http://"),
+        hash
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.bytes().await.unwrap().as_ref(), &[0; 4096][..]);
+
+    let _ = fs::remove_dir_all(source.root());
+    let _ = fs::remove_dir_all(&remote_dir);
+}
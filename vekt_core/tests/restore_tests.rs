@@ -4,21 +4,37 @@ use std::fs::File;
 use std::io::{Read, Write};
 
 use vekt_core::blobs;
+use vekt_core::config::SettingsLayer;
+use vekt_core::repository::Repository;
 use vekt_core::storage::{ManifestTensor, VektManifest};
 use vekt_core::{ModelArchiver, SafetensorFile};
 
+/// A fresh repository in a temp directory, so tests never share a store
+fn temp_repo(name: &str) -> Repository {
+    let root = std::env::temp_dir().join("vekt_restore_test").join(format!(
+        "{}_{}",
+        name,
+        rand::random::<u64>()
+    ));
+    Repository::init(root)
+        .unwrap()
+        // Settings come from the repository alone, not the user config or VEKT_* variables
+        .with_settings_layers(SettingsLayer::default(), SettingsLayer::default())
+}
+
 // Helper to create a dummy blob
-fn create_blob(data: &[u8]) -> String {
-    blobs::write_blob_atomic(data).unwrap()
+fn create_blob(repo: &Repository, data: &[u8]) -> String {
+    blobs::write_blob_atomic_in(&repo.store(), data).unwrap()
 }
 
 #[test]
 fn test_full_cycle_restore() -> Result<(), Box<dyn std::error::Error>> {
-    let original_path = "test_cycle_original.safetensors";
-    let restored_path = "test_cycle_restored.safetensors";
+    let repo = temp_repo("cycle");
+    let original_path = repo.root().join("original.safetensors");
+    let restored_path = repo.root().join("restored.safetensors");
 
     {
-        let mut file = File::create(original_path)?;
+        let mut file = File::create(&original_path)?;
         let header_json = r#"{"test_tensor": {"dtype":"F32", "shape":[1], "data_offsets":[0, 4]}}"#;
         let header_len = header_json.len() as u64;
         file.write_all(&header_len.to_le_bytes())?;
@@ -26,30 +42,31 @@ fn test_full_cycle_restore() -> Result<(), Box<dyn std::error::Error>> {
         file.write_all(&[1u8, 2u8, 3u8, 4u8])?; // The data
     }
 
-    let file = SafetensorFile::open(original_path)?;
-    let manifest = file.process(true).unwrap(); // true = save blobs
+    let file = SafetensorFile::open(original_path.to_str().unwrap())?;
+    let manifest = file.process(&repo, true).unwrap(); // true = save blobs
 
-    std::fs::remove_file(original_path)?;
+    std::fs::remove_file(&original_path)?;
 
-    manifest.restore(std::path::Path::new(restored_path), None)?;
+    manifest.restore(&repo, &restored_path, None)?;
 
-    let mut f = File::open(restored_path)?;
+    let mut f = File::open(&restored_path)?;
     let mut buffer = Vec::new();
     f.read_to_end(&mut buffer)?;
 
     // Size = 8 (len) + 79 (header) + 4 (data) = 91 bytes
     assert!(buffer.len() > 70);
 
-    std::fs::remove_file(restored_path)?;
+    std::fs::remove_dir_all(repo.root())?;
 
     Ok(())
 }
 
 #[test]
 fn test_shared_weights_deduplication() {
+    let repo = temp_repo("shared");
     // Unique data to avoid conflict
     let data = vec![11u8, 22u8, 33u8, 44u8];
-    let hash = create_blob(&data);
+    let hash = create_blob(&repo, &data);
 
     let mut tensors = BTreeMap::new();
 
@@ -81,10 +98,10 @@ fn test_shared_weights_deduplication() {
         total_size: 4,
     };
 
-    let output_path = std::path::Path::new("test_shared.safetensors");
-    manifest.restore(output_path, None).unwrap();
+    let output_path = repo.root().join("test_shared.safetensors");
+    manifest.restore(&repo, &output_path, None).unwrap();
 
-    let mut file = File::open(output_path).unwrap();
+    let mut file = File::open(&output_path).unwrap();
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer).unwrap();
 
@@ -101,16 +118,17 @@ fn test_shared_weights_deduplication() {
         "Offsets for shared weights must be identical"
     );
 
-    std::fs::remove_file(output_path).unwrap();
-    std::fs::remove_file(blobs::get_blob_path(&hash)).ok();
+    std::fs::remove_dir_all(repo.root()).ok();
 }
 
 #[test]
+#[allow(clippy::needless_range_loop)]
 fn test_alignment_padding() {
+    let repo = temp_repo("aligned");
     let data_a = vec![0xCC];
     let data_b = vec![0xDD];
-    let hash_a = create_blob(&data_a);
-    let hash_b = create_blob(&data_b);
+    let hash_a = create_blob(&repo, &data_a);
+    let hash_b = create_blob(&repo, &data_b);
 
     let mut tensors = BTreeMap::new();
 
@@ -142,10 +160,10 @@ fn test_alignment_padding() {
         total_size: 2,
     };
 
-    let output_path = std::path::Path::new("test_aligned.safetensors");
-    manifest.restore(output_path, None).unwrap();
+    let output_path = repo.root().join("test_aligned.safetensors");
+    manifest.restore(&repo, &output_path, None).unwrap();
 
-    let mut file = File::open(output_path).unwrap();
+    let mut file = File::open(&output_path).unwrap();
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer).unwrap();
 
@@ -159,15 +177,48 @@ fn test_alignment_padding() {
     }
     assert_eq!(data_section[8], 0xDD);
 
-    std::fs::remove_file(output_path).unwrap();
-    std::fs::remove_file(blobs::get_blob_path(&hash_a)).ok();
-    std::fs::remove_file(blobs::get_blob_path(&hash_b)).ok();
+    std::fs::remove_dir_all(repo.root()).ok();
+}
+
+#[test]
+fn test_restore_alignment_setting() {
+    let repo = temp_repo("alignment_setting");
+    let hash_a = create_blob(&repo, &[0xCC]);
+    let hash_b = create_blob(&repo, &[0xDD]);
+    let tensor = |hash: &str, index| ManifestTensor {
+        shape: vec![1],
+        dtype: "U8".to_string(),
+        hash: hash.to_string(),
+        index,
+        extra: IndexMap::new(),
+    };
+    let manifest = VektManifest {
+        tensors: BTreeMap::from([
+            ("tensor_a".to_string(), tensor(&hash_a, 0)),
+            ("tensor_b".to_string(), tensor(&hash_b, 1)),
+        ]),
+        version: "1.0".to_string(),
+        total_size: 2,
+    };
+
+    let mut config = repo.config().unwrap();
+    config.settings.set("restore_alignment", "64").unwrap();
+    repo.save_config(&config).unwrap();
+
+    let output_path = repo.root().join("aligned_64.safetensors");
+    manifest.restore(&repo, &output_path, None).unwrap();
+    let restored = SafetensorFile::open(output_path.to_str().unwrap()).unwrap();
+    assert_eq!(restored.header["tensor_b"].data_offsets, (64, 65));
+    assert_eq!(restored.mmap[restored.header_len + 8 + 64], 0xDD);
+
+    std::fs::remove_dir_all(repo.root()).ok();
 }
 
 #[test]
 fn test_extra_metadata_preservation() {
+    let repo = temp_repo("meta");
     let data = vec![0xFF];
-    let hash = create_blob(&data);
+    let hash = create_blob(&repo, &data);
 
     let mut extra = IndexMap::new();
     extra.insert("quantization".to_string(), serde_json::json!("int8"));
@@ -191,10 +242,10 @@ fn test_extra_metadata_preservation() {
         total_size: 1,
     };
 
-    let output_path = std::path::Path::new("test_meta.safetensors");
-    manifest.restore(output_path, None).unwrap();
+    let output_path = repo.root().join("test_meta.safetensors");
+    manifest.restore(&repo, &output_path, None).unwrap();
 
-    let mut file = File::open(output_path).unwrap();
+    let mut file = File::open(&output_path).unwrap();
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer).unwrap();
 
@@ -203,6 +254,138 @@ fn test_extra_metadata_preservation() {
 
     assert!(header_str.contains("\"quantization\":\"int8\""));
 
-    std::fs::remove_file(output_path).unwrap();
-    std::fs::remove_file(blobs::get_blob_path(&hash)).ok();
+    std::fs::remove_dir_all(repo.root()).ok();
+}
+
+#[test]
+fn test_missing_blobs_honours_filter() {
+    let repo = temp_repo("missing");
+    let present = vec![0x5Au8, 0x5B, 0x5C, 0x5D];
+    let present_hash = create_blob(&repo, &present);
+    let absent_hash = blobs::compute_blob_hash(b"never written to the store");
+
+    let mut tensors = BTreeMap::new();
+    tensors.insert(
+        "encoder.weight".to_string(),
+        ManifestTensor {
+            shape: vec![4],
+            dtype: "U8".to_string(),
+            hash: present_hash.clone(),
+            index: 0,
+            extra: IndexMap::new(),
+        },
+    );
+    tensors.insert(
+        "decoder.weight".to_string(),
+        ManifestTensor {
+            shape: vec![4],
+            dtype: "U8".to_string(),
+            hash: absent_hash.clone(),
+            index: 1,
+            extra: IndexMap::new(),
+        },
+    );
+    tensors.insert(
+        "decoder.tied".to_string(),
+        ManifestTensor {
+            shape: vec![4],
+            dtype: "U8".to_string(),
+            hash: absent_hash.clone(),
+            index: 2,
+            extra: IndexMap::new(),
+        },
+    );
+
+    let manifest = VektManifest {
+        tensors,
+        version: "1.0".to_string(),
+        total_size: 12,
+    };
+
+    assert!(manifest.missing_blobs(&repo, Some("encoder")).is_empty());
+    assert_eq!(
+        manifest.missing_blobs(&repo, Some("decoder")),
+        vec![absent_hash.clone()]
+    );
+    assert_eq!(manifest.missing_blobs(&repo, None), vec![absent_hash]);
+
+    std::fs::remove_dir_all(repo.root()).ok();
+}
+
+#[test]
+fn test_layout_reads_match_restored_file() {
+    let repo = temp_repo("layout");
+    let first = vec![0x11u8; 6];
+    let second: Vec<u8> = (0u8..10).collect();
+    let first_hash = create_blob(&repo, &first);
+    let second_hash = create_blob(&repo, &second);
+
+    let mut tensors = BTreeMap::new();
+    for (name, data, hash, index) in [
+        ("a.weight", &first, &first_hash, 0),
+        ("b.weight", &second, &second_hash, 1),
+    ] {
+        tensors.insert(
+            name.to_string(),
+            ManifestTensor {
+                shape: vec![data.len()],
+                dtype: "U8".to_string(),
+                hash: hash.clone(),
+                index,
+                extra: IndexMap::new(),
+            },
+        );
+    }
+    let manifest = VektManifest {
+        tensors,
+        version: "1.0".to_string(),
+        total_size: 16,
+    };
+
+    let restored_path = repo.root().join("restored.safetensors");
+    manifest.restore(&repo, &restored_path, None).unwrap();
+    let restored = std::fs::read(&restored_path).unwrap();
+
+    let alignment = repo.settings().unwrap().restore_alignment;
+    let layout = manifest.layout(None, alignment).unwrap();
+    assert_eq!(layout.size, restored.len() as u64);
+
+    let data_start = layout.header.len();
+    for (offset, len) in [
+        (0, restored.len()),
+        (3, 5),
+        (data_start - 2, 4),
+        (data_start + 4, 20),
+        (restored.len() - 1, 8),
+    ] {
+        let mut touched = Vec::new();
+        let mut buf = vec![0xFFu8; len];
+        let read = layout
+            .read_at(offset as u64, &mut buf, |segment, blob_offset, out| {
+                touched.push(segment.tensor.clone());
+                let blob = std::fs::read(blobs::blob_path_in(&repo.store(), &segment.hash))?;
+                let start = blob_offset as usize;
+                out.copy_from_slice(&blob[start..start + out.len()]);
+                Ok(())
+            })
+            .unwrap();
+
+        let expected = &restored[offset..(offset + len).min(restored.len())];
+        assert_eq!(&buf[..read], expected, "window at {}", offset);
+        for segment in &layout.segments {
+            let overlaps = segment.offset < (offset + read) as u64
+                && segment.offset + segment.len > offset as u64;
+            assert_eq!(touched.contains(&segment.tensor), overlaps);
+        }
+    }
+
+    let mut buf = [0u8; 4];
+    assert_eq!(
+        layout
+            .read_at(layout.size, &mut buf, |_, _, _| unreachable!())
+            .unwrap(),
+        0
+    );
+
+    std::fs::remove_dir_all(repo.root()).ok();
 }
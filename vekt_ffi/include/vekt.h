@@ -0,0 +1,212 @@
+/* Generated by cbindgen from vekt_ffi/src/lib.rs. Do not edit. */
+
+#ifndef VEKT_H
+#define VEKT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of every fallible call. Values are part of the ABI and never renumbered.
+ */
+typedef enum VektStatus {
+  VEKT_STATUS_OK = 0,
+  /**
+   * A required pointer was NULL or a string was not valid UTF-8
+   */
+  VEKT_STATUS_INVALID_ARGUMENT = 1,
+  VEKT_STATUS_IO = 2,
+  VEKT_STATUS_REPO_NOT_FOUND = 3,
+  /**
+   * Another vekt operation holds the repository lock
+   */
+  VEKT_STATUS_LOCKED = 4,
+  VEKT_STATUS_BLOB_NOT_FOUND = 5,
+  /**
+   * Stored data does not match its hash
+   */
+  VEKT_STATUS_INTEGRITY = 6,
+  VEKT_STATUS_INVALID_MANIFEST = 7,
+  VEKT_STATUS_INVALID_SAFETENSOR = 8,
+  VEKT_STATUS_TENSOR_NOT_FOUND = 9,
+  /**
+   * The caller's buffer is too small; the required size is reported
+   */
+  VEKT_STATUS_BUFFER_TOO_SMALL = 10,
+  VEKT_STATUS_OUT_OF_RANGE = 11,
+  VEKT_STATUS_REMOTE = 12,
+  VEKT_STATUS_CANCELLED = 13,
+  /**
+   * A config file or `VEKT_*` setting is invalid
+   */
+  VEKT_STATUS_INVALID_CONFIG = 14,
+  /**
+   * A bug in vekt; the message has details
+   */
+  VEKT_STATUS_INTERNAL = 99,
+} VektStatus;
+
+/**
+ * A loaded manifest, reading tensors from its repository's blob store
+ */
+typedef struct VektManifest VektManifest;
+
+/**
+ * A vekt repository: the directory containing `.vekt`
+ */
+typedef struct VektRepo VektRepo;
+
+/**
+ * Shape, dtype and size of a tensor. Pointers belong to the manifest handle.
+ */
+typedef struct VektTensorInfo {
+  /**
+   * safetensors dtype name, e.g. "F32" or "BF16"
+   */
+  const char *dtype;
+  size_t ndim;
+  const size_t *shape;
+  /**
+   * Size of the tensor's data in bytes
+   */
+  uint64_t nbytes;
+} VektTensorInfo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Library version, e.g. "0.1.0". The string is static.
+ */
+const char *vekt_version(void);
+
+/**
+ * Message for the last failed call on this thread, or "" after a successful one.
+ * Valid until the next vekt call on the same thread.
+ */
+const char *vekt_last_error(void);
+
+/**
+ * Opens the repository at `root` (the directory containing `.vekt`), or the one found
+ * from `VEKT_ROOT` or the working directory if `root` is NULL.
+ *
+ * # Safety
+ * `root` must be NULL or a NUL-terminated string; `out` must be valid for writes.
+ */
+enum VektStatus vekt_repo_open(const char *root, struct VektRepo **out);
+
+/**
+ * Releases a repository handle. Passing NULL is a no-op.
+ *
+ * # Safety
+ * `repo` must be NULL or a handle from `vekt_repo_open` not yet freed.
+ */
+void vekt_repo_free(struct VektRepo *repo);
+
+/**
+ * Archives the safetensors file at `path` into the repository and writes its manifest
+ * next to it as `<name>.vekt.json`. If `out` is not NULL it receives the manifest.
+ *
+ * # Safety
+ * `repo` must be a live handle, `path` a NUL-terminated string, and `out` NULL or
+ * valid for writes.
+ */
+enum VektStatus vekt_add(const struct VektRepo *repo, const char *path, struct VektManifest **out);
+
+/**
+ * Loads the manifest at `path`, reading its tensors from `repo`
+ *
+ * # Safety
+ * `repo` must be a live handle, `path` a NUL-terminated string, and `out` valid for
+ * writes.
+ */
+enum VektStatus vekt_manifest_open(const struct VektRepo *repo,
+                                   const char *path,
+                                   struct VektManifest **out);
+
+/**
+ * Releases a manifest handle and every string and shape obtained from it.
+ * Passing NULL is a no-op.
+ *
+ * # Safety
+ * `manifest` must be NULL or a handle from this library not yet freed.
+ */
+void vekt_manifest_free(struct VektManifest *manifest);
+
+/**
+ * Writes the safetensors file for `manifest` to `output`. `filter` (may be NULL)
+ * keeps tensors whose name contains any of its comma-separated terms.
+ *
+ * # Safety
+ * `manifest` must be a live handle, `output` a NUL-terminated string and `filter`
+ * NULL or a NUL-terminated string.
+ */
+enum VektStatus vekt_restore(const struct VektManifest *manifest,
+                             const char *output,
+                             const char *filter);
+
+/**
+ * Number of tensors in the manifest, 0 if `manifest` is NULL
+ *
+ * # Safety
+ * `manifest` must be NULL or a live handle.
+ */
+size_t vekt_manifest_tensor_count(const struct VektManifest *manifest);
+
+/**
+ * Name of the tensor at `index` in file order, or NULL if out of range
+ *
+ * # Safety
+ * `manifest` must be NULL or a live handle.
+ */
+const char *vekt_manifest_tensor_name(const struct VektManifest *manifest, size_t index);
+
+/**
+ * Fills `info` with the shape, dtype and size of tensor `name`
+ *
+ * # Safety
+ * `manifest` must be a live handle, `name` a NUL-terminated string and `info` valid
+ * for writes.
+ */
+enum VektStatus vekt_tensor_info(const struct VektManifest *manifest,
+                                 const char *name,
+                                 struct VektTensorInfo *info);
+
+/**
+ * Copies tensor `name`'s bytes into `buf` after verifying them against the manifest
+ * hash. If `buf_len` is too small nothing is copied, `VEKT_STATUS_BUFFER_TOO_SMALL` is
+ * returned and `written` (if not NULL) receives the size needed.
+ *
+ * # Safety
+ * `manifest` must be a live handle, `name` a NUL-terminated string, `buf` valid for
+ * `buf_len` bytes of writes and `written` NULL or valid for writes.
+ */
+enum VektStatus vekt_tensor_read(const struct VektManifest *manifest,
+                                 const char *name,
+                                 uint8_t *buf,
+                                 size_t buf_len,
+                                 size_t *written);
+
+/**
+ * Copies `len` bytes of tensor `name` starting at `offset` into `buf`, verifying only
+ * the part read when the blob has an outboard tree
+ *
+ * # Safety
+ * `manifest` must be a live handle, `name` a NUL-terminated string and `buf` valid for
+ * `len` bytes of writes.
+ */
+enum VektStatus vekt_tensor_read_range(const struct VektManifest *manifest,
+                                       const char *name,
+                                       uint64_t offset,
+                                       uint8_t *buf,
+                                       size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* VEKT_H */
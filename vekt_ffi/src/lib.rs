@@ -0,0 +1,508 @@
+//! C API for vekt, built as `libvekt` with the header in `include/vekt.h`.
+//!
+//! Repositories and manifests are opaque handles created by `*_open` functions and
+//! released with the matching `*_free`. Every fallible function returns a
+//! `VektStatus`; on failure `vekt_last_error` describes what went wrong. Strings are
+//! NUL-terminated UTF-8, and pointers returned by the library stay valid until the
+//! handle they came from is freed.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::fs::File;
+use std::io::Write;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::Path;
+use std::ptr;
+use vekt_core::errors::VektError;
+use vekt_core::progress::Hooks;
+use vekt_core::reader::TensorReader;
+use vekt_core::repository::Repository;
+use vekt_core::storage;
+use vekt_core::utils::get_dtype_size;
+use vekt_core::{ModelArchiver, SafetensorFile};
+
+/// Result of every fallible call. Values are part of the ABI and never renumbered.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VektStatus {
+    Ok = 0,
+    /// A required pointer was NULL or a string was not valid UTF-8
+    InvalidArgument = 1,
+    Io = 2,
+    RepoNotFound = 3,
+    /// Another vekt operation holds the repository lock
+    Locked = 4,
+    BlobNotFound = 5,
+    /// Stored data does not match its hash
+    Integrity = 6,
+    InvalidManifest = 7,
+    InvalidSafetensor = 8,
+    TensorNotFound = 9,
+    /// The caller's buffer is too small; the required size is reported
+    BufferTooSmall = 10,
+    OutOfRange = 11,
+    Remote = 12,
+    Cancelled = 13,
+    /// A config file or `VEKT_*` setting is invalid
+    InvalidConfig = 14,
+    /// A bug in vekt; the message has details
+    Internal = 99,
+}
+
+impl From<&VektError> for VektStatus {
+    fn from(error: &VektError) -> Self {
+        match error {
+            VektError::Io(_) => VektStatus::Io,
+            VektError::RepoNotFound => VektStatus::RepoNotFound,
+            VektError::LockExists | VektError::StaleLock(_) => VektStatus::Locked,
+            VektError::BlobNotFound(_) => VektStatus::BlobNotFound,
+            VektError::HashMismatch { .. }
+            | VektError::TensorCorruption(_)
+            | VektError::ManifestCorrupted(_) => VektStatus::Integrity,
+            VektError::Json(_) | VektError::InvalidManifest(_) | VektError::PathTraversal(_) => {
+                VektStatus::InvalidManifest
+            }
+            VektError::InvalidSafetensor(_) => VektStatus::InvalidSafetensor,
+            VektError::InvalidTensorName(_) => VektStatus::TensorNotFound,
+            VektError::OutOfRange(_) => VektStatus::OutOfRange,
+            VektError::RemoteError(_)
+            | VektError::InvalidRemoteUrl(_)
+            | VektError::RemoteNotFound(_)
+            | VektError::ReadOnlyRemote(_)
+            | VektError::Unsupported(_)
+            | VektError::RemoteUnavailable(_)
+            | VektError::CredentialError(_)
+            | VektError::ConflictDetected(_) => VektStatus::Remote,
+            VektError::Cancelled(_) => VektStatus::Cancelled,
+            VektError::InvalidConfig(_) => VektStatus::InvalidConfig,
+            _ => VektStatus::Internal,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Failure of an FFI call: a status and the message for `vekt_last_error`
+struct Failure(VektStatus, String);
+
+impl From<VektError> for Failure {
+    fn from(error: VektError) -> Self {
+        Failure((&error).into(), error.to_string())
+    }
+}
+
+impl From<std::io::Error> for Failure {
+    fn from(error: std::io::Error) -> Self {
+        VektError::Io(error).into()
+    }
+}
+
+fn invalid(message: impl Into<String>) -> Failure {
+    Failure(VektStatus::InvalidArgument, message.into())
+}
+
+/// Runs `body`, recording its error and turning panics into `Internal`
+fn ffi(body: impl FnOnce() -> Result<(), Failure>) -> VektStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => {
+            set_last_error("");
+            VektStatus::Ok
+        }
+        Ok(Err(Failure(status, message))) => {
+            set_last_error(&message);
+            status
+        }
+        Err(_) => {
+            set_last_error("vekt panicked; this is a bug");
+            VektStatus::Internal
+        }
+    }
+}
+
+/// Borrows a C string argument
+///
+/// # Safety
+/// `s` must be NULL or point to a NUL-terminated string.
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str, Failure> {
+    if s.is_null() {
+        return Err(invalid(format!("{} is NULL", name)));
+    }
+    // SAFETY: non-NULL and NUL-terminated per the caller's contract
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|_| invalid(format!("{} is not valid UTF-8", name)))
+}
+
+/// Borrows an optional C string argument, NULL meaning none
+///
+/// # Safety
+/// As `str_arg`.
+unsafe fn opt_str_arg<'a>(s: *const c_char, name: &str) -> Result<Option<&'a str>, Failure> {
+    if s.is_null() {
+        Ok(None)
+    } else {
+        unsafe { str_arg(s, name) }.map(Some)
+    }
+}
+
+/// Borrows a handle argument
+///
+/// # Safety
+/// `handle` must be NULL or a live handle of type `T`.
+unsafe fn handle_arg<'a, T>(handle: *const T, name: &str) -> Result<&'a T, Failure> {
+    // SAFETY: live or NULL per the caller's contract
+    unsafe { handle.as_ref() }.ok_or_else(|| invalid(format!("{} is NULL", name)))
+}
+
+/// Writes `value` to an out-parameter
+///
+/// # Safety
+/// `out` must be NULL or valid for writes.
+unsafe fn set_out<T>(out: *mut T, value: T, name: &str) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(invalid(format!("{} is NULL", name)));
+    }
+    // SAFETY: non-NULL and writable per the caller's contract
+    unsafe { out.write(value) };
+    Ok(())
+}
+
+/// A vekt repository: the directory containing `.vekt`
+pub struct VektRepo {
+    repo: Repository,
+}
+
+/// A loaded manifest, reading tensors from its repository's blob store
+pub struct VektManifest {
+    repo: Repository,
+    reader: TensorReader,
+    /// Tensors in file order, with NUL-terminated copies of their names and dtypes
+    tensors: Vec<TensorEntry>,
+}
+
+struct TensorEntry {
+    name: CString,
+    dtype: CString,
+    shape: Vec<usize>,
+    nbytes: u64,
+}
+
+impl VektManifest {
+    fn new(manifest: storage::VektManifest, repo: &Repository) -> Result<Self, Failure> {
+        let tensors = manifest
+            .select_tensors(None)
+            .into_iter()
+            .map(|name| {
+                let tensor = &manifest.tensors[name];
+                Ok(TensorEntry {
+                    name: CString::new(name.as_str())
+                        .map_err(|_| invalid(format!("tensor name '{}' contains NUL", name)))?,
+                    dtype: CString::new(tensor.dtype.as_str())
+                        .map_err(|_| invalid(format!("dtype of '{}' contains NUL", name)))?,
+                    shape: tensor.shape.clone(),
+                    nbytes: (tensor.shape.iter().product::<usize>() * get_dtype_size(&tensor.dtype))
+                        as u64,
+                })
+            })
+            .collect::<Result<_, Failure>>()?;
+        Ok(Self {
+            repo: repo.clone(),
+            reader: TensorReader::new(repo, manifest),
+            tensors,
+        })
+    }
+
+    fn entry(&self, name: &str) -> Result<&TensorEntry, Failure> {
+        self.tensors
+            .iter()
+            .find(|entry| entry.name.as_bytes() == name.as_bytes())
+            .ok_or_else(|| {
+                Failure(
+                    VektStatus::TensorNotFound,
+                    format!("no tensor named '{}'", name),
+                )
+            })
+    }
+}
+
+/// Shape, dtype and size of a tensor. Pointers belong to the manifest handle.
+#[repr(C)]
+pub struct VektTensorInfo {
+    /// safetensors dtype name, e.g. "F32" or "BF16"
+    pub dtype: *const c_char,
+    pub ndim: usize,
+    pub shape: *const usize,
+    /// Size of the tensor's data in bytes
+    pub nbytes: u64,
+}
+
+/// Library version, e.g. "0.1.0". The string is static.
+#[unsafe(no_mangle)]
+pub extern "C" fn vekt_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Message for the last failed call on this thread, or "" after a successful one.
+/// Valid until the next vekt call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn vekt_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Opens the repository at `root` (the directory containing `.vekt`), or the one found
+/// from `VEKT_ROOT` or the working directory if `root` is NULL.
+///
+/// # Safety
+/// `root` must be NULL or a NUL-terminated string; `out` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_repo_open(
+    root: *const c_char,
+    out: *mut *mut VektRepo,
+) -> VektStatus {
+    ffi(|| {
+        let repo = match unsafe { opt_str_arg(root, "root")? } {
+            Some(root) => Repository::open(root)?,
+            None => Repository::discover()?,
+        };
+        let repo = VektRepo { repo };
+        unsafe { set_out(out, Box::into_raw(Box::new(repo)), "out") }
+    })
+}
+
+/// Releases a repository handle. Passing NULL is a no-op.
+///
+/// # Safety
+/// `repo` must be NULL or a handle from `vekt_repo_open` not yet freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_repo_free(repo: *mut VektRepo) {
+    if !repo.is_null() {
+        // SAFETY: created by Box::into_raw in vekt_repo_open
+        drop(unsafe { Box::from_raw(repo) });
+    }
+}
+
+/// Archives the safetensors file at `path` into the repository and writes its manifest
+/// next to it as `<name>.vekt.json`. If `out` is not NULL it receives the manifest.
+///
+/// # Safety
+/// `repo` must be a live handle, `path` a NUL-terminated string, and `out` NULL or
+/// valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_add(
+    repo: *const VektRepo,
+    path: *const c_char,
+    out: *mut *mut VektManifest,
+) -> VektStatus {
+    ffi(|| {
+        let repo = &unsafe { handle_arg(repo, "repo")? }.repo;
+        let path = unsafe { str_arg(path, "path")? };
+
+        let _lock = repo.lock()?;
+        let manifest = SafetensorFile::open(path)?.process(repo, true)?;
+        let manifest_path = Path::new(path).with_extension("vekt.json");
+        File::create(&manifest_path)?.write_all(
+            serde_json::to_string_pretty(&manifest)
+                .map_err(VektError::from)?
+                .as_bytes(),
+        )?;
+
+        if !out.is_null() {
+            let handle = VektManifest::new(manifest, repo)?;
+            unsafe { set_out(out, Box::into_raw(Box::new(handle)), "out")? };
+        }
+        Ok(())
+    })
+}
+
+/// Loads the manifest at `path`, reading its tensors from `repo`
+///
+/// # Safety
+/// `repo` must be a live handle, `path` a NUL-terminated string, and `out` valid for
+/// writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_manifest_open(
+    repo: *const VektRepo,
+    path: *const c_char,
+    out: *mut *mut VektManifest,
+) -> VektStatus {
+    ffi(|| {
+        let repo = &unsafe { handle_arg(repo, "repo")? }.repo;
+        let path = unsafe { str_arg(path, "path")? };
+        let manifest = storage::VektManifest::load(Path::new(path))?;
+        let handle = VektManifest::new(manifest, repo)?;
+        unsafe { set_out(out, Box::into_raw(Box::new(handle)), "out") }
+    })
+}
+
+/// Releases a manifest handle and every string and shape obtained from it.
+/// Passing NULL is a no-op.
+///
+/// # Safety
+/// `manifest` must be NULL or a handle from this library not yet freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_manifest_free(manifest: *mut VektManifest) {
+    if !manifest.is_null() {
+        // SAFETY: created by Box::into_raw in vekt_add or vekt_manifest_open
+        drop(unsafe { Box::from_raw(manifest) });
+    }
+}
+
+/// Writes the safetensors file for `manifest` to `output`. `filter` (may be NULL)
+/// keeps tensors whose name contains any of its comma-separated terms.
+///
+/// # Safety
+/// `manifest` must be a live handle, `output` a NUL-terminated string and `filter`
+/// NULL or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_restore(
+    manifest: *const VektManifest,
+    output: *const c_char,
+    filter: *const c_char,
+) -> VektStatus {
+    ffi(|| {
+        let manifest = unsafe { handle_arg(manifest, "manifest")? };
+        let output = unsafe { str_arg(output, "output")? };
+        let filter = unsafe { opt_str_arg(filter, "filter")? };
+        manifest.reader.manifest().restore_with(
+            &manifest.repo,
+            Path::new(output),
+            filter,
+            &Hooks::default(),
+        )?;
+        Ok(())
+    })
+}
+
+/// Number of tensors in the manifest, 0 if `manifest` is NULL
+///
+/// # Safety
+/// `manifest` must be NULL or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_manifest_tensor_count(manifest: *const VektManifest) -> usize {
+    unsafe { manifest.as_ref() }.map_or(0, |m| m.tensors.len())
+}
+
+/// Name of the tensor at `index` in file order, or NULL if out of range
+///
+/// # Safety
+/// `manifest` must be NULL or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_manifest_tensor_name(
+    manifest: *const VektManifest,
+    index: usize,
+) -> *const c_char {
+    unsafe { manifest.as_ref() }
+        .and_then(|m| m.tensors.get(index))
+        .map_or(ptr::null(), |entry| entry.name.as_ptr())
+}
+
+/// Fills `info` with the shape, dtype and size of tensor `name`
+///
+/// # Safety
+/// `manifest` must be a live handle, `name` a NUL-terminated string and `info` valid
+/// for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_tensor_info(
+    manifest: *const VektManifest,
+    name: *const c_char,
+    info: *mut VektTensorInfo,
+) -> VektStatus {
+    ffi(|| {
+        let manifest = unsafe { handle_arg(manifest, "manifest")? };
+        let entry = manifest.entry(unsafe { str_arg(name, "name")? })?;
+        let value = VektTensorInfo {
+            dtype: entry.dtype.as_ptr(),
+            ndim: entry.shape.len(),
+            shape: entry.shape.as_ptr(),
+            nbytes: entry.nbytes,
+        };
+        unsafe { set_out(info, value, "info") }
+    })
+}
+
+/// Copies tensor `name`'s bytes into `buf` after verifying them against the manifest
+/// hash. If `buf_len` is too small nothing is copied, `VEKT_STATUS_BUFFER_TOO_SMALL` is
+/// returned and `written` (if not NULL) receives the size needed.
+///
+/// # Safety
+/// `manifest` must be a live handle, `name` a NUL-terminated string, `buf` valid for
+/// `buf_len` bytes of writes and `written` NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_tensor_read(
+    manifest: *const VektManifest,
+    name: *const c_char,
+    buf: *mut u8,
+    buf_len: usize,
+    written: *mut usize,
+) -> VektStatus {
+    ffi(|| {
+        let manifest = unsafe { handle_arg(manifest, "manifest")? };
+        let name = unsafe { str_arg(name, "name")? };
+        let entry = manifest.entry(name)?;
+        let needed = entry.nbytes as usize;
+        if !written.is_null() {
+            unsafe { written.write(0) };
+        }
+        if buf_len < needed {
+            if !written.is_null() {
+                unsafe { written.write(needed) };
+            }
+            return Err(Failure(
+                VektStatus::BufferTooSmall,
+                format!(
+                    "tensor '{}' needs {} bytes, buffer has {}",
+                    name, needed, buf_len
+                ),
+            ));
+        }
+        if buf.is_null() && needed > 0 {
+            return Err(invalid("buf is NULL"));
+        }
+
+        let tensor = manifest.reader.tensor(name)?;
+        let bytes = tensor.bytes()?;
+        // SAFETY: buf holds at least `needed` == bytes.len() writable bytes
+        unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), buf, bytes.len()) };
+        if !written.is_null() {
+            unsafe { written.write(bytes.len()) };
+        }
+        Ok(())
+    })
+}
+
+/// Copies `len` bytes of tensor `name` starting at `offset` into `buf`, verifying only
+/// the part read when the blob has an outboard tree
+///
+/// # Safety
+/// `manifest` must be a live handle, `name` a NUL-terminated string and `buf` valid for
+/// `len` bytes of writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn vekt_tensor_read_range(
+    manifest: *const VektManifest,
+    name: *const c_char,
+    offset: u64,
+    buf: *mut u8,
+    len: usize,
+) -> VektStatus {
+    ffi(|| {
+        let manifest = unsafe { handle_arg(manifest, "manifest")? };
+        let name = unsafe { str_arg(name, "name")? };
+        if buf.is_null() && len > 0 {
+            return Err(invalid("buf is NULL"));
+        }
+        let end = offset
+            .checked_add(len as u64)
+            .ok_or_else(|| VektError::OutOfRange(format!("{} + {} overflows", offset, len)))?;
+        let data = manifest.reader.read_range(name, offset..end)?;
+        // SAFETY: read_range returned exactly `len` bytes and buf holds `len`
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), buf, data.len()) };
+        Ok(())
+    })
+}
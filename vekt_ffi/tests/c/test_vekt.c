@@ -0,0 +1,105 @@
+/* Exercises the C API end to end. Run from an empty directory containing `.vekt`;
+ * built and run by tests/c_api.rs. */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "vekt.h"
+
+#define CHECK(call)                                                              \
+    do {                                                                         \
+        VektStatus status_ = (call);                                             \
+        if (status_ != VEKT_STATUS_OK) {                                         \
+            fprintf(stderr, "%s:%d: %s failed with %d: %s\n", __FILE__, __LINE__, \
+                    #call, (int)status_, vekt_last_error());                     \
+            return 1;                                                            \
+        }                                                                        \
+    } while (0)
+
+#define EXPECT(cond)                                                             \
+    do {                                                                         \
+        if (!(cond)) {                                                           \
+            fprintf(stderr, "%s:%d: expected %s\n", __FILE__, __LINE__, #cond);  \
+            return 1;                                                            \
+        }                                                                        \
+    } while (0)
+
+static const float WEIGHT[4] = {1.0f, 2.0f, 3.0f, 4.0f};
+static const unsigned char BIAS[2] = {7, 8};
+
+static int write_model(const char *path) {
+    const char *header =
+        "{\"weight\":{\"dtype\":\"F32\",\"shape\":[2,2],\"data_offsets\":[0,16]},"
+        "\"bias\":{\"dtype\":\"U8\",\"shape\":[2],\"data_offsets\":[16,18]}}";
+    unsigned long long len = strlen(header);
+    unsigned char len_le[8];
+    int i;
+    FILE *f = fopen(path, "wb");
+    if (!f) {
+        return 1;
+    }
+    for (i = 0; i < 8; i++) {
+        len_le[i] = (unsigned char)(len >> (8 * i));
+    }
+    fwrite(len_le, 1, 8, f);
+    fwrite(header, 1, len, f);
+    fwrite(WEIGHT, 1, sizeof WEIGHT, f); /* assumes a little-endian host */
+    fwrite(BIAS, 1, sizeof BIAS, f);
+    fclose(f);
+    return 0;
+}
+
+int main(void) {
+    VektRepo *repo = NULL;
+    VektManifest *added = NULL;
+    VektManifest *manifest = NULL;
+    VektTensorInfo info;
+    float weight[4];
+    unsigned char small[1];
+    size_t written = 0;
+
+    EXPECT(strlen(vekt_version()) > 0);
+    EXPECT(write_model("model.safetensors") == 0);
+
+    CHECK(vekt_repo_open(".", &repo));
+    CHECK(vekt_add(repo, "model.safetensors", &added));
+    EXPECT(vekt_manifest_tensor_count(added) == 2);
+    vekt_manifest_free(added);
+
+    CHECK(vekt_manifest_open(repo, "model.vekt.json", &manifest));
+    EXPECT(vekt_manifest_tensor_count(manifest) == 2);
+    EXPECT(strcmp(vekt_manifest_tensor_name(manifest, 0), "weight") == 0);
+    EXPECT(strcmp(vekt_manifest_tensor_name(manifest, 1), "bias") == 0);
+    EXPECT(vekt_manifest_tensor_name(manifest, 2) == NULL);
+
+    CHECK(vekt_tensor_info(manifest, "weight", &info));
+    EXPECT(strcmp(info.dtype, "F32") == 0);
+    EXPECT(info.ndim == 2 && info.shape[0] == 2 && info.shape[1] == 2);
+    EXPECT(info.nbytes == sizeof weight);
+
+    CHECK(vekt_tensor_read(manifest, "weight", (uint8_t *)weight, sizeof weight, &written));
+    EXPECT(written == sizeof weight);
+    EXPECT(memcmp(weight, WEIGHT, sizeof weight) == 0);
+
+    CHECK(vekt_tensor_read_range(manifest, "weight", 4, (uint8_t *)weight, 8));
+    EXPECT(weight[0] == 2.0f && weight[1] == 3.0f);
+
+    EXPECT(vekt_tensor_read(manifest, "bias", small, sizeof small, &written) ==
+           VEKT_STATUS_BUFFER_TOO_SMALL);
+    EXPECT(written == 2);
+    EXPECT(vekt_tensor_info(manifest, "missing", &info) == VEKT_STATUS_TENSOR_NOT_FOUND);
+    EXPECT(strstr(vekt_last_error(), "missing") != NULL);
+    EXPECT(vekt_tensor_read(NULL, "bias", small, sizeof small, NULL) ==
+           VEKT_STATUS_INVALID_ARGUMENT);
+
+    CHECK(vekt_restore(manifest, "restored.safetensors", NULL));
+    EXPECT(vekt_manifest_open(repo, "nope.vekt.json", &added) == VEKT_STATUS_IO);
+
+    vekt_manifest_free(manifest);
+    vekt_repo_free(repo);
+    EXPECT(vekt_repo_open("does-not-exist", &repo) == VEKT_STATUS_REPO_NOT_FOUND);
+
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,63 @@
+//! Compiles tests/c/test_vekt.c against the generated header and the built library,
+//! then runs it in a fresh repository.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn test_c_program_against_the_library() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Integration tests live in target/<profile>/deps, next to which the cdylib is built
+    let lib_dir = env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(|deps| deps.parent())
+        .unwrap()
+        .to_path_buf();
+
+    let work = env::temp_dir().join(format!("vekt_ffi_test_{}", rand_suffix()));
+    fs::create_dir_all(work.join(".vekt")).unwrap();
+    let exe = work.join("test_vekt");
+
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&compiler)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg(crate_dir.join("tests/c/test_vekt.c"))
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lvekt")
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .unwrap_or_else(|e| panic!("failed to run {}: {}", compiler, e));
+    assert!(status.success(), "compiling the C test failed");
+
+    let output = Command::new(&exe)
+        .current_dir(&work)
+        .env_remove("VEKT_ROOT")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "C test failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(work.join("restored.safetensors").exists());
+
+    fs::remove_dir_all(work).ok();
+}
+
+fn rand_suffix() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos()
+}
@@ -0,0 +1,90 @@
+"""Archive, restore and load ML models tracked by vekt.
+
+Blobs are stored in and read from the vekt repository found from the current
+directory, or from ``VEKT_ROOT`` if it is set, exactly as the ``vekt`` command does.
+Pass ``repo`` (the directory containing ``.vekt``) to use another repository.
+"""
+
+from collections import namedtuple
+
+from ._vekt import (
+    BlobNotFoundError,
+    CancelledError,
+    ConfigError,
+    IntegrityError,
+    InvalidManifestError,
+    LockError,
+    OutOfRangeError,
+    RemoteError,
+    RepoNotFoundError,
+    VektError,
+    add,
+    diff,
+    load_raw,
+    restore,
+)
+
+__all__ = [
+    "BlobNotFoundError",
+    "CancelledError",
+    "ConfigError",
+    "IntegrityError",
+    "InvalidManifestError",
+    "LockError",
+    "OutOfRangeError",
+    "RemoteError",
+    "RepoNotFoundError",
+    "Tensor",
+    "VektError",
+    "add",
+    "diff",
+    "load",
+    "load_raw",
+    "restore",
+]
+
+#: A tensor NumPy has no dtype for (or loaded without NumPy): safetensors dtype name,
+#: shape and little-endian bytes
+Tensor = namedtuple("Tensor", ["dtype", "shape", "data"])
+
+# safetensors dtype -> NumPy dtype; BF16 and the F8 types have no NumPy equivalent
+_NUMPY_DTYPES = {
+    "F64": "<f8",
+    "F32": "<f4",
+    "F16": "<f2",
+    "I64": "<i8",
+    "I32": "<i4",
+    "I16": "<i2",
+    "I8": "i1",
+    "U64": "<u8",
+    "U32": "<u4",
+    "U16": "<u2",
+    "U8": "u1",
+    "BOOL": "?",
+}
+
+
+def load(manifest, filter=None, numpy=None, repo=None):
+    """Loads the tensors of ``manifest`` without writing a safetensors file.
+
+    Returns a dict from tensor name to a read-only NumPy array, in file order. Tensors
+    whose dtype NumPy lacks (such as BF16) are returned as :class:`Tensor` instead, as is
+    everything when ``numpy`` is False or NumPy is not installed. ``filter`` keeps
+    tensors whose name contains any of its comma-separated terms.
+    """
+    raw = load_raw(manifest, filter, repo)
+    np = None
+    if numpy is not False:
+        try:
+            import numpy as np
+        except ImportError:
+            if numpy:
+                raise
+
+    tensors = {}
+    for name, (dtype, shape, data) in raw.items():
+        if np is not None and dtype in _NUMPY_DTYPES:
+            tensors[name] = np.frombuffer(data, dtype=_NUMPY_DTYPES[dtype]).reshape(shape)
+        else:
+            tensors[name] = Tensor(dtype, tuple(shape), data)
+    return tensors
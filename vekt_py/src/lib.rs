@@ -0,0 +1,249 @@
+//! Python bindings for vekt, imported as `vekt._vekt` and wrapped by the `vekt` package.
+//!
+//! Hashing and file IO run with the GIL released; `VektError` variants surface as
+//! subclasses of `vekt.VektError`, and IO errors as the matching `OSError`.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use vekt_core::errors;
+use vekt_core::reader::TensorReader;
+use vekt_core::repository::Repository;
+use vekt_core::storage::VektManifest;
+use vekt_core::{ModelArchiver, SafetensorFile};
+
+create_exception!(vekt, VektError, PyException, "Base class for vekt errors");
+create_exception!(
+    vekt,
+    RepoNotFoundError,
+    VektError,
+    "No vekt repository here; run `vekt init`"
+);
+create_exception!(
+    vekt,
+    LockError,
+    VektError,
+    "Another vekt operation holds the repository lock"
+);
+create_exception!(
+    vekt,
+    BlobNotFoundError,
+    VektError,
+    "A blob is missing from the store"
+);
+create_exception!(
+    vekt,
+    IntegrityError,
+    VektError,
+    "Stored data does not match its hash"
+);
+create_exception!(
+    vekt,
+    InvalidManifestError,
+    VektError,
+    "A manifest or safetensors file is malformed"
+);
+create_exception!(vekt, RemoteError, VektError, "A remote operation failed");
+create_exception!(
+    vekt,
+    OutOfRangeError,
+    VektError,
+    "A requested range lies outside the tensor"
+);
+create_exception!(
+    vekt,
+    CancelledError,
+    VektError,
+    "The operation was cancelled"
+);
+create_exception!(
+    vekt,
+    ConfigError,
+    VektError,
+    "A config file or VEKT_* setting is invalid"
+);
+
+fn to_py_err(error: errors::VektError) -> PyErr {
+    let message = error.to_string();
+    match error {
+        // Keeps FileNotFoundError, PermissionError and friends
+        errors::VektError::Io(e) => e.into(),
+        errors::VektError::RepoNotFound => RepoNotFoundError::new_err(message),
+        errors::VektError::LockExists | errors::VektError::StaleLock(_) => {
+            LockError::new_err(message)
+        }
+        errors::VektError::BlobNotFound(_) => BlobNotFoundError::new_err(message),
+        errors::VektError::HashMismatch { .. }
+        | errors::VektError::TensorCorruption(_)
+        | errors::VektError::ManifestCorrupted(_) => IntegrityError::new_err(message),
+        errors::VektError::Json(_)
+        | errors::VektError::InvalidManifest(_)
+        | errors::VektError::InvalidSafetensor(_)
+        | errors::VektError::InvalidTensorName(_)
+        | errors::VektError::PathTraversal(_) => InvalidManifestError::new_err(message),
+        errors::VektError::RemoteError(_)
+        | errors::VektError::InvalidRemoteUrl(_)
+        | errors::VektError::RemoteNotFound(_)
+        | errors::VektError::ReadOnlyRemote(_)
+        | errors::VektError::RemoteUnavailable(_)
+        | errors::VektError::CredentialError(_)
+        | errors::VektError::ConflictDetected(_) => RemoteError::new_err(message),
+        errors::VektError::OutOfRange(_) => OutOfRangeError::new_err(message),
+        errors::VektError::Cancelled(_) => CancelledError::new_err(message),
+        errors::VektError::InvalidConfig(_) => ConfigError::new_err(message),
+        _ => VektError::new_err(message),
+    }
+}
+
+/// Runs `work` with the GIL released, converting its error
+fn without_gil<T: Send>(
+    py: Python<'_>,
+    work: impl FnOnce() -> errors::Result<T> + Send,
+) -> PyResult<T> {
+    py.allow_threads(work).map_err(to_py_err)
+}
+
+/// The repository rooted at `root`, or the one found from the working directory
+fn open_repo(root: Option<PathBuf>) -> errors::Result<Repository> {
+    match root {
+        Some(root) => Repository::open(root),
+        None => Repository::discover(),
+    }
+}
+
+/// Archives a safetensors file into the store and writes its manifest next to it.
+/// Returns the manifest path.
+#[pyfunction]
+#[pyo3(signature = (path, repo=None))]
+fn add(py: Python<'_>, path: PathBuf, repo: Option<PathBuf>) -> PyResult<PathBuf> {
+    without_gil(py, || {
+        let repo = open_repo(repo)?;
+        let _lock = repo.lock()?;
+        let path_str = path.to_str().ok_or_else(|| {
+            errors::VektError::InvalidSafetensor(format!("{} is not valid UTF-8", path.display()))
+        })?;
+        let manifest = SafetensorFile::open(path_str)?.process(&repo, true)?;
+
+        let output_path = path.with_extension("vekt.json");
+        let mut output_file = File::create(&output_path)?;
+        output_file.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+        Ok(output_path)
+    })
+}
+
+/// Rebuilds the safetensors file for `manifest`, by default next to it.
+/// `filter` keeps tensors whose name contains any of its comma-separated terms.
+#[pyfunction]
+#[pyo3(signature = (manifest, output=None, filter=None, repo=None))]
+fn restore(
+    py: Python<'_>,
+    manifest: PathBuf,
+    output: Option<PathBuf>,
+    filter: Option<String>,
+    repo: Option<PathBuf>,
+) -> PyResult<PathBuf> {
+    without_gil(py, || {
+        let repo = open_repo(repo)?;
+        let output = output.unwrap_or_else(|| default_output(&manifest));
+        VektManifest::load(&manifest)?.restore(&repo, &output, filter.as_deref())?;
+        Ok(output)
+    })
+}
+
+fn default_output(manifest: &Path) -> PathBuf {
+    let name = manifest
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stem = name.strip_suffix(".vekt.json").unwrap_or(&name);
+    manifest.with_file_name(format!("{}.safetensors", stem))
+}
+
+/// Compares two manifests by tensor hash
+#[pyfunction]
+fn diff<'py>(py: Python<'py>, old: PathBuf, new: PathBuf) -> PyResult<Bound<'py, PyDict>> {
+    let comparison = without_gil(py, || {
+        Ok(VektManifest::load(&old)?.diff(&VektManifest::load(&new)?))
+    })?;
+
+    let mut tensors = comparison.tensor_diff;
+    for names in [
+        &mut tensors.added,
+        &mut tensors.removed,
+        &mut tensors.modified,
+        &mut tensors.unchanged,
+    ] {
+        names.sort();
+    }
+    let savings = comparison.storage_savings;
+
+    let result = PyDict::new(py);
+    result.set_item("added", tensors.added)?;
+    result.set_item("removed", tensors.removed)?;
+    result.set_item("modified", tensors.modified)?;
+    result.set_item("unchanged", tensors.unchanged)?;
+    result.set_item("size_change", comparison.size_change)?;
+    result.set_item("shared_blobs", savings.shared_blobs)?;
+    result.set_item("deduplication_ratio", savings.deduplication_ratio)?;
+    Ok(result)
+}
+
+/// Reads the tensors of `manifest` matching `filter` straight from the store, verifying
+/// each against its hash. Returns `{name: (dtype, shape, bytes)}` in file order.
+#[pyfunction]
+#[pyo3(signature = (manifest, filter=None, repo=None))]
+fn load_raw<'py>(
+    py: Python<'py>,
+    manifest: PathBuf,
+    filter: Option<String>,
+    repo: Option<PathBuf>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let tensors = without_gil(py, || {
+        let reader = TensorReader::open(&open_repo(repo)?, &manifest)?;
+        reader
+            .manifest()
+            .select_tensors(filter.as_deref())
+            .into_iter()
+            .map(|name| {
+                let tensor = reader.tensor(name)?;
+                tensor.verify()?;
+                Ok(tensor)
+            })
+            .collect::<errors::Result<Vec<_>>>()
+    })?;
+
+    let result = PyDict::new(py);
+    for tensor in tensors {
+        let data = PyBytes::new(py, tensor.bytes_unverified());
+        result.set_item(&tensor.name, (&tensor.dtype, &tensor.shape, data))?;
+    }
+    Ok(result)
+}
+
+#[pymodule]
+fn _vekt(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("VektError", py.get_type::<VektError>())?;
+    m.add("RepoNotFoundError", py.get_type::<RepoNotFoundError>())?;
+    m.add("LockError", py.get_type::<LockError>())?;
+    m.add("BlobNotFoundError", py.get_type::<BlobNotFoundError>())?;
+    m.add("IntegrityError", py.get_type::<IntegrityError>())?;
+    m.add(
+        "InvalidManifestError",
+        py.get_type::<InvalidManifestError>(),
+    )?;
+    m.add("RemoteError", py.get_type::<RemoteError>())?;
+    m.add("OutOfRangeError", py.get_type::<OutOfRangeError>())?;
+    m.add("CancelledError", py.get_type::<CancelledError>())?;
+    m.add("ConfigError", py.get_type::<ConfigError>())?;
+
+    m.add_function(wrap_pyfunction!(add, m)?)?;
+    m.add_function(wrap_pyfunction!(restore, m)?)?;
+    m.add_function(wrap_pyfunction!(diff, m)?)?;
+    m.add_function(wrap_pyfunction!(load_raw, m)?)?;
+    Ok(())
+}
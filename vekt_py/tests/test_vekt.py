@@ -0,0 +1,73 @@
+"""Run after `maturin develop` with `python -m unittest discover vekt_py/tests`."""
+
+import json
+import os
+import struct
+import tempfile
+import unittest
+
+import vekt
+
+
+def write_safetensors(path, tensors):
+    header, data = {}, b""
+    for name, (dtype, shape, raw) in tensors.items():
+        header[name] = {"dtype": dtype, "shape": shape, "data_offsets": [len(data), len(data) + len(raw)]}
+        data += raw
+    encoded = json.dumps(header).encode()
+    with open(path, "wb") as f:
+        f.write(struct.pack("<Q", len(encoded)) + encoded + data)
+
+
+class VektTest(unittest.TestCase):
+    def setUp(self):
+        self.cwd = os.getcwd()
+        self.dir = tempfile.TemporaryDirectory()
+        os.chdir(self.dir.name)
+        os.mkdir(".vekt")
+        self.weight = struct.pack("<4f", 1, 2, 3, 4)
+        write_safetensors(
+            "model.safetensors",
+            {"w": ("F32", [2, 2], self.weight), "b": ("BF16", [2], b"\x01\x02\x03\x04")},
+        )
+        self.manifest = vekt.add("model.safetensors")
+
+    def tearDown(self):
+        os.chdir(self.cwd)
+        self.dir.cleanup()
+
+    def test_load_without_numpy(self):
+        tensors = vekt.load(self.manifest, numpy=False)
+        self.assertEqual(list(tensors), ["w", "b"])
+        self.assertEqual(tensors["w"], vekt.Tensor("F32", (2, 2), self.weight))
+        self.assertEqual(list(vekt.load(self.manifest, filter="b", numpy=False)), ["b"])
+
+    def test_restore_and_diff(self):
+        restored = vekt.restore(self.manifest, output="restored.safetensors")
+        with open(restored, "rb") as f:
+            self.assertTrue(f.read().endswith(self.weight + b"\x01\x02\x03\x04"))
+        self.assertEqual(vekt.diff(self.manifest, self.manifest)["unchanged"], ["b", "w"])
+
+    def test_errors(self):
+        with self.assertRaises(FileNotFoundError):
+            vekt.load("missing.vekt.json")
+        for blob in os.listdir(".vekt/blobs"):
+            os.remove(os.path.join(".vekt/blobs", blob))
+        with self.assertRaises(vekt.BlobNotFoundError) as caught:
+            vekt.load(self.manifest)
+        self.assertIsInstance(caught.exception, vekt.VektError)
+
+    def test_explicit_repo(self):
+        with tempfile.TemporaryDirectory() as other:
+            os.mkdir(os.path.join(other, ".vekt"))
+            with self.assertRaises(vekt.BlobNotFoundError):
+                vekt.load(self.manifest, repo=other)
+            vekt.add("model.safetensors", repo=other)
+            self.assertEqual(len(os.listdir(os.path.join(other, ".vekt", "blobs"))), 2)
+            self.assertIn("w", vekt.load(self.manifest, numpy=False, repo=other))
+        with self.assertRaises(vekt.RepoNotFoundError):
+            vekt.load(self.manifest, repo=self.dir.name + "/nowhere")
+
+
+if __name__ == "__main__":
+    unittest.main()